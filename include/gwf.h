@@ -0,0 +1,275 @@
+#ifndef GWF_H
+#define GWF_H
+
+/* Generated by cbindgen from src/capi.rs; do not edit. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/*
+ GwfStatus is the result of every call.
+ */
+enum GwfStatus
+#if defined(__cplusplus) || __STDC_VERSION__ >= 202311L
+  : int32_t
+#endif // defined(__cplusplus) || __STDC_VERSION__ >= 202311L
+ {
+  GWF_STATUS_OK = 0,
+  /*
+   InvalidArgument is a null pointer, a bad index or a bad path.
+   */
+  GWF_STATUS_INVALID_ARGUMENT = 1,
+  /*
+   NotFound is a missing file or channel.
+   */
+  GWF_STATUS_NOT_FOUND = 2,
+  /*
+   InvalidData is a file that cannot be parsed.
+   */
+  GWF_STATUS_INVALID_DATA = 3,
+  /*
+   Io is any other failure to read the file.
+   */
+  GWF_STATUS_IO = 4,
+  /*
+   BufferTooSmall is a buffer shorter than the data; the length
+   needed is still reported.
+   */
+  GWF_STATUS_BUFFER_TOO_SMALL = 5,
+  /*
+   Unsupported is data that cannot be read as asked, e.g. strings.
+   */
+  GWF_STATUS_UNSUPPORTED = 6,
+  /*
+   Internal is a bug in the library.
+   */
+  GWF_STATUS_INTERNAL = 7,
+};
+#ifndef __cplusplus
+#if __STDC_VERSION__ >= 202311L
+typedef enum GwfStatus GwfStatus;
+#else
+typedef int32_t GwfStatus;
+#endif // __STDC_VERSION__ >= 202311L
+#endif // __cplusplus
+
+/*
+ GwfKind is the structure type of a channel.
+ */
+enum GwfKind
+#if defined(__cplusplus) || __STDC_VERSION__ >= 202311L
+  : int32_t
+#endif // defined(__cplusplus) || __STDC_VERSION__ >= 202311L
+ {
+  GWF_KIND_ADC = 0,
+  GWF_KIND_POST_PROCESSED = 1,
+  GWF_KIND_SIMULATION = 2,
+  GWF_KIND_SERIAL = 3,
+};
+#ifndef __cplusplus
+#if __STDC_VERSION__ >= 202311L
+typedef enum GwfKind GwfKind;
+#else
+typedef int32_t GwfKind;
+#endif // __STDC_VERSION__ >= 202311L
+#endif // __cplusplus
+
+/*
+ GwfType is the sample type of a channel.  Complex samples are pairs of
+ doubles, the real part first.
+ */
+enum GwfType
+#if defined(__cplusplus) || __STDC_VERSION__ >= 202311L
+  : int32_t
+#endif // defined(__cplusplus) || __STDC_VERSION__ >= 202311L
+ {
+  GWF_TYPE_I8 = 0,
+  GWF_TYPE_U8 = 1,
+  GWF_TYPE_I16 = 2,
+  GWF_TYPE_U16 = 3,
+  GWF_TYPE_I32 = 4,
+  GWF_TYPE_U32 = 5,
+  GWF_TYPE_U64 = 6,
+  GWF_TYPE_I64 = 7,
+  GWF_TYPE_F32 = 8,
+  GWF_TYPE_F64 = 9,
+  GWF_TYPE_STRING = 10,
+  GWF_TYPE_COMPLEX128 = 11,
+};
+#ifndef __cplusplus
+#if __STDC_VERSION__ >= 202311L
+typedef enum GwfType GwfType;
+#else
+typedef int32_t GwfType;
+#endif // __STDC_VERSION__ >= 202311L
+#endif // __cplusplus
+
+/*
+ GwfFile is an open frame file.
+ */
+typedef struct GwfFile GwfFile;
+
+/*
+ GwfFrameInfo describes a frame.
+ */
+typedef struct GwfFrameInfo {
+  int32_t run;
+  uint32_t frame;
+  uint32_t data_quality;
+  uint32_t gps_start_time_s;
+  uint32_t gps_residual_time_ns;
+  double frame_length_s;
+  size_t num_channels;
+} GwfFrameInfo;
+
+/*
+ GwfChannelInfo describes a channel of a frame and its data.
+ */
+typedef struct GwfChannelInfo {
+  GwfKind kind;
+  GwfType data_type;
+  uint64_t num_samples;
+  uint32_t num_dimensions;
+  /*
+   sample_rate is the inverse of the first dimension's spacing, or 0.
+   */
+  double sample_rate;
+  /*
+   gps_start_s is the GPS time of the first sample: the frame start
+   plus the channel's offset and the vector's origin.
+   */
+  double gps_start_s;
+  /*
+   bias and slope calibrate ADC counts as `slope * (x + bias)`; other
+   channels have 0 and 1.
+   */
+  double bias;
+  double slope;
+} GwfChannelInfo;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/*
+ gwf_open reads every frame of the file at path.  Close the file with
+ gwf_close.
+
+ # Safety
+
+ path must be a NUL terminated string and file a valid pointer.
+ */
+GwfStatus gwf_open(const char *path, struct GwfFile **file);
+
+/*
+ gwf_close frees a file opened by gwf_open.  Null is ignored.
+
+ # Safety
+
+ file must come from gwf_open and not be used afterwards.
+ */
+void gwf_close(struct GwfFile *file);
+
+/*
+ gwf_frame_count sets count to the number of frames in the file.
+
+ # Safety
+
+ file must come from gwf_open and count be a valid pointer.
+ */
+GwfStatus gwf_frame_count(const struct GwfFile *file, size_t *count);
+
+/*
+ gwf_frame_info describes frame, counting from 0.
+
+ # Safety
+
+ file must come from gwf_open and info be a valid pointer.
+ */
+GwfStatus gwf_frame_info(const struct GwfFile *file, size_t frame, struct GwfFrameInfo *info);
+
+/*
+ gwf_channel_name copies the NUL terminated name of a frame's channel,
+ counting from 0, into name and sets needed to the buffer length it takes.
+ A short buffer gets BufferTooSmall and is left untouched.
+
+ # Safety
+
+ file must come from gwf_open, name must have len bytes and needed be a
+ valid pointer.
+ */
+GwfStatus gwf_channel_name(const struct GwfFile *file,
+                           size_t frame,
+                           size_t channel,
+                           char *name,
+                           size_t len,
+                           size_t *needed);
+
+/*
+ gwf_channel_info describes the named channel of frame.
+
+ # Safety
+
+ file must come from gwf_open, name be a NUL terminated string and info
+ a valid pointer.
+ */
+GwfStatus gwf_channel_info(const struct GwfFile *file,
+                           size_t frame,
+                           const char *name,
+                           struct GwfChannelInfo *info);
+
+/*
+ gwf_read_channel copies the samples of the named channel of frame, in
+ their own type and native byte order, into data of len bytes and sets
+ samples to their number, even when data is too short.  Strings are
+ Unsupported.
+
+ # Safety
+
+ file must come from gwf_open, name be a NUL terminated string, data
+ have len bytes and samples be a valid pointer.
+ */
+GwfStatus gwf_read_channel(const struct GwfFile *file,
+                           size_t frame,
+                           const char *name,
+                           uint8_t *data,
+                           size_t len,
+                           size_t *samples);
+
+/*
+ gwf_read_channel_f64 copies the samples of the named channel of frame,
+ converted to doubles, into data of len doubles and sets samples to their
+ number, even when data is too short.  Complex samples and strings are
+ Unsupported.
+
+ # Safety
+
+ file must come from gwf_open, name be a NUL terminated string, data
+ have len doubles and samples be a valid pointer.
+ */
+GwfStatus gwf_read_channel_f64(const struct GwfFile *file,
+                               size_t frame,
+                               const char *name,
+                               double *data,
+                               size_t len,
+                               size_t *samples);
+
+/*
+ gwf_last_error copies the NUL terminated message of the last failure on
+ this thread into message, truncated to len bytes, and returns the
+ message length without the NUL, like snprintf.
+
+ # Safety
+
+ message must have len bytes, or be null when len is 0.
+ */
+size_t gwf_last_error(char *message, size_t len);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* GWF_H */
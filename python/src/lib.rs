@@ -0,0 +1,192 @@
+//! Python bindings for the gwf reader.  Build and install the `gwf` module
+//! into the active environment with `maturin develop --release` from this
+//! directory, then:
+//!
+//! ```python
+//! import gwf
+//! for name, (data, meta) in gwf.read("assets/PyCBC_T3_0.gwf").items():
+//!     print(name, data.dtype, data.shape, meta.get("t0"), meta.get("sample_rate"))
+//! ```
+//!
+//! The tests in `tests` read the PyCBC files in `assets`: after
+//! `maturin develop`, run `pytest` from this directory.
+
+use gwf::gps::GpsTime;
+use gwf::npy::Excerpt;
+use gwf::structures::{Channel, Vector};
+use numpy::{Complex64, IntoPyArray, PyArrayMethods};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+use pyo3::IntoPyObjectExt;
+use std::io::{BufReader, Error};
+use std::path::PathBuf;
+
+/// Paths is one file or a list of files in GPS order.
+#[derive(FromPyObject)]
+enum Paths {
+    One(PathBuf),
+    Many(Vec<PathBuf>),
+}
+
+/// Names is one channel name or a list of them.
+#[derive(FromPyObject)]
+enum Names {
+    One(String),
+    Many(Vec<String>),
+}
+
+/// read(path, channels=None, start=None, end=None)
+/// --
+///
+/// Reads channels of a frame file, or of a list of files in GPS order, in
+/// [start, end) GPS seconds.  Returns a dict mapping each channel name to a
+/// tuple of its samples, a NumPy array shaped by the vector's dimensions,
+/// and a metadata dict.  The metadata holds the channel structure's fields,
+/// its "kind", the "vector" info and, for time series, "t0", the GPS time
+/// of the first sample, and "sample_rate".  Time series of consecutive
+/// frames are joined.  With no channels every channel is read.
+#[pyfunction]
+#[pyo3(signature = (path, channels=None, start=None, end=None))]
+fn read<'py>(
+    py: Python<'py>,
+    path: Paths,
+    channels: Option<Names>,
+    start: Option<f64>,
+    end: Option<f64>,
+) -> PyResult<Bound<'py, PyDict>> {
+    let paths = match path {
+        Paths::One(p) => vec![p],
+        Paths::Many(p) => p,
+    };
+    let names = match channels {
+        None => Vec::new(),
+        Some(Names::One(n)) => vec![n],
+        Some(Names::Many(n)) => n,
+    };
+    let time = |t: f64| GpsTime::from_seconds(t).map_err(|e| PyValueError::new_err(e.to_string()));
+    let start = start.map(time).transpose()?;
+    let end = end.map(time).transpose()?;
+
+    let vectors = py.allow_threads(|| -> std::io::Result<Vec<(Channel, Vector)>> {
+        let names: Vec<&str> = names.iter().map(|n| n.as_str()).collect();
+        let mut excerpt = Excerpt::new(&names);
+        excerpt.window(start, end);
+        for p in paths.iter() {
+            std::fs::File::open(p)
+                .and_then(|f| gwf::parse(&mut BufReader::new(f), &mut excerpt))
+                .map_err(|e| Error::new(e.kind(), format!("{}: {}", p.display(), e)))?;
+        }
+        excerpt.finish()
+    })?;
+
+    let result = PyDict::new(py);
+    for (channel, data) in vectors {
+        let meta = metadata(py, &channel, &data)?;
+        let array = array(py, data)?;
+        result.set_item(channel.name(), (array, meta))?;
+    }
+    Ok(result)
+}
+
+/// array converts the data into a NumPy array of its type shaped by the
+/// dimension lengths, the first varying slowest.  Strings become a list.
+fn array(py: Python<'_>, data: Vector) -> PyResult<PyObject> {
+    let info = data.info();
+    let mut shape: Vec<usize> = info.dimension_lengths.iter().map(|n| *n as usize).collect();
+    if shape.iter().product::<usize>() != data.len() {
+        shape = vec![data.len()];
+    }
+    macro_rules! numeric {
+        ($($variant:ident),*) => {
+            match data {
+                $(Vector::$variant(_, v) => v.into_pyarray(py).reshape(shape)?.into_py_any(py),)*
+                Vector::Complexes(_, v) => v
+                    .into_iter()
+                    .map(|c| Complex64::new(c.real, c.imaginary))
+                    .collect::<Vec<_>>()
+                    .into_pyarray(py)
+                    .reshape(shape)?
+                    .into_py_any(py),
+                Vector::Strings(_, v) => PyList::new(py, v)?.into_py_any(py),
+            }
+        };
+    }
+    numeric!(I8, U8, I16, U16, I32, U32, U64, I64, F32, F64)
+}
+
+/// metadata describes the channel and its vector as a dict.
+fn metadata<'py>(
+    py: Python<'py>,
+    channel: &Channel,
+    data: &Vector,
+) -> PyResult<Bound<'py, PyDict>> {
+    let meta = PyDict::new(py);
+    // channels serialize as {"ADC": {...}}; the fields go in the dict and
+    // the variant in "kind".
+    if let serde_json::Value::Object(m) = to_json(channel)? {
+        for (kind, fields) in m {
+            meta.set_item("kind", kind)?;
+            if let serde_json::Value::Object(fields) = fields {
+                for (k, v) in fields.iter() {
+                    meta.set_item(k, python(py, v)?)?;
+                }
+            }
+        }
+    }
+    let info = data.info();
+    meta.set_item("vector", python(py, &to_json(info)?)?)?;
+    if let Some(axis) = info
+        .axis(0)
+        .filter(|a| a.is_time() && info.num_dimensions == 1)
+    {
+        meta.set_item("t0", axis.origin)?;
+        if axis.spacing > 0.0 {
+            meta.set_item("sample_rate", 1.0 / axis.spacing)?;
+        }
+    }
+    Ok(meta)
+}
+
+fn to_json<T: serde::Serialize>(v: &T) -> PyResult<serde_json::Value> {
+    serde_json::to_value(v).map_err(|e| PyValueError::new_err(e.to_string()))
+}
+
+/// python converts a JSON value into the matching Python object.
+fn python(py: Python<'_>, v: &serde_json::Value) -> PyResult<PyObject> {
+    use serde_json::Value;
+    match v {
+        Value::Null => Ok(py.None()),
+        Value::Bool(b) => b.into_py_any(py),
+        Value::Number(n) => match (n.as_i64(), n.as_u64()) {
+            (Some(i), _) => i.into_py_any(py),
+            (None, Some(u)) => u.into_py_any(py),
+            _ => n.as_f64().unwrap_or(f64::NAN).into_py_any(py),
+        },
+        Value::String(s) => s.into_py_any(py),
+        Value::Array(a) => {
+            let items = a
+                .iter()
+                .map(|x| python(py, x))
+                .collect::<PyResult<Vec<_>>>()?;
+            PyList::new(py, items)?.into_py_any(py)
+        }
+        Value::Object(m) => {
+            let d = PyDict::new(py);
+            for (k, x) in m.iter() {
+                d.set_item(k, python(py, x)?)?;
+            }
+            d.into_py_any(py)
+        }
+    }
+}
+
+/// gwf reads gravitational wave frame files.
+#[pymodule]
+#[pyo3(name = "gwf")]
+fn gwf_python(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    // fail the import, rather than the first read, without NumPy.
+    m.py().import("numpy")?;
+    m.add_function(wrap_pyfunction!(read, m)?)?;
+    Ok(())
+}
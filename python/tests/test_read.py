@@ -0,0 +1,51 @@
+"""Tests of gwf.read over the version 8 files in assets.
+
+Run from the python directory after building the module:
+
+    maturin develop
+    pytest
+"""
+
+from pathlib import Path
+
+import numpy as np
+import pytest
+
+import gwf
+
+ASSETS = Path(__file__).resolve().parents[2] / "assets"
+FILES = sorted(ASSETS.glob("PyCBC_T*.gwf"))
+CHANNEL = "H1:TEST-STRAIN"
+
+pytestmark = pytest.mark.skipif(not FILES, reason="no PyCBC_T*.gwf in assets")
+
+
+@pytest.mark.parametrize("path", FILES, ids=lambda p: p.name)
+def test_read(path):
+    channels = gwf.read(str(path))
+    assert CHANNEL in channels
+    data, meta = channels[CHANNEL]
+    assert isinstance(data, np.ndarray)
+    assert data.dtype == np.float64
+    assert data.ndim == 1 and data.size > 0
+    assert "kind" in meta
+    assert meta["vector"]["name"] == CHANNEL
+    assert meta["t0"] == 0.0
+    assert meta["sample_rate"] == 4096.0
+
+
+@pytest.mark.parametrize("path", FILES, ids=lambda p: p.name)
+def test_read_window(path):
+    channels = gwf.read(path, channels=[CHANNEL], start=1.0, end=2.0)
+    assert list(channels) == [CHANNEL]
+    data, meta = channels[CHANNEL]
+    assert data.shape == (4096,)
+    assert meta["t0"] == 1.0
+
+
+@pytest.mark.parametrize("path", FILES[:1], ids=lambda p: p.name)
+def test_read_errors(path):
+    with pytest.raises(OSError):
+        gwf.read(path, channels="H1:MISSING")
+    with pytest.raises(ValueError):
+        gwf.read(path, start=-1.0)
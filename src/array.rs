@@ -0,0 +1,102 @@
+use crate::structures::*;
+use ndarray::{Array1, ArrayD, IxDyn};
+use std::io::{Error, ErrorKind, Result};
+
+impl VectorInfo {
+    /// axis_arrays returns the coordinates of every dimension.
+    pub fn axis_arrays(&self) -> Vec<Array1<f64>> {
+        self.axes()
+            .into_iter()
+            .map(|a| Array1::from(a.coordinates()))
+            .collect()
+    }
+}
+
+impl Vector {
+    /// into_array converts numeric data into an f64 array shaped by the
+    /// dimension lengths.  The first dimension varies slowest.
+    pub fn into_array(self) -> Result<ArrayD<f64>> {
+        let shape = self.info().shape();
+        let name = self.info().name.clone();
+        match self.into_f64() {
+            Some(samples) => shaped(&name, shape, samples),
+            None => Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("{}: only real numbers can be converted", name),
+            )),
+        }
+    }
+
+    /// into_complex_array converts complex data into an array shaped by the dimension lengths.
+    pub fn into_complex_array(self) -> Result<ArrayD<Complex>> {
+        match self {
+            Vector::Complexes(info, samples) => shaped(&info.name, info.shape(), samples),
+            v => Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("{}: not complex", v.info().name),
+            )),
+        }
+    }
+}
+
+fn shaped<T>(name: &str, shape: Vec<usize>, samples: Vec<T>) -> Result<ArrayD<T>> {
+    let len = samples.len();
+    ArrayD::from_shape_vec(IxDyn(&shape), samples).map_err(|_| {
+        Error::new(
+            ErrorKind::InvalidData,
+            format!("{}: {} samples do not fit shape {:?}", name, len, shape),
+        )
+    })
+}
+
+#[cfg(test)]
+mod array_tests {
+    use super::*;
+
+    fn info(dimension_lengths: Vec<u64>) -> VectorInfo {
+        VectorInfo {
+            name: "H1:SPECTROGRAM".to_string(),
+            num_samples: dimension_lengths.iter().product(),
+            num_dimensions: dimension_lengths.len() as u32,
+            sample_spacing: vec![0.5, 16.0],
+            x_origins: vec![10.0, 0.0],
+            unit_x_scale_factors: vec!["s".to_string(), "Hz".to_string()],
+            unit_y: String::new(),
+            compression: 0,
+            dimension_lengths,
+        }
+    }
+
+    #[test]
+    fn test_into_array() -> Result<()> {
+        let v = Vector::I16(info(vec![2, 3]), vec![0, 1, 2, 3, 4, 5]);
+        let a = v.into_array()?;
+        assert_eq!(a.shape(), &[2, 3]);
+        assert_eq!(a[[1, 0]], 3.0);
+        assert_eq!(a[[0, 2]], 2.0);
+
+        let axes = info(vec![2, 3]).axis_arrays();
+        assert_eq!(axes[0].to_vec(), vec![10.0, 10.5]);
+        assert_eq!(axes[1].to_vec(), vec![0.0, 16.0, 32.0]);
+
+        let bad = Vector::F32(info(vec![2, 2]), vec![0.0; 3]);
+        assert!(bad.into_array().is_err());
+
+        let strings = Vector::Strings(info(vec![1, 1]), vec![String::new()]);
+        assert!(strings.into_array().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_into_complex_array() -> Result<()> {
+        let c = Complex {
+            real: 1.0,
+            imaginary: -1.0,
+        };
+        let v = Vector::Complexes(info(vec![1, 2]), vec![c.clone(), c.clone()]);
+        let a = v.into_complex_array()?;
+        assert_eq!(a.shape(), &[1, 2]);
+        assert_eq!(a[[0, 1]], c);
+        Ok(())
+    }
+}
@@ -0,0 +1,378 @@
+use crate::gps::GpsTime;
+use crate::handler::Handler;
+use crate::structures::*;
+use arrow_array::types::*;
+use arrow_array::{Array, ArrayRef, Float64Array, PrimitiveArray, RecordBatch, StringArray};
+use arrow_array::{ArrowPrimitiveType, StructArray};
+use arrow_schema::{DataType, Field, Fields, Schema, SchemaRef};
+use parquet::arrow::ArrowWriter;
+use std::collections::HashMap;
+use std::io::{Error, ErrorKind, Result, Write};
+use std::sync::Arc;
+
+/// TIME is the name of the column of sample times in GPS seconds.
+pub const TIME: &str = "time";
+
+impl Vector {
+    /// into_arrow converts the data into an Arrow array.  Numbers are moved
+    /// into the array without a copy; strings and complex numbers, which
+    /// become a struct of real and imaginary columns, are copied.
+    pub fn into_arrow(self) -> ArrayRef {
+        match self {
+            Vector::I8(_, v) => primitive::<Int8Type>(v),
+            Vector::U8(_, v) => primitive::<UInt8Type>(v),
+            Vector::I16(_, v) => primitive::<Int16Type>(v),
+            Vector::U16(_, v) => primitive::<UInt16Type>(v),
+            Vector::I32(_, v) => primitive::<Int32Type>(v),
+            Vector::U32(_, v) => primitive::<UInt32Type>(v),
+            Vector::U64(_, v) => primitive::<UInt64Type>(v),
+            Vector::I64(_, v) => primitive::<Int64Type>(v),
+            Vector::F32(_, v) => primitive::<Float32Type>(v),
+            Vector::F64(_, v) => primitive::<Float64Type>(v),
+            Vector::Strings(_, v) => Arc::new(StringArray::from(v)),
+            Vector::Complexes(_, v) => {
+                let real: Float64Array = v.iter().map(|c| c.real).collect::<Vec<_>>().into();
+                let imaginary: Float64Array =
+                    v.iter().map(|c| c.imaginary).collect::<Vec<_>>().into();
+                Arc::new(StructArray::from(vec![
+                    (
+                        Arc::new(Field::new("real", DataType::Float64, false)),
+                        Arc::new(real) as ArrayRef,
+                    ),
+                    (
+                        Arc::new(Field::new("imaginary", DataType::Float64, false)),
+                        Arc::new(imaginary) as ArrayRef,
+                    ),
+                ]))
+            }
+        }
+    }
+}
+
+fn primitive<T: ArrowPrimitiveType>(v: Vec<T::Native>) -> ArrayRef {
+    Arc::new(PrimitiveArray::<T>::new(v.into(), None))
+}
+
+/// metadata describes a channel for the schema metadata: `gwf:NAME` holds
+/// the channel structure and `gwf:NAME:vector` the vector info, both as JSON.
+pub fn metadata(channel: &Channel, info: &VectorInfo) -> Result<HashMap<String, String>> {
+    let mut m = HashMap::new();
+    m.insert(
+        format!("gwf:{}", channel.name()),
+        serde_json::to_string(channel)?,
+    );
+    m.insert(
+        format!("gwf:{}:vector", channel.name()),
+        serde_json::to_string(info)?,
+    );
+    Ok(m)
+}
+
+/// record_batch makes a batch with a column per channel of a frame, led by
+/// the sample times of the first channel when it is one dimensional.
+/// Every channel must have the same number of samples; vectors of more
+/// dimensions are flattened with the first dimension varying slowest.
+pub fn record_batch(frame: &FrameHeader, channels: Vec<(Channel, Vector)>) -> Result<RecordBatch> {
+    let (first, data) = match channels.first() {
+        Some(c) => c,
+        None => return Err(Error::new(ErrorKind::InvalidInput, "no channels")),
+    };
+    let len = data.len();
+    let first_name = first.name().to_string();
+    let mut fields = Vec::with_capacity(channels.len() + 1);
+    let mut columns = Vec::with_capacity(channels.len() + 1);
+    let mut meta = HashMap::new();
+    if data.info().dimension_lengths.len() == 1 {
+        let info = data.info();
+        let start = first.start_time(frame) + info.x_origins.first().copied().unwrap_or(0.0);
+        let dx = info.sample_spacing.first().copied().unwrap_or(0.0);
+        let times: Vec<f64> = (0..len)
+            .map(|i| (start + i as f64 * dx).as_seconds())
+            .collect();
+        fields.push(Field::new(TIME, DataType::Float64, false));
+        columns.push(primitive::<Float64Type>(times));
+    }
+    for (channel, data) in channels {
+        if data.len() != len {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "{} has {} samples but {} has {}",
+                    channel.name(),
+                    data.len(),
+                    first_name,
+                    len
+                ),
+            ));
+        }
+        meta.extend(metadata(&channel, data.info())?);
+        let array = data.into_arrow();
+        fields.push(Field::new(channel.name(), array.data_type().clone(), false));
+        columns.push(array);
+    }
+    let schema = Schema::new(Fields::from(fields)).with_metadata(meta);
+    RecordBatch::try_new(Arc::new(schema), columns).map_err(invalid)
+}
+
+fn invalid<E: std::error::Error + Send + Sync + 'static>(e: E) -> Error {
+    Error::new(ErrorKind::InvalidData, e)
+}
+
+/// ParquetExporter is a handler writing the named channels of frame files
+/// to a Parquet file with a row group per frame.  Parse files in GPS order
+/// with the same exporter, then call finish.
+///
+/// The columns are those of record_batch.  The schema, with its metadata,
+/// comes from the first frame written; later frames must have the same
+/// columns.
+pub struct ParquetExporter<W: Write + Send> {
+    writer: Option<W>,
+    parquet: Option<ArrowWriter<W>>,
+    schema: Option<SchemaRef>,
+    names: Vec<String>,
+    start: Option<GpsTime>,
+    end: Option<GpsTime>,
+    frame: Option<FrameHeader>,
+    channels: Vec<Option<(Channel, Vector)>>,
+    error: Option<Error>,
+}
+
+impl<W: Write + Send> ParquetExporter<W> {
+    /// new exports the channels in the order named.
+    pub fn new(writer: W, names: &[&str]) -> Self {
+        ParquetExporter {
+            writer: Some(writer),
+            parquet: None,
+            schema: None,
+            names: names.iter().map(|n| n.to_string()).collect(),
+            start: None,
+            end: None,
+            frame: None,
+            channels: vec![None; names.len()],
+            error: None,
+        }
+    }
+
+    /// window limits the rows to sample times in `[start, end)`.
+    pub fn window(&mut self, start: Option<GpsTime>, end: Option<GpsTime>) {
+        self.start = start;
+        self.end = end;
+    }
+
+    /// finish writes the last frame and the Parquet footer.
+    pub fn finish(mut self) -> Result<W> {
+        self.end_frame();
+        if let Some(e) = self.error {
+            return Err(e);
+        }
+        match self.parquet {
+            Some(p) => p.into_inner().map_err(invalid),
+            None => Err(Error::new(
+                ErrorKind::NotFound,
+                format!("no data for {}", self.names.join(", ")),
+            )),
+        }
+    }
+
+    /// end_frame writes the rows of the frame read inside the window.
+    fn end_frame(&mut self) {
+        let frame = match self.frame.take() {
+            Some(f) => f,
+            None => return,
+        };
+        let channels: Vec<Option<(Channel, Vector)>> =
+            std::mem::replace(&mut self.channels, vec![None; self.names.len()]);
+        if channels.iter().all(|c| c.is_none()) {
+            return;
+        }
+        if let Err(e) = self.write(&frame, channels) {
+            self.error.get_or_insert(e);
+        }
+    }
+
+    fn write(
+        &mut self,
+        frame: &FrameHeader,
+        channels: Vec<Option<(Channel, Vector)>>,
+    ) -> Result<()> {
+        let mut found = Vec::with_capacity(channels.len());
+        for (name, c) in self.names.iter().zip(channels) {
+            match c {
+                Some(c) => found.push(c),
+                None => {
+                    return Err(Error::new(
+                        ErrorKind::NotFound,
+                        format!("{}: not in the frame at {}", name, frame.start_time()),
+                    ))
+                }
+            }
+        }
+        let batch = record_batch(frame, found)?;
+        let batch = self.window_rows(batch);
+        if batch.num_rows() == 0 {
+            return Ok(());
+        }
+
+        if self.parquet.is_none() {
+            let schema = batch.schema();
+            let writer = self.writer.take().expect("writer taken only once");
+            self.parquet =
+                Some(ArrowWriter::try_new(writer, schema.clone(), None).map_err(invalid)?);
+            self.schema = Some(schema);
+        }
+        let schema = self.schema.clone().unwrap();
+        if schema.fields() != batch.schema().fields() {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "the frame at {} has different columns from the first frame",
+                    frame.start_time()
+                ),
+            ));
+        }
+        let batch = RecordBatch::try_new(schema, batch.columns().to_vec()).map_err(invalid)?;
+        let parquet = self.parquet.as_mut().unwrap();
+        parquet.write(&batch).map_err(invalid)?;
+        parquet.flush().map_err(invalid)
+    }
+
+    /// window_rows slices the batch to the rows with times in the window.
+    /// Batches without a time column are kept whole.
+    fn window_rows(&self, batch: RecordBatch) -> RecordBatch {
+        let times = match batch
+            .column_by_name(TIME)
+            .and_then(|c| c.as_any().downcast_ref::<Float64Array>())
+        {
+            Some(t) => t.values().clone(),
+            None => return batch,
+        };
+        let start = self.start.map_or(f64::NEG_INFINITY, |t| t.as_seconds());
+        let end = self.end.map_or(f64::INFINITY, |t| t.as_seconds());
+        let first = times
+            .iter()
+            .position(|&t| t >= start)
+            .unwrap_or(times.len());
+        let last = times.iter().position(|&t| t >= end).unwrap_or(times.len());
+        batch.slice(first, last.max(first) - first)
+    }
+}
+
+impl<W: Write + Send> Handler for ParquetExporter<W> {
+    fn begin_frame(&mut self) -> Option<fn(&mut Self, header: FrameHeader)> {
+        Some(|e, h| {
+            e.end_frame();
+            e.frame = Some(h);
+        })
+    }
+
+    fn channel(&mut self) -> Option<fn(&mut Self, channel: Channel, data: Vector)> {
+        Some(|e, c, v| {
+            if let Some(i) = e.names.iter().position(|n| n == c.name()) {
+                e.channels[i] = Some((c, v));
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod arrow_tests {
+    use super::*;
+    use crate::fixtures::{adc, file, header, info};
+    use arrow_array::cast::AsArray;
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_into_arrow() {
+        let a = Vector::I16(info("H1:A", 3), vec![1, -2, 3]).into_arrow();
+        assert_eq!(a.data_type(), &DataType::Int16);
+        assert_eq!(
+            a.as_primitive::<Int16Type>().values().to_vec(),
+            vec![1, -2, 3]
+        );
+
+        let c = Complex {
+            real: 1.0,
+            imaginary: -1.0,
+        };
+        let a = Vector::Complexes(info("H1:C", 2), vec![c.clone(), c]).into_arrow();
+        let s = a.as_struct();
+        assert_eq!(s.column_names(), vec!["real", "imaginary"]);
+        assert_eq!(s.column(1).as_primitive::<Float64Type>().value(0), -1.0);
+    }
+
+    #[test]
+    fn test_record_batch() -> Result<()> {
+        let a = ADC {
+            bias: 1.0,
+            slope: 2.0,
+            units: Some("V".to_string()),
+            ..adc("H1:A")
+        };
+        let channels = vec![
+            (
+                Channel::ADC(a.clone()),
+                Vector::I32(info("H1:A", 4), vec![0, 1, 2, 3]),
+            ),
+            (
+                Channel::ADC(adc("H1:B")),
+                Vector::F32(info("H1:B", 4), vec![0.0; 4]),
+            ),
+        ];
+        let batch = record_batch(&header(0), channels)?;
+        assert_eq!(batch.num_columns(), 3);
+        let times = batch.column(0).as_primitive::<Float64Type>();
+        assert_eq!(
+            times.values().to_vec(),
+            vec![1e9, 1e9 + 0.25, 1e9 + 0.5, 1e9 + 0.75]
+        );
+        let meta = batch.schema().metadata().clone();
+        let channel: Channel = serde_json::from_str(&meta["gwf:H1:A"])?;
+        assert_eq!(channel, Channel::ADC(a));
+        let vector: VectorInfo = serde_json::from_str(&meta["gwf:H1:B:vector"])?;
+        assert_eq!(vector.unit_y, "counts");
+
+        let short = vec![
+            (
+                Channel::ADC(adc("H1:A")),
+                Vector::I32(info("H1:A", 4), vec![0; 4]),
+            ),
+            (
+                Channel::ADC(adc("H1:B")),
+                Vector::I32(info("H1:B", 2), vec![0; 2]),
+            ),
+        ];
+        assert!(record_batch(&header(0), short).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parquet_exporter() -> Result<()> {
+        let input = file(3, &["H1:A"]);
+
+        let path = std::env::temp_dir().join(format!("gwf-arrow-{}.parquet", std::process::id()));
+        let mut exporter = ParquetExporter::new(std::fs::File::create(&path)?, &["H1:A"]);
+        exporter.window(
+            Some(GpsTime::new(1_000_000_000, 500_000_000)),
+            Some(GpsTime::new(1_000_000_002, 250_000_000)),
+        );
+        crate::parse(&mut Cursor::new(&input), &mut exporter)?;
+        exporter.finish()?;
+
+        let file = std::fs::File::open(&path)?;
+        std::fs::remove_file(&path)?;
+        let builder = ParquetRecordBatchReaderBuilder::try_new(file).map_err(invalid)?;
+        assert!(builder.schema().metadata().contains_key("gwf:H1:A"));
+        let reader = builder.build().map_err(invalid)?;
+        let mut values: Vec<i16> = Vec::new();
+        for batch in reader {
+            let batch = batch.map_err(invalid)?;
+            values.extend(batch.column(1).as_primitive::<Int16Type>().values().iter());
+        }
+        assert_eq!(values, vec![2, 3, 4, 5, 6, 7, 8]);
+
+        let missing = ParquetExporter::new(Vec::new(), &["H1:Z"]);
+        assert!(missing.finish().is_err());
+        Ok(())
+    }
+}
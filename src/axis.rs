@@ -0,0 +1,156 @@
+use crate::gps::GpsTime;
+use crate::structures::*;
+
+/// Axis is one dimension of a vector: its coordinates are `origin + i * spacing`.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Axis {
+    pub length: usize,
+    pub origin: f64,
+    pub spacing: f64,
+    /// unit is the vector's unitX for this dimension, e.g. `s` or `Hz`.
+    pub unit: String,
+}
+
+impl Axis {
+    pub fn coordinate(&self, i: usize) -> f64 {
+        self.origin + i as f64 * self.spacing
+    }
+
+    pub fn coordinates(&self) -> Vec<f64> {
+        (0..self.length).map(|i| self.coordinate(i)).collect()
+    }
+
+    /// is_time is true when the unit is seconds.
+    pub fn is_time(&self) -> bool {
+        matches!(
+            self.unit.trim().to_ascii_lowercase().as_str(),
+            "s" | "sec" | "second" | "seconds" | "time"
+        )
+    }
+
+    /// is_frequency is true when the unit is hertz.
+    pub fn is_frequency(&self) -> bool {
+        matches!(
+            self.unit.trim().to_ascii_lowercase().as_str(),
+            "hz" | "hertz" | "1/s" | "s^-1"
+        )
+    }
+}
+
+impl VectorInfo {
+    /// shape is the length of each dimension; a vector without dimensions is one dimensional.
+    pub fn shape(&self) -> Vec<usize> {
+        if self.dimension_lengths.is_empty() {
+            return vec![self.num_samples as usize];
+        }
+        self.dimension_lengths.iter().map(|n| *n as usize).collect()
+    }
+
+    pub fn axes(&self) -> Vec<Axis> {
+        self.shape()
+            .into_iter()
+            .enumerate()
+            .map(|(d, length)| Axis {
+                length,
+                origin: self.x_origins.get(d).copied().unwrap_or(0.0),
+                spacing: self.sample_spacing.get(d).copied().unwrap_or(1.0),
+                unit: self
+                    .unit_x_scale_factors
+                    .get(d)
+                    .cloned()
+                    .unwrap_or_default(),
+            })
+            .collect()
+    }
+
+    pub fn axis(&self, dimension: usize) -> Option<Axis> {
+        self.axes().into_iter().nth(dimension)
+    }
+
+    /// gps_times returns the absolute time of each sample along the first
+    /// dimension of channel's vector in frame.  Vectors whose first axis is
+    /// not time return None.
+    pub fn gps_times(&self, frame: &FrameHeader, channel: &Channel) -> Option<Vec<GpsTime>> {
+        let axis = self.axis(0)?;
+        if !axis.is_time() {
+            return None;
+        }
+        let start = channel.start_time(frame);
+        Some(
+            (0..axis.length)
+                .map(|i| start + axis.coordinate(i))
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod axis_tests {
+    use super::*;
+
+    fn frame() -> FrameHeader {
+        FrameHeader {
+            name: "H1".to_string(),
+            run: 0,
+            frame: 0,
+            data_quality: DataQuality(0),
+            gps_start_time_s: 1_000_000_000,
+            gps_residual_time_ns: 0,
+            gps_leap_s: 18,
+            frame_length_s: 1.0,
+        }
+    }
+
+    fn adc(time_offset_s: f64) -> Channel {
+        Channel::ADC(ADC {
+            name: "H1:TEST".to_string(),
+            comment: String::new(),
+            channel_group: 0,
+            channel_number: 0,
+            num_bits: 16,
+            bias: 0.0,
+            slope: 1.0,
+            units: None,
+            sample_rate: 4.0,
+            time_offset_s,
+            f_shift: 0.0,
+            phase: 0.0,
+            data_valid: DataValid(0),
+        })
+    }
+
+    fn info(unit_x: &str) -> VectorInfo {
+        VectorInfo {
+            name: "H1:TEST".to_string(),
+            num_samples: 4,
+            num_dimensions: 1,
+            dimension_lengths: vec![4],
+            sample_spacing: vec![0.25],
+            x_origins: vec![0.5],
+            unit_x_scale_factors: vec![unit_x.to_string()],
+            unit_y: String::new(),
+            compression: 0,
+        }
+    }
+
+    #[test]
+    fn test_axes() {
+        let axes = info("s").axes();
+        assert_eq!(axes.len(), 1);
+        assert!(axes[0].is_time());
+        assert_eq!(axes[0].coordinates(), vec![0.5, 0.75, 1.0, 1.25]);
+
+        let hz = info("Hz").axis(0).unwrap();
+        assert!(hz.is_frequency());
+        assert!(info("s").axis(1).is_none());
+    }
+
+    #[test]
+    fn test_gps_times() {
+        let times = info("s").gps_times(&frame(), &adc(0.125)).unwrap();
+        assert_eq!(times[0], GpsTime::new(1_000_000_000, 625_000_000));
+        assert_eq!(times[3], GpsTime::new(1_000_000_001, 375_000_000));
+        assert!(info("Hz").gps_times(&frame(), &adc(0.0)).is_none());
+    }
+}
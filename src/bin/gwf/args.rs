@@ -0,0 +1,103 @@
+use std::io::{Error, ErrorKind, Result};
+use std::str::FromStr;
+
+/// Args is a minimal command-line parser: options are `--name value` or
+/// `--name=value` and may appear anywhere; everything else is positional.
+pub struct Args {
+    args: Vec<String>,
+}
+
+impl Args {
+    pub fn new<I: Iterator<Item = String>>(args: I) -> Args {
+        Args {
+            args: args.collect(),
+        }
+    }
+
+    /// flag removes `--name` and reports whether it was present.
+    pub fn flag(&mut self, name: &str) -> bool {
+        let flag = format!("--{}", name);
+        let before = self.args.len();
+        self.args.retain(|a| *a != flag);
+        self.args.len() != before
+    }
+
+    /// option removes `--name value` or `--name=value` and returns the value.
+    pub fn option(&mut self, name: &str) -> Result<Option<String>> {
+        let flag = format!("--{}", name);
+        let prefix = format!("--{}=", name);
+        if let Some(i) = self.args.iter().position(|a| a.starts_with(&prefix)) {
+            let a = self.args.remove(i);
+            return Ok(Some(a[prefix.len()..].to_string()));
+        }
+        match self.args.iter().position(|a| *a == flag) {
+            Some(i) if i + 1 < self.args.len() => {
+                self.args.remove(i);
+                Ok(Some(self.args.remove(i)))
+            }
+            Some(_) => Err(invalid(format!("{} requires a value", flag))),
+            None => Ok(None),
+        }
+    }
+
+    /// parse removes and parses `--name value`.
+    pub fn parse<T: FromStr>(&mut self, name: &str) -> Result<Option<T>> {
+        match self.option(name)? {
+            Some(v) => v
+                .parse()
+                .map(Some)
+                .map_err(|_| invalid(format!("invalid --{}: {}", name, v))),
+            None => Ok(None),
+        }
+    }
+
+    /// positional returns the remaining arguments; unknown options are errors.
+    pub fn positional(self) -> Result<Vec<String>> {
+        if let Some(a) = self.args.iter().find(|a| a.starts_with("--")) {
+            return Err(invalid(format!("unknown option: {}", a)));
+        }
+        Ok(self.args)
+    }
+}
+
+pub fn invalid(msg: String) -> Error {
+    Error::new(ErrorKind::InvalidInput, msg)
+}
+
+#[cfg(test)]
+mod args_tests {
+    use super::*;
+
+    fn args(a: &[&str]) -> Args {
+        Args::new(a.iter().map(|s| s.to_string()))
+    }
+
+    #[test]
+    fn test_option() -> Result<()> {
+        let mut a = args(&["in.gwf", "--start", "10", "--end=20", "-"]);
+        assert_eq!(a.option("start")?, Some("10".to_string()));
+        assert_eq!(a.parse::<u32>("end")?, Some(20));
+        assert_eq!(a.option("start")?, None);
+        assert_eq!(a.positional()?, vec!["in.gwf", "-"]);
+
+        let mut a = args(&["--rate=fast", "--end"]);
+        assert_eq!(
+            a.parse::<f64>("rate").unwrap_err().kind(),
+            ErrorKind::InvalidInput
+        );
+        assert_eq!(a.option("end").unwrap_err().kind(), ErrorKind::InvalidInput);
+        Ok(())
+    }
+
+    #[test]
+    fn test_positional() -> Result<()> {
+        let mut a = args(&["--toc", "a.gwf", "b.gwf"]);
+        assert!(a.flag("toc"));
+        assert!(!a.flag("toc"));
+        assert_eq!(a.positional()?, vec!["a.gwf", "b.gwf"]);
+
+        let a = args(&["a.gwf", "--unknown"]);
+        assert_eq!(a.positional().unwrap_err().kind(), ErrorKind::InvalidInput);
+        Ok(())
+    }
+}
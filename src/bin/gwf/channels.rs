@@ -0,0 +1,103 @@
+use crate::args::{invalid, Args};
+use crate::dump::Format;
+use gwf::inventory::ChannelSummary;
+use gwf::structures::{ChannelKind, Scheme};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io::{self, BufWriter, Result, Write};
+
+pub const USAGE: &str = "usage: gwf channels [--format text|json] FILE...
+
+Prints one row per channel of the files: name, structure kind, sample rate,
+data type, unit, compression and the number of frames with the channel.
+The table of contents is used when a file has one; otherwise each file is scanned.";
+
+/// Row is a channel of all files together with the total number of frames.
+#[derive(Serialize)]
+struct Row {
+    #[serde(flatten)]
+    channel: ChannelSummary,
+    total_frames: usize,
+}
+
+pub fn run(mut args: Args) -> Result<()> {
+    let format = args.parse("format")?.unwrap_or(Format::Text);
+    let files = args.positional()?;
+    if files.is_empty() {
+        return Err(invalid(USAGE.to_string()));
+    }
+
+    let mut total_frames = 0;
+    let mut channels: Vec<ChannelSummary> = Vec::new();
+    let mut index: HashMap<(String, ChannelKind), usize> = HashMap::new();
+    for filename in files {
+        let mut reader = crate::open(&filename)?;
+        let inventory =
+            gwf::inventory::read(&mut reader).map_err(|e| crate::in_file(&filename, e))?;
+        total_frames += inventory.frames;
+        for c in inventory.channels {
+            let key = (c.name.clone(), c.kind);
+            match index.get(&key) {
+                Some(i) => channels[*i].frames += c.frames,
+                None => {
+                    index.insert(key, channels.len());
+                    channels.push(c);
+                }
+            }
+        }
+    }
+
+    let mut out = BufWriter::new(io::stdout());
+    match format {
+        Format::Text => {
+            let width = channels.iter().map(|c| c.name.len()).max().unwrap_or(0);
+            writeln!(
+                out,
+                "{:<width$}  {:<4}  {:>10}  {:<7}  {:<10}  {:<18}  frames",
+                "name",
+                "kind",
+                "rate",
+                "type",
+                "unit",
+                "compression",
+                width = width
+            )?;
+            for c in &channels {
+                writeln!(
+                    out,
+                    "{:<width$}  {:<4}  {:>10}  {:<7}  {:<10}  {:<18}  {}/{}",
+                    c.name,
+                    c.kind.to_string(),
+                    c.sample_rate,
+                    c.data_type,
+                    c.unit,
+                    scheme(c.compression),
+                    c.frames,
+                    total_frames,
+                    width = width
+                )?;
+            }
+        }
+        Format::Json => {
+            for channel in channels {
+                serde_json::to_writer(
+                    &mut out,
+                    &Row {
+                        channel,
+                        total_frames,
+                    },
+                )?;
+                writeln!(out)?;
+            }
+        }
+    }
+    out.flush()
+}
+
+/// scheme names an FrVect compression scheme.
+fn scheme(compression: u16) -> String {
+    match Scheme::from_compression(compression) {
+        Some(s) => s.to_string(),
+        None => (compression & 0xff).to_string(),
+    }
+}
@@ -0,0 +1,62 @@
+use crate::args::{invalid, Args};
+use gwf::cut::Cutter;
+use gwf::gps::GpsTime;
+use gwf::writer::{Compression, Writer};
+use std::fs::File;
+use std::io::{BufWriter, Result, Write};
+
+pub const USAGE: &str = "usage: gwf cut --output FILE [options] FILE...
+
+Copies channels and a GPS time range of the files into a new version 8 file.
+The files must be given in time order.  gwf copy is the same command.
+
+options:
+  --output FILE           the file to write
+  --channels GLOB,...     the channels to copy, e.g. 'H1:GDS-*,L1:?DC'; all by default
+  --start GPS             the start of the time range
+  --end GPS               the end of the time range
+  --frame-length SECONDS  the duration of each frame written; by default
+                          the frames read are kept
+  --compression SCHEME    raw (default), gzip, diff_gzip, zero_suppress or
+                          auto, which picks the smallest; gzip, diff_gzip
+                          and auto take a level, e.g. gzip:9";
+
+pub fn run(mut args: Args) -> Result<()> {
+    let output = args.option("output")?;
+    let channels = args.option("channels")?.unwrap_or_default();
+    let start: Option<GpsTime> = args.parse("start")?;
+    let end: Option<GpsTime> = args.parse("end")?;
+    let frame_length: Option<f64> = args.parse("frame-length")?;
+    let compression = args.parse("compression")?.unwrap_or(Compression::Raw);
+    let files = args.positional()?;
+    let output = match output {
+        Some(o) if !files.is_empty() => o,
+        _ => return Err(invalid(USAGE.to_string())),
+    };
+    if let (Some(s), Some(e)) = (start, end) {
+        if e <= s {
+            return Err(invalid(format!("--end {} is not after --start {}", e, s)));
+        }
+    }
+    if let Some(l) = frame_length {
+        if !l.is_finite() || l <= 0.0 {
+            return Err(invalid(format!("invalid --frame-length: {}", l)));
+        }
+    }
+
+    let patterns: Vec<&str> = channels.split(',').filter(|p| !p.is_empty()).collect();
+    let out = File::create(&output).map_err(|e| crate::in_file(&output, e))?;
+    let mut writer = Writer::new(BufWriter::new(out)).map_err(|e| crate::in_file(&output, e))?;
+    writer.compress(compression);
+    let mut cutter = Cutter::new(writer, &patterns);
+    cutter.window(start, end);
+    if let Some(l) = frame_length {
+        cutter.frame_length(l);
+    }
+    for filename in files {
+        let mut reader = crate::open(&filename)?;
+        gwf::parse(&mut reader, &mut cutter).map_err(|e| crate::in_file(&filename, e))?;
+    }
+    let mut out = cutter.finish().map_err(|e| crate::in_file(&output, e))?;
+    out.flush()
+}
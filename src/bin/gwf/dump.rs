@@ -0,0 +1,304 @@
+use crate::args::{invalid, Args};
+use gwf::handler::Handler;
+use gwf::structures::*;
+use gwf::toc::Toc;
+use serde::Serialize;
+use std::fmt::Debug;
+use std::io::{self, BufWriter, Result, Stdout, Write};
+use std::str::FromStr;
+
+pub const USAGE: &str = "usage: gwf dump [--level headers|metadata|preview|full] [--preview N] [--format text|json] FILE...
+
+Prints the structures of each file in the order they are stored.
+  headers   the file header and frame headers
+  metadata  every structure; vectors without their samples (default)
+  preview   every structure with the first N samples of each vector (default 10)
+  full      every structure with all samples
+
+Only version 8 files have their FrEndOfFrame, FrTOC and FrEndOfFile printed.";
+
+/// Level is how much of the file dump prints.
+#[derive(Debug, PartialEq, PartialOrd, Copy, Clone)]
+pub enum Level {
+    Headers,
+    Metadata,
+    Preview,
+    Full,
+}
+
+impl FromStr for Level {
+    type Err = io::Error;
+    fn from_str(s: &str) -> Result<Level> {
+        match s {
+            "headers" => Ok(Level::Headers),
+            "metadata" => Ok(Level::Metadata),
+            "preview" => Ok(Level::Preview),
+            "full" => Ok(Level::Full),
+            _ => Err(invalid(format!("unknown level: {}", s))),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum Format {
+    Text,
+    Json,
+}
+
+impl FromStr for Format {
+    type Err = io::Error;
+    fn from_str(s: &str) -> Result<Format> {
+        match s {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            _ => Err(invalid(format!("unknown format: {}", s))),
+        }
+    }
+}
+
+pub fn run(mut args: Args) -> Result<()> {
+    let level = args.parse("level")?.unwrap_or(Level::Metadata);
+    let format = args.parse("format")?.unwrap_or(Format::Text);
+    let preview = args.parse("preview")?.unwrap_or(10);
+    let files = args.positional()?;
+    if files.is_empty() {
+        return Err(invalid(USAGE.to_string()));
+    }
+
+    let mut dumper = Dumper {
+        level,
+        format,
+        samples: match level {
+            Level::Headers | Level::Metadata => Some(0),
+            Level::Preview => Some(preview),
+            Level::Full => None,
+        },
+        out: BufWriter::new(io::stdout()),
+        err: None,
+    };
+    for filename in files {
+        let mut reader = crate::open(&filename)?;
+        let header = gwf::parse_header(&mut reader).map_err(|e| crate::in_file(&filename, e))?;
+        dumper.emit(
+            "file",
+            &FileHeader {
+                filename: filename.clone(),
+                header,
+            },
+        );
+        gwf::parse(&mut reader, &mut dumper).map_err(|e| crate::in_file(&filename, e))?;
+        if let Some(err) = dumper.err.take() {
+            return Err(err);
+        }
+    }
+    dumper.out.flush()
+}
+
+/// FileHeader labels the header with the file it was read from.
+#[derive(Debug, Serialize)]
+struct FileHeader {
+    filename: String,
+    header: Header,
+}
+
+struct Dumper {
+    level: Level,
+    format: Format,
+    /// samples is the number of samples of each vector to print; None prints all.
+    samples: Option<usize>,
+    out: BufWriter<Stdout>,
+    err: Option<io::Error>,
+}
+
+impl Dumper {
+    fn emit<T: Debug + Serialize>(&mut self, structure: &str, data: &T) {
+        if self.err.is_some() {
+            return;
+        }
+        let res = match self.format {
+            Format::Text => writeln!(self.out, "{}: {:#?}", structure, data),
+            Format::Json => {
+                #[derive(Serialize)]
+                struct Line<'a, T> {
+                    structure: &'a str,
+                    data: &'a T,
+                }
+                serde_json::to_writer(&mut self.out, &Line { structure, data })
+                    .map_err(io::Error::from)
+                    .and_then(|_| writeln!(self.out))
+            }
+        };
+        self.err = res.err();
+    }
+
+    /// metadata is true when structures other than headers are printed.
+    fn metadata(&self) -> bool {
+        self.level >= Level::Metadata
+    }
+
+    fn trim(&self, v: Vector) -> Vector {
+        match self.samples {
+            Some(n) => truncate(v, n),
+            None => v,
+        }
+    }
+}
+
+/// truncate keeps the first n samples of v.
+fn truncate(v: Vector, n: usize) -> Vector {
+    macro_rules! keep {
+        ($variant:ident, $info:ident, $data:ident) => {{
+            let mut $data = $data;
+            $data.truncate(n);
+            Vector::$variant($info, $data)
+        }};
+    }
+    match v {
+        Vector::I8(info, data) => keep!(I8, info, data),
+        Vector::U8(info, data) => keep!(U8, info, data),
+        Vector::I16(info, data) => keep!(I16, info, data),
+        Vector::U16(info, data) => keep!(U16, info, data),
+        Vector::I32(info, data) => keep!(I32, info, data),
+        Vector::U32(info, data) => keep!(U32, info, data),
+        Vector::U64(info, data) => keep!(U64, info, data),
+        Vector::I64(info, data) => keep!(I64, info, data),
+        Vector::F32(info, data) => keep!(F32, info, data),
+        Vector::F64(info, data) => keep!(F64, info, data),
+        Vector::Strings(info, data) => keep!(Strings, info, data),
+        Vector::Complexes(info, data) => keep!(Complexes, info, data),
+    }
+}
+
+impl Handler for Dumper {
+    fn begin_frame(&mut self) -> Option<fn(&mut Self, header: FrameHeader)> {
+        Some(|d, data| d.emit("FrameH", &data))
+    }
+
+    fn end_frame(&mut self) -> Option<fn(&mut Self, data: EndOfFrame)> {
+        if !self.metadata() {
+            return None;
+        }
+        Some(|d, data| d.emit("FrEndOfFrame", &data))
+    }
+
+    fn toc(&mut self) -> Option<fn(&mut Self, data: Toc)> {
+        if !self.metadata() {
+            return None;
+        }
+        Some(|d, data| d.emit("FrTOC", &data))
+    }
+
+    fn end_of_file(&mut self) -> Option<fn(&mut Self, data: EndOfFile)> {
+        if !self.metadata() {
+            return None;
+        }
+        Some(|d, data| d.emit("FrEndOfFile", &data))
+    }
+
+    fn adc(&mut self) -> Option<fn(&mut Self, data: ADC)> {
+        if !self.metadata() {
+            return None;
+        }
+        Some(|d, data| d.emit("FrAdcData", &data))
+    }
+
+    fn detector(&mut self) -> Option<fn(&mut Self, data: Detector)> {
+        if !self.metadata() {
+            return None;
+        }
+        Some(|d, data| d.emit("FrDetector", &data))
+    }
+
+    fn event(&mut self) -> Option<fn(&mut Self, data: Event)> {
+        if !self.metadata() {
+            return None;
+        }
+        Some(|d, data| d.emit("FrEvent", &data))
+    }
+
+    fn history(&mut self) -> Option<fn(&mut Self, data: History)> {
+        if !self.metadata() {
+            return None;
+        }
+        Some(|d, data| d.emit("FrHistory", &data))
+    }
+
+    fn message(&mut self) -> Option<fn(&mut Self, data: Message)> {
+        if !self.metadata() {
+            return None;
+        }
+        Some(|d, data| d.emit("FrMsg", &data))
+    }
+
+    fn post_processed(&mut self) -> Option<fn(&mut Self, data: PostProcessed)> {
+        if !self.metadata() {
+            return None;
+        }
+        Some(|d, data| d.emit("FrProcData", &data))
+    }
+
+    fn raw(&mut self) -> Option<fn(&mut Self, data: RawData)> {
+        if !self.metadata() {
+            return None;
+        }
+        Some(|d, data| d.emit("FrRawData", &data))
+    }
+
+    fn serial(&mut self) -> Option<fn(&mut Self, data: Serial)> {
+        if !self.metadata() {
+            return None;
+        }
+        Some(|d, data| d.emit("FrSerData", &data))
+    }
+
+    fn simulated(&mut self) -> Option<fn(&mut Self, data: Simulation)> {
+        if !self.metadata() {
+            return None;
+        }
+        Some(|d, data| d.emit("FrSimData", &data))
+    }
+
+    fn simulated_event(&mut self) -> Option<fn(&mut Self, data: SimulatedEvent)> {
+        if !self.metadata() {
+            return None;
+        }
+        Some(|d, data| d.emit("FrSimEvent", &data))
+    }
+
+    fn static_data(&mut self) -> Option<fn(&mut Self, data: StaticData)> {
+        if !self.metadata() {
+            return None;
+        }
+        Some(|d, mut data| {
+            data.data = data.data.map(|v| d.trim(v));
+            d.emit("FrStatData", &data)
+        })
+    }
+
+    fn summary(&mut self) -> Option<fn(&mut Self, data: Summary)> {
+        if !self.metadata() {
+            return None;
+        }
+        Some(|d, data| d.emit("FrSummary", &data))
+    }
+
+    fn table(&mut self) -> Option<fn(&mut Self, data: Table)> {
+        if !self.metadata() {
+            return None;
+        }
+        Some(|d, mut data| {
+            data.columns = data.columns.into_iter().map(|v| d.trim(v)).collect();
+            d.emit("FrTable", &data)
+        })
+    }
+
+    fn vector(&mut self) -> Option<fn(&mut Self, data: Vector)> {
+        if !self.metadata() {
+            return None;
+        }
+        Some(|d, data| {
+            let data = d.trim(data);
+            d.emit("FrVect", &data)
+        })
+    }
+}
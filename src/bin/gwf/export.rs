@@ -0,0 +1,380 @@
+use crate::args::{invalid, Args};
+use gwf::gps::GpsTime;
+use gwf::npy::{self, Excerpt};
+use gwf::timeseries::{Collector, Samples, TimeSeries};
+use std::fs::File;
+use std::io::{self, BufWriter, Error, ErrorKind, Result, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+pub const USAGE: &str = "usage: gwf export --channels NAME,... [options] FILE...
+
+Writes channels of the files, which must be given in time order, as a table
+with a time column and a value column per channel; complex channels have a
+real and an imaginary column.  Every channel must have the same sample times.
+
+Parquet files, which need gwf built with the arrow feature and an --output,
+keep the sample types and have a row group per frame; the schema metadata
+holds each channel structure and vector info as JSON.
+
+NumPy files keep the sample types and the shape of each vector; time series
+of consecutive frames are joined.  npy writes NAME.npy and NAME.json, the
+vector info, per channel into the --output directory; npz writes one
+--output archive of arrays named by channel and the vector infos in a JSON
+file beside it, e.g. excerpt.npz and excerpt.json.
+
+options:
+  --channels NAME,...  the channels to export
+  --format csv|tsv|parquet|npy|npz
+                       comma or tab separated values (default csv), Parquet
+                       or NumPy
+  --start GPS          the start of the time range
+  --end GPS            the end of the time range
+  --relative           times in seconds from the first sample instead of GPS
+  --output PATH        the file to write instead of standard output, or the
+                       directory for npy";
+
+/// Format is the file format written.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum Format {
+    Csv,
+    Tsv,
+    Parquet,
+    Npy,
+    Npz,
+}
+
+impl FromStr for Format {
+    type Err = io::Error;
+    fn from_str(s: &str) -> Result<Format> {
+        match s {
+            "csv" => Ok(Format::Csv),
+            "tsv" => Ok(Format::Tsv),
+            "parquet" => Ok(Format::Parquet),
+            "npy" => Ok(Format::Npy),
+            "npz" => Ok(Format::Npz),
+            _ => Err(invalid(format!("unknown format: {}", s))),
+        }
+    }
+}
+
+pub fn run(mut args: Args) -> Result<()> {
+    let channels = args.option("channels")?.unwrap_or_default();
+    let format = args.parse("format")?.unwrap_or(Format::Csv);
+    let start: Option<GpsTime> = args.parse("start")?;
+    let end: Option<GpsTime> = args.parse("end")?;
+    let relative = args.flag("relative");
+    let output = args.option("output")?;
+    let files = args.positional()?;
+    let names: Vec<&str> = channels.split(',').filter(|n| !n.is_empty()).collect();
+    if names.is_empty() || files.is_empty() {
+        return Err(invalid(USAGE.to_string()));
+    }
+
+    match (format, output.as_deref()) {
+        (Format::Parquet, Some(o)) => return parquet(&files, &names, start, end, o),
+        (Format::Npy, Some(o)) | (Format::Npz, Some(o)) => {
+            return numpy(&files, &names, start, end, format, o)
+        }
+        (Format::Parquet, None) | (Format::Npy, None) | (Format::Npz, None) => {
+            return Err(invalid(format!(
+                "--format {} needs an --output",
+                format!("{:?}", format).to_lowercase()
+            )));
+        }
+        _ => {}
+    }
+
+    let series = read(&files, &names, start, end)?;
+    let out: Box<dyn Write> = match &output {
+        Some(o) => Box::new(File::create(o).map_err(|e| crate::in_file(o, e))?),
+        None => Box::new(io::stdout()),
+    };
+    let mut out = BufWriter::new(out);
+    match format {
+        Format::Csv => table(&mut out, &series, ',', relative)?,
+        Format::Tsv => table(&mut out, &series, '\t', relative)?,
+        _ => unreachable!(),
+    }
+    out.flush()
+}
+
+/// parquet writes the named channels of the files to a Parquet file.
+#[cfg(feature = "arrow")]
+fn parquet(
+    files: &[String],
+    names: &[&str],
+    start: Option<GpsTime>,
+    end: Option<GpsTime>,
+    output: &str,
+) -> Result<()> {
+    let out = File::create(output).map_err(|e| crate::in_file(output, e))?;
+    let mut exporter = gwf::arrow::ParquetExporter::new(BufWriter::new(out), names);
+    exporter.window(start, end);
+    for filename in files {
+        let mut reader = crate::open(filename)?;
+        gwf::parse(&mut reader, &mut exporter).map_err(|e| crate::in_file(filename, e))?;
+    }
+    let mut out = exporter.finish().map_err(|e| crate::in_file(output, e))?;
+    out.flush()
+}
+
+#[cfg(not(feature = "arrow"))]
+fn parquet(
+    _files: &[String],
+    _names: &[&str],
+    _start: Option<GpsTime>,
+    _end: Option<GpsTime>,
+    _output: &str,
+) -> Result<()> {
+    Err(invalid(
+        "gwf was built without the arrow feature needed for parquet".to_string(),
+    ))
+}
+
+/// numpy writes the named channels of the files as .npy files in the output
+/// directory or as a .npz file, with their vector infos as JSON.
+fn numpy(
+    files: &[String],
+    names: &[&str],
+    start: Option<GpsTime>,
+    end: Option<GpsTime>,
+    format: Format,
+    output: &str,
+) -> Result<()> {
+    let mut excerpt = Excerpt::new(names);
+    excerpt.window(start, end);
+    for filename in files {
+        let mut reader = crate::open(filename)?;
+        gwf::parse(&mut reader, &mut excerpt).map_err(|e| crate::in_file(filename, e))?;
+    }
+    let vectors = excerpt.finish()?;
+
+    let create = |path: &Path| {
+        File::create(path)
+            .map(BufWriter::new)
+            .map_err(|e| crate::in_file(&path.to_string_lossy(), e))
+    };
+    if format == Format::Npy {
+        let dir = Path::new(output);
+        std::fs::create_dir_all(dir).map_err(|e| crate::in_file(output, e))?;
+        for (channel, data) in vectors.iter() {
+            let name = channel.name();
+            let path = dir.join(format!("{}.npy", name));
+            let mut out = create(&path)?;
+            npy::write_npy(&mut out, data)?;
+            out.flush()?;
+            let mut out = create(&dir.join(format!("{}.json", name)))?;
+            serde_json::to_writer_pretty(&mut out, data.info())?;
+            out.flush()?;
+        }
+        return Ok(());
+    }
+
+    let path = Path::new(output);
+    let mut npz = npy::NpzWriter::new(create(path)?);
+    let mut infos = serde_json::Map::new();
+    for (channel, data) in vectors.iter() {
+        npz.add(channel.name(), data)?;
+        infos.insert(
+            channel.name().to_string(),
+            serde_json::to_value(data.info())?,
+        );
+    }
+    npz.finish()?.flush()?;
+    let mut out = create(&path.with_extension("json"))?;
+    serde_json::to_writer_pretty(&mut out, &infos)?;
+    out.flush()
+}
+
+/// read collects the named channels of the files within the time range.
+/// Each channel is a list of contiguous series.
+fn read(
+    files: &[String],
+    names: &[&str],
+    start: Option<GpsTime>,
+    end: Option<GpsTime>,
+) -> Result<Vec<Vec<TimeSeries>>> {
+    let mut collector = Collector::new(names);
+    for filename in files {
+        let mut reader = crate::open(filename)?;
+        gwf::parse(&mut reader, &mut collector).map_err(|e| crate::in_file(filename, e))?;
+    }
+    if let Some(e) = collector.errors().first() {
+        return Err(Error::new(e.kind(), e.to_string()));
+    }
+
+    let start = start.map_or(f64::NEG_INFINITY, |t| t.as_seconds());
+    let end = end.map_or(f64::INFINITY, |t| t.as_seconds());
+    let mut segments = collector.into_segments();
+    let mut channels = Vec::with_capacity(names.len());
+    for name in names {
+        let series: Vec<TimeSeries> = segments
+            .remove(*name)
+            .map(|s| s.series)
+            .unwrap_or_default()
+            .iter()
+            .map(|s| s.window(start, end))
+            .filter(|s| !s.is_empty())
+            .collect();
+        if series.is_empty() {
+            return Err(Error::new(
+                ErrorKind::NotFound,
+                format!("{}: no data in the time range", name),
+            ));
+        }
+        channels.push(series);
+    }
+    Ok(channels)
+}
+
+/// same_times checks that every channel has the same sample times as the first.
+fn same_times(channels: &[Vec<TimeSeries>]) -> Result<()> {
+    let first = &channels[0];
+    for c in channels[1..].iter() {
+        let same = c.len() == first.len()
+            && c.iter().zip(first.iter()).all(|(a, b)| {
+                a.len() == b.len()
+                    && a.sample_rate == b.sample_rate
+                    && (a.gps_start_s - b.gps_start_s).abs() < 0.5 / a.sample_rate
+            });
+        if !same {
+            return Err(invalid(format!(
+                "{} and {} have different sample times; export them separately",
+                first[0].name, c[0].name
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// table writes a header row then a row per sample time.
+fn table<W: Write>(
+    out: &mut W,
+    channels: &[Vec<TimeSeries>],
+    separator: char,
+    relative: bool,
+) -> Result<()> {
+    same_times(channels)?;
+    let mut header = vec!["time".to_string()];
+    for c in channels.iter() {
+        match c[0].samples {
+            Samples::Real(_) => header.push(c[0].name.clone()),
+            Samples::Complex(_) => {
+                header.push(format!("{}.real", c[0].name));
+                header.push(format!("{}.imag", c[0].name));
+            }
+        }
+    }
+    let header: Vec<String> = header.iter().map(|h| quote(h, separator)).collect();
+    writeln!(out, "{}", header.join(&separator.to_string()))?;
+
+    let origin = channels[0][0].gps_start_s;
+    for (i, series) in channels[0].iter().enumerate() {
+        let start = GpsTime::from_seconds(series.gps_start_s)?;
+        for j in 0..series.len() {
+            let dt = j as f64 / series.sample_rate;
+            if relative {
+                write!(out, "{}", series.gps_start_s - origin + dt)?;
+            } else {
+                write!(out, "{}", start + dt)?;
+            }
+            for c in channels.iter() {
+                match &c[i].samples {
+                    Samples::Real(v) => write!(out, "{}{}", separator, v[j])?,
+                    Samples::Complex(v) => write!(
+                        out,
+                        "{}{}{}{}",
+                        separator, v[j].real, separator, v[j].imaginary
+                    )?,
+                }
+            }
+            writeln!(out)?;
+        }
+    }
+    Ok(())
+}
+
+/// quote quotes a field holding the separator or a quote.
+fn quote(field: &str, separator: char) -> String {
+    if field.contains(separator) || field.contains('"') {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod export_tests {
+    use super::*;
+    use gwf::structures::Complex;
+
+    fn series(name: &str, gps_start_s: f64, samples: Samples) -> TimeSeries {
+        TimeSeries {
+            name: name.to_string(),
+            gps_start_s,
+            sample_rate: 2.0,
+            unit: String::new(),
+            samples,
+        }
+    }
+
+    fn export(channels: &[Vec<TimeSeries>], separator: char, relative: bool) -> String {
+        let mut out = Vec::new();
+        table(&mut out, channels, separator, relative).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_table() {
+        let a = vec![series("H1:A", 10.0, Samples::Real(vec![1.0, 2.5]))];
+        let b = vec![series(
+            "H1:B,C",
+            10.0,
+            Samples::Complex(vec![
+                Complex {
+                    real: 1.0,
+                    imaginary: -1.0,
+                },
+                Complex {
+                    real: 0.5,
+                    imaginary: 0.0,
+                },
+            ]),
+        )];
+        let channels = vec![a, b];
+        assert_eq!(
+            export(&channels, ',', true),
+            "time,H1:A,\"H1:B,C.real\",\"H1:B,C.imag\"\n0,1,1,-1\n0.5,2.5,0.5,0\n"
+        );
+        assert_eq!(
+            export(&channels, '\t', false),
+            "time\tH1:A\tH1:B,C.real\tH1:B,C.imag\n10\t1\t1\t-1\n10.5\t2.5\t0.5\t0\n"
+        );
+    }
+
+    #[test]
+    fn test_same_times() {
+        let a = || series("H1:A", 10.0, Samples::Real(vec![1.0, 2.0]));
+        assert!(same_times(&[vec![a()], vec![a()]]).is_ok());
+
+        let later = series("H1:B", 10.5, Samples::Real(vec![1.0, 2.0]));
+        let shorter = series("H1:B", 10.0, Samples::Real(vec![1.0]));
+        let split = vec![
+            series("H1:B", 10.0, Samples::Real(vec![1.0])),
+            series("H1:B", 10.5, Samples::Real(vec![2.0])),
+        ];
+        for b in vec![vec![later], vec![shorter], split] {
+            let err = same_times(&[vec![a()], b]).unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::InvalidInput);
+        }
+    }
+
+    #[test]
+    fn test_quote() {
+        assert_eq!(quote("H1:A", ','), "H1:A");
+        assert_eq!(quote("a,b", ','), "\"a,b\"");
+        assert_eq!(quote("a,b", '\t'), "a,b");
+        assert_eq!(quote("say \"hi\"", ','), "\"say \"\"hi\"\"\"");
+    }
+}
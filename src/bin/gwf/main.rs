@@ -0,0 +1,68 @@
+mod args;
+mod channels;
+mod cut;
+mod dump;
+mod export;
+mod verify;
+mod wav;
+
+use args::Args;
+use std::fs::File;
+use std::io::{BufReader, Error, Result};
+use std::process::exit;
+
+const USAGE: &str = "usage: gwf <command> [options] FILE...
+
+commands:
+  channels  list the channels of frame files
+  cut       copy channels and a time range into a new file (also: copy)
+  dump      print the structures of frame files
+  export    write channels as csv or tsv tables, Parquet or NumPy files
+  verify    check frame files for corruption
+  wav       write a channel as a WAV audio file
+
+Run gwf <command> --help for the options of a command.";
+
+fn main() {
+    let mut argv = std::env::args().skip(1);
+    let command = argv.next().unwrap_or_default();
+    let mut args = Args::new(argv);
+    let help = args.flag("help");
+
+    let (usage, run): (&str, fn(Args) -> Result<()>) = match command.as_str() {
+        "channels" => (channels::USAGE, channels::run),
+        "cut" | "copy" => (cut::USAGE, cut::run),
+        "dump" => (dump::USAGE, dump::run),
+        "export" => (export::USAGE, export::run),
+        "verify" => (verify::USAGE, verify::run),
+        "wav" => (wav::USAGE, wav::run),
+        "help" | "--help" | "-h" => {
+            println!("{}", USAGE);
+            return;
+        }
+        _ => {
+            eprintln!("{}", USAGE);
+            exit(2);
+        }
+    };
+    if help {
+        println!("{}", usage);
+        return;
+    }
+    if let Err(e) = run(args) {
+        eprintln!("gwf {}: {}", command, e);
+        exit(1);
+    }
+}
+
+/// open opens a frame file for reading; errors name the file.
+pub fn open(path: &str) -> Result<BufReader<File>> {
+    File::open(path)
+        .map(BufReader::new)
+        .map_err(|e| in_file(path, e))
+}
+
+/// in_file prefixes the error with the file name.
+pub fn in_file(path: &str, e: Error) -> Error {
+    Error::new(e.kind(), format!("{}: {}", path, e))
+}
@@ -0,0 +1,93 @@
+use crate::args::{invalid, Args};
+use crate::dump::Format;
+use gwf::verify::Problem;
+use serde::Serialize;
+use std::io::{self, BufWriter, Error, ErrorKind, Result, Write};
+
+pub const USAGE: &str = "usage: gwf verify [--format text|json] [--quiet] FILE...
+
+Reads every byte of the files and prints each problem found with its byte
+offset: a bad file header, structure lengths that do not match their
+contents, classes missing from the dictionary, dangling pointers, a table
+of contents that does not match the file, and wrong checksums.
+
+  --quiet  only print files with problems
+
+The exit status is 0 when every file verifies and 1 otherwise.";
+
+/// Row is one problem of a file, or a file without problems.
+#[derive(Serialize)]
+struct Row<'a> {
+    file: &'a str,
+    #[serde(flatten)]
+    problem: Option<&'a Problem>,
+}
+
+pub fn run(mut args: Args) -> Result<()> {
+    let format = args.parse("format")?.unwrap_or(Format::Text);
+    let quiet = args.flag("quiet");
+    let files = args.positional()?;
+    if files.is_empty() {
+        return Err(invalid(USAGE.to_string()));
+    }
+
+    let mut out = BufWriter::new(io::stdout());
+    let mut bad = 0;
+    for filename in files.iter() {
+        let problems = match std::fs::File::open(filename) {
+            Ok(f) => gwf::verify::verify(&mut io::BufReader::new(f)),
+            Err(e) => Err(e),
+        }
+        .unwrap_or_else(|e| {
+            // a file that cannot be read is reported like a problem at its start.
+            vec![Problem {
+                offset: 0,
+                message: e.to_string(),
+            }]
+        });
+        if !problems.is_empty() {
+            bad += 1;
+        } else if quiet {
+            continue;
+        }
+        match format {
+            Format::Text if problems.is_empty() => writeln!(out, "{}: ok", filename)?,
+            Format::Text => {
+                for p in problems.iter() {
+                    writeln!(out, "{}: {}", filename, p)?;
+                }
+            }
+            Format::Json if problems.is_empty() => {
+                serde_json::to_writer(
+                    &mut out,
+                    &Row {
+                        file: filename,
+                        problem: None,
+                    },
+                )?;
+                writeln!(out)?;
+            }
+            Format::Json => {
+                for p in problems.iter() {
+                    serde_json::to_writer(
+                        &mut out,
+                        &Row {
+                            file: filename,
+                            problem: Some(p),
+                        },
+                    )?;
+                    writeln!(out)?;
+                }
+            }
+        }
+    }
+    out.flush()?;
+
+    if bad > 0 {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!("{} of {} files have problems", bad, files.len()),
+        ));
+    }
+    Ok(())
+}
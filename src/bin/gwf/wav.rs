@@ -0,0 +1,96 @@
+use crate::args::{invalid, Args};
+use gwf::gps::GpsTime;
+use gwf::timeseries::Collector;
+use gwf::wav::{Encoding, Wav};
+use std::fs::File;
+use std::io::{BufWriter, Error, ErrorKind, Result, Write};
+
+pub const USAGE: &str = "usage: gwf wav --channel NAME --output FILE [options] FILE...
+
+Writes a channel of the files, which must be given in time order, as a mono
+WAV file.  ADC channels are calibrated with their bias and slope; processed
+channels are written as they are.  The time range must hold no gaps.
+
+options:
+  --channel NAME       the channel to write
+  --output FILE        the WAV file to write
+  --start GPS          the start of the time range
+  --end GPS            the end of the time range
+  --band LOW,HIGH      keep only frequencies between LOW and HIGH Hz
+  --normalize          scale the loudest sample to full scale; PCM files of
+                       strain are silent without it
+  --rate HZ            the playback rate; by default the channel's sample
+                       rate, and higher rates play faster and higher
+  --encoding ENCODING  pcm16 (default), pcm24, pcm32 or float32";
+
+pub fn run(mut args: Args) -> Result<()> {
+    let name = args.option("channel")?;
+    let output = args.option("output")?;
+    let start: Option<GpsTime> = args.parse("start")?;
+    let end: Option<GpsTime> = args.parse("end")?;
+    let band = args.option("band")?;
+    let normalize = args.flag("normalize");
+    let rate: Option<u32> = args.parse("rate")?;
+    let encoding = args.parse("encoding")?.unwrap_or(Encoding::Pcm16);
+    let files = args.positional()?;
+    let (name, output) = match (name, output) {
+        (Some(n), Some(o)) if !files.is_empty() => (n, o),
+        _ => return Err(invalid(USAGE.to_string())),
+    };
+
+    let mut wav = Wav::new(encoding);
+    wav.normalize(normalize);
+    if let Some(r) = rate {
+        wav.playback_rate(r);
+    }
+    if let Some(b) = band {
+        let (low, high) = b
+            .split_once(',')
+            .and_then(|(l, h)| Some((l.parse().ok()?, h.parse().ok()?)))
+            .ok_or_else(|| invalid(format!("invalid --band: {}", b)))?;
+        wav.band(low, high);
+    }
+
+    let mut collector = Collector::new(&[&name]);
+    collector.calibrate(true);
+    for filename in files.iter() {
+        let mut reader = crate::open(filename)?;
+        gwf::parse(&mut reader, &mut collector).map_err(|e| crate::in_file(filename, e))?;
+    }
+    if let Some(e) = collector.errors().first() {
+        return Err(Error::new(e.kind(), e.to_string()));
+    }
+    let start = start.map_or(f64::NEG_INFINITY, |t| t.as_seconds());
+    let end = end.map_or(f64::INFINITY, |t| t.as_seconds());
+    let series: Vec<_> = collector
+        .into_segments()
+        .remove(&name)
+        .map(|s| s.series)
+        .unwrap_or_default()
+        .iter()
+        .map(|s| s.window(start, end))
+        .filter(|s| !s.is_empty())
+        .collect();
+    let series = match series.as_slice() {
+        [s] => s,
+        [] => {
+            return Err(Error::new(
+                ErrorKind::NotFound,
+                format!("{}: no data in the time range", name),
+            ))
+        }
+        [first, second, ..] => {
+            return Err(invalid(format!(
+                "{}: gap from {} to {}; choose a time range without gaps",
+                name,
+                GpsTime::from_seconds(first.gps_end_s())?,
+                GpsTime::from_seconds(second.gps_start_s)?
+            )))
+        }
+    };
+
+    let out = File::create(&output).map_err(|e| crate::in_file(&output, e))?;
+    let mut out = BufWriter::new(out);
+    wav.write(&mut out, series)?;
+    out.flush()
+}
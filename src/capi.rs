@@ -0,0 +1,643 @@
+//! capi is a C interface to the parser.  Build a library with
+//! `cargo rustc --release --lib --features capi --crate-type staticlib` (or
+//! `cdylib`) and include `include/gwf.h`, which is generated with
+//! `cbindgen --config cbindgen.toml --output include/gwf.h`.
+//!
+//! A file is opened into an opaque handle that holds every frame.  Frames
+//! and their channels are iterated by index and channels are read by name
+//! into buffers the caller owns.  Every function returns a GwfStatus; the
+//! message of the last failure on the calling thread is copied out by
+//! gwf_last_error.
+
+use crate::handler::Handler;
+use crate::structures::*;
+use std::cell::RefCell;
+use std::ffi::CStr;
+use std::io::{BufReader, Error, ErrorKind, Result};
+use std::os::raw::c_char;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+/// GwfStatus is the result of every call.
+#[repr(i32)]
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum GwfStatus {
+    Ok = 0,
+    /// InvalidArgument is a null pointer, a bad index or a bad path.
+    InvalidArgument = 1,
+    /// NotFound is a missing file or channel.
+    NotFound = 2,
+    /// InvalidData is a file that cannot be parsed.
+    InvalidData = 3,
+    /// Io is any other failure to read the file.
+    Io = 4,
+    /// BufferTooSmall is a buffer shorter than the data; the length
+    /// needed is still reported.
+    BufferTooSmall = 5,
+    /// Unsupported is data that cannot be read as asked, e.g. strings.
+    Unsupported = 6,
+    /// Internal is a bug in the library.
+    Internal = 7,
+}
+
+impl From<&Error> for GwfStatus {
+    fn from(e: &Error) -> GwfStatus {
+        match e.kind() {
+            ErrorKind::InvalidInput => GwfStatus::InvalidArgument,
+            ErrorKind::NotFound => GwfStatus::NotFound,
+            ErrorKind::InvalidData | ErrorKind::UnexpectedEof | ErrorKind::Other => {
+                GwfStatus::InvalidData
+            }
+            _ => GwfStatus::Io,
+        }
+    }
+}
+
+/// GwfType is the sample type of a channel.  Complex samples are pairs of
+/// doubles, the real part first.
+#[repr(i32)]
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum GwfType {
+    I8 = 0,
+    U8 = 1,
+    I16 = 2,
+    U16 = 3,
+    I32 = 4,
+    U32 = 5,
+    U64 = 6,
+    I64 = 7,
+    F32 = 8,
+    F64 = 9,
+    String = 10,
+    Complex128 = 11,
+}
+
+/// GwfKind is the structure type of a channel.
+#[repr(i32)]
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum GwfKind {
+    Adc = 0,
+    PostProcessed = 1,
+    Simulation = 2,
+    Serial = 3,
+}
+
+/// GwfFrameInfo describes a frame.
+#[repr(C)]
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct GwfFrameInfo {
+    pub run: i32,
+    pub frame: u32,
+    pub data_quality: u32,
+    pub gps_start_time_s: u32,
+    pub gps_residual_time_ns: u32,
+    pub frame_length_s: f64,
+    pub num_channels: usize,
+}
+
+/// GwfChannelInfo describes a channel of a frame and its data.
+#[repr(C)]
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct GwfChannelInfo {
+    pub kind: GwfKind,
+    pub data_type: GwfType,
+    pub num_samples: u64,
+    pub num_dimensions: u32,
+    /// sample_rate is the inverse of the first dimension's spacing, or 0.
+    pub sample_rate: f64,
+    /// gps_start_s is the GPS time of the first sample: the frame start
+    /// plus the channel's offset and the vector's origin.
+    pub gps_start_s: f64,
+    /// bias and slope calibrate ADC counts as `slope * (x + bias)`; other
+    /// channels have 0 and 1.
+    pub bias: f64,
+    pub slope: f64,
+}
+
+/// GwfFile is an open frame file.
+pub struct GwfFile {
+    frames: Vec<Frame>,
+}
+
+struct Frame {
+    header: FrameHeader,
+    channels: Vec<(Channel, Vector)>,
+}
+
+impl Handler for GwfFile {
+    fn begin_frame(&mut self) -> Option<fn(&mut Self, header: FrameHeader)> {
+        Some(|f, header| {
+            f.frames.push(Frame {
+                header,
+                channels: Vec::new(),
+            })
+        })
+    }
+
+    fn channel(&mut self) -> Option<fn(&mut Self, channel: Channel, data: Vector)> {
+        Some(|f, c, v| {
+            if let Some(frame) = f.frames.last_mut() {
+                frame.channels.push((c, v));
+            }
+        })
+    }
+}
+
+impl GwfFile {
+    fn frame(&self, frame: usize) -> Result<&Frame> {
+        self.frames.get(frame).ok_or_else(|| {
+            invalid(format!(
+                "frame {} of a file with {} frames",
+                frame,
+                self.frames.len()
+            ))
+        })
+    }
+
+    fn channel(&self, frame: usize, name: *const c_char) -> Result<(&Frame, &Channel, &Vector)> {
+        let f = self.frame(frame)?;
+        let name = string(name)?;
+        f.channels
+            .iter()
+            .find(|(c, _)| c.name() == name)
+            .map(|(c, v)| (f, c, v))
+            .ok_or_else(|| {
+                Error::new(
+                    ErrorKind::NotFound,
+                    format!("{}: not in frame {}", name, frame),
+                )
+            })
+    }
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<String> = const { RefCell::new(String::new()) };
+}
+
+/// call runs f, recording its error or panic for gwf_last_error.  Short
+/// buffers fail with WriteZero and unreadable data with Unsupported.
+fn call<F: FnOnce() -> Result<()>>(f: F) -> GwfStatus {
+    let (status, message) = match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(())) => (GwfStatus::Ok, String::new()),
+        Ok(Err(e)) if e.kind() == ErrorKind::WriteZero => {
+            (GwfStatus::BufferTooSmall, e.to_string())
+        }
+        Ok(Err(e)) if e.kind() == ErrorKind::Unsupported => (GwfStatus::Unsupported, e.to_string()),
+        Ok(Err(e)) => (GwfStatus::from(&e), e.to_string()),
+        Err(_) => (GwfStatus::Internal, "panic in gwf".to_string()),
+    };
+    if status != GwfStatus::Ok {
+        LAST_ERROR.with(|m| *m.borrow_mut() = message);
+    }
+    status
+}
+
+fn invalid(message: String) -> Error {
+    Error::new(ErrorKind::InvalidInput, message)
+}
+
+fn null(name: &str) -> Error {
+    invalid(format!("{} is null", name))
+}
+
+fn string<'a>(s: *const c_char) -> Result<&'a str> {
+    if s.is_null() {
+        return Err(null("string"));
+    }
+    // callers promise a NUL terminated string.
+    unsafe { CStr::from_ptr(s) }
+        .to_str()
+        .map_err(|_| invalid("string is not UTF-8".to_string()))
+}
+
+fn data_type(v: &Vector) -> GwfType {
+    match v {
+        Vector::I8(_, _) => GwfType::I8,
+        Vector::U8(_, _) => GwfType::U8,
+        Vector::I16(_, _) => GwfType::I16,
+        Vector::U16(_, _) => GwfType::U16,
+        Vector::I32(_, _) => GwfType::I32,
+        Vector::U32(_, _) => GwfType::U32,
+        Vector::U64(_, _) => GwfType::U64,
+        Vector::I64(_, _) => GwfType::I64,
+        Vector::F32(_, _) => GwfType::F32,
+        Vector::F64(_, _) => GwfType::F64,
+        Vector::Strings(_, _) => GwfType::String,
+        Vector::Complexes(_, _) => GwfType::Complex128,
+    }
+}
+
+/// gwf_open reads every frame of the file at path.  Close the file with
+/// gwf_close.
+///
+/// # Safety
+///
+/// path must be a NUL terminated string and file a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn gwf_open(path: *const c_char, file: *mut *mut GwfFile) -> GwfStatus {
+    call(|| {
+        if file.is_null() {
+            return Err(null("file"));
+        }
+        let path = string(path)?;
+        let reader = std::fs::File::open(path)
+            .map_err(|e| Error::new(e.kind(), format!("{}: {}", path, e)))?;
+        let mut f = GwfFile { frames: Vec::new() };
+        crate::parse(&mut BufReader::new(reader), &mut f)
+            .map_err(|e| Error::new(e.kind(), format!("{}: {}", path, e)))?;
+        *file = Box::into_raw(Box::new(f));
+        Ok(())
+    })
+}
+
+/// gwf_close frees a file opened by gwf_open.  Null is ignored.
+///
+/// # Safety
+///
+/// file must come from gwf_open and not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn gwf_close(file: *mut GwfFile) {
+    if !file.is_null() {
+        drop(Box::from_raw(file));
+    }
+}
+
+/// gwf_frame_count sets count to the number of frames in the file.
+///
+/// # Safety
+///
+/// file must come from gwf_open and count be a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn gwf_frame_count(file: *const GwfFile, count: *mut usize) -> GwfStatus {
+    call(|| {
+        let f = file.as_ref().ok_or_else(|| null("file"))?;
+        let count = count.as_mut().ok_or_else(|| null("count"))?;
+        *count = f.frames.len();
+        Ok(())
+    })
+}
+
+/// gwf_frame_info describes frame, counting from 0.
+///
+/// # Safety
+///
+/// file must come from gwf_open and info be a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn gwf_frame_info(
+    file: *const GwfFile,
+    frame: usize,
+    info: *mut GwfFrameInfo,
+) -> GwfStatus {
+    call(|| {
+        let f = file.as_ref().ok_or_else(|| null("file"))?.frame(frame)?;
+        let info = info.as_mut().ok_or_else(|| null("info"))?;
+        let h = &f.header;
+        *info = GwfFrameInfo {
+            run: h.run,
+            frame: h.frame,
+            data_quality: h.data_quality.0,
+            gps_start_time_s: h.gps_start_time_s,
+            gps_residual_time_ns: h.gps_residual_time_ns,
+            frame_length_s: h.frame_length_s,
+            num_channels: f.channels.len(),
+        };
+        Ok(())
+    })
+}
+
+/// gwf_channel_name copies the NUL terminated name of a frame's channel,
+/// counting from 0, into name and sets needed to the buffer length it takes.
+/// A short buffer gets BufferTooSmall and is left untouched.
+///
+/// # Safety
+///
+/// file must come from gwf_open, name must have len bytes and needed be a
+/// valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn gwf_channel_name(
+    file: *const GwfFile,
+    frame: usize,
+    channel: usize,
+    name: *mut c_char,
+    len: usize,
+    needed: *mut usize,
+) -> GwfStatus {
+    call(|| {
+        let f = file.as_ref().ok_or_else(|| null("file"))?.frame(frame)?;
+        let (c, _) = f.channels.get(channel).ok_or_else(|| {
+            invalid(format!(
+                "channel {} of a frame with {} channels",
+                channel,
+                f.channels.len()
+            ))
+        })?;
+        let bytes = c.name().as_bytes();
+        let needed = needed.as_mut().ok_or_else(|| null("needed"))?;
+        *needed = bytes.len() + 1;
+        if len < bytes.len() + 1 {
+            return Err(Error::new(
+                ErrorKind::WriteZero,
+                format!("{} needs {} bytes, not {}", c.name(), bytes.len() + 1, len),
+            ));
+        }
+        if name.is_null() {
+            return Err(null("name"));
+        }
+        std::ptr::copy_nonoverlapping(bytes.as_ptr(), name as *mut u8, bytes.len());
+        *name.add(bytes.len()) = 0;
+        Ok(())
+    })
+}
+
+/// gwf_channel_info describes the named channel of frame.
+///
+/// # Safety
+///
+/// file must come from gwf_open, name be a NUL terminated string and info
+/// a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn gwf_channel_info(
+    file: *const GwfFile,
+    frame: usize,
+    name: *const c_char,
+    info: *mut GwfChannelInfo,
+) -> GwfStatus {
+    call(|| {
+        let (f, c, v) = file
+            .as_ref()
+            .ok_or_else(|| null("file"))?
+            .channel(frame, name)?;
+        let info = info.as_mut().ok_or_else(|| null("info"))?;
+        let vector = v.info();
+        let spacing = vector.sample_spacing.first().copied().unwrap_or(0.0);
+        let origin = vector.x_origins.first().copied().unwrap_or(0.0);
+        let (bias, slope) = match c {
+            Channel::ADC(a) => (a.bias as f64, a.slope as f64),
+            _ => (0.0, 1.0),
+        };
+        *info = GwfChannelInfo {
+            kind: match c {
+                Channel::ADC(_) => GwfKind::Adc,
+                Channel::PostProcessed(_) => GwfKind::PostProcessed,
+                Channel::Simulation(_) => GwfKind::Simulation,
+                Channel::Serial(_) => GwfKind::Serial,
+            },
+            data_type: data_type(v),
+            num_samples: v.len() as u64,
+            num_dimensions: vector.num_dimensions,
+            sample_rate: if spacing > 0.0 { 1.0 / spacing } else { 0.0 },
+            gps_start_s: (c.start_time(&f.header) + origin).as_seconds(),
+            bias,
+            slope,
+        };
+        Ok(())
+    })
+}
+
+/// gwf_read_channel copies the samples of the named channel of frame, in
+/// their own type and native byte order, into data of len bytes and sets
+/// samples to their number, even when data is too short.  Strings are
+/// Unsupported.
+///
+/// # Safety
+///
+/// file must come from gwf_open, name be a NUL terminated string, data
+/// have len bytes and samples be a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn gwf_read_channel(
+    file: *const GwfFile,
+    frame: usize,
+    name: *const c_char,
+    data: *mut u8,
+    len: usize,
+    samples: *mut usize,
+) -> GwfStatus {
+    call(|| {
+        let (_, c, v) = file
+            .as_ref()
+            .ok_or_else(|| null("file"))?
+            .channel(frame, name)?;
+        let samples = samples.as_mut().ok_or_else(|| null("samples"))?;
+        let bytes: Vec<u8> = {
+            macro_rules! bytes {
+                ($($variant:ident),*) => {
+                    match v {
+                        $(Vector::$variant(_, x) => x.iter().flat_map(|s| s.to_ne_bytes()).collect(),)*
+                        Vector::Complexes(_, x) => x
+                            .iter()
+                            .flat_map(|s| [s.real.to_ne_bytes(), s.imaginary.to_ne_bytes()])
+                            .flatten()
+                            .collect(),
+                        Vector::Strings(_, _) => {
+                            return Err(Error::new(
+                                ErrorKind::Unsupported,
+                                format!("{}: strings cannot be read as samples", c.name()),
+                            ))
+                        }
+                    }
+                };
+            }
+            bytes!(I8, U8, I16, U16, I32, U32, U64, I64, F32, F64)
+        };
+        *samples = v.len();
+        copy(c.name(), &bytes, data, len)
+    })
+}
+
+/// gwf_read_channel_f64 copies the samples of the named channel of frame,
+/// converted to doubles, into data of len doubles and sets samples to their
+/// number, even when data is too short.  Complex samples and strings are
+/// Unsupported.
+///
+/// # Safety
+///
+/// file must come from gwf_open, name be a NUL terminated string, data
+/// have len doubles and samples be a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn gwf_read_channel_f64(
+    file: *const GwfFile,
+    frame: usize,
+    name: *const c_char,
+    data: *mut f64,
+    len: usize,
+    samples: *mut usize,
+) -> GwfStatus {
+    call(|| {
+        let (_, c, v) = file
+            .as_ref()
+            .ok_or_else(|| null("file"))?
+            .channel(frame, name)?;
+        let samples = samples.as_mut().ok_or_else(|| null("samples"))?;
+        let values = v.clone().into_f64().ok_or_else(|| {
+            Error::new(
+                ErrorKind::Unsupported,
+                format!("{}: only real numbers can be read as doubles", c.name()),
+            )
+        })?;
+        let bytes: Vec<u8> = values.iter().flat_map(|x| x.to_ne_bytes()).collect();
+        *samples = values.len();
+        copy(c.name(), &bytes, data as *mut u8, len.saturating_mul(8))
+    })
+}
+
+/// copy copies bytes into the caller's buffer of len bytes.
+unsafe fn copy(name: &str, bytes: &[u8], data: *mut u8, len: usize) -> Result<()> {
+    if len < bytes.len() {
+        return Err(Error::new(
+            ErrorKind::WriteZero,
+            format!("{} needs {} bytes, not {}", name, bytes.len(), len),
+        ));
+    }
+    if bytes.is_empty() {
+        return Ok(());
+    }
+    if data.is_null() {
+        return Err(null("data"));
+    }
+    std::ptr::copy_nonoverlapping(bytes.as_ptr(), data, bytes.len());
+    Ok(())
+}
+
+/// gwf_last_error copies the NUL terminated message of the last failure on
+/// this thread into message, truncated to len bytes, and returns the
+/// message length without the NUL, like snprintf.
+///
+/// # Safety
+///
+/// message must have len bytes, or be null when len is 0.
+#[no_mangle]
+pub unsafe extern "C" fn gwf_last_error(message: *mut c_char, len: usize) -> usize {
+    LAST_ERROR.with(|m| {
+        let m = m.borrow();
+        if !message.is_null() && len > 0 {
+            let n = m.len().min(len - 1);
+            std::ptr::copy_nonoverlapping(m.as_ptr(), message as *mut u8, n);
+            *message.add(n) = 0;
+        }
+        m.len()
+    })
+}
+
+#[cfg(test)]
+mod capi_tests {
+    use super::*;
+    use crate::fixtures;
+    use crate::writer::Frame;
+    use std::ffi::CString;
+
+    /// file writes two frames of run 7 whose ADC H1:A has a bias and slope.
+    fn file() -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("gwf-capi-{}.gwf", std::process::id()));
+        let frames: Vec<Frame> = (0..2)
+            .map(|s| {
+                let mut f = fixtures::frame(s, &["H1:A"]);
+                f.header.run = 7;
+                if let Channel::ADC(adc) = &mut f.channels[0].0 {
+                    adc.bias = 0.5;
+                    adc.slope = 2.0;
+                }
+                f
+            })
+            .collect();
+        std::fs::write(&path, fixtures::write(&frames)).unwrap();
+        path
+    }
+
+    fn last_error() -> String {
+        let mut buf = vec![0 as c_char; 256];
+        let n = unsafe { gwf_last_error(buf.as_mut_ptr(), buf.len()) };
+        let m = unsafe { CStr::from_ptr(buf.as_ptr()) };
+        assert_eq!(m.to_bytes().len(), n.min(255));
+        m.to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn test_capi() {
+        let path = file();
+        let c_path = CString::new(path.to_str().unwrap()).unwrap();
+        let mut f: *mut GwfFile = std::ptr::null_mut();
+        unsafe {
+            assert_eq!(gwf_open(c_path.as_ptr(), &mut f), GwfStatus::Ok);
+            std::fs::remove_file(&path).unwrap();
+
+            let mut count = 0;
+            assert_eq!(gwf_frame_count(f, &mut count), GwfStatus::Ok);
+            assert_eq!(count, 2);
+            let mut frame = std::mem::zeroed::<GwfFrameInfo>();
+            assert_eq!(gwf_frame_info(f, 1, &mut frame), GwfStatus::Ok);
+            assert_eq!(frame.gps_start_time_s, 1_000_000_001);
+            assert_eq!(frame.run, 7);
+            assert_eq!(frame.num_channels, 1);
+            assert_eq!(gwf_frame_info(f, 2, &mut frame), GwfStatus::InvalidArgument);
+
+            let mut name = [0 as c_char; 8];
+            let mut needed = 0;
+            assert_eq!(
+                gwf_channel_name(f, 1, 0, name.as_mut_ptr(), 2, &mut needed),
+                GwfStatus::BufferTooSmall
+            );
+            assert_eq!(needed, 5);
+            assert!(last_error().contains("needs 5 bytes"));
+            assert_eq!(
+                gwf_channel_name(f, 1, 0, name.as_mut_ptr(), name.len(), &mut needed),
+                GwfStatus::Ok
+            );
+            assert_eq!(CStr::from_ptr(name.as_ptr()).to_str().unwrap(), "H1:A");
+
+            let mut info = std::mem::zeroed::<GwfChannelInfo>();
+            assert_eq!(
+                gwf_channel_info(f, 1, name.as_ptr(), &mut info),
+                GwfStatus::Ok
+            );
+            assert_eq!(info.kind, GwfKind::Adc);
+            assert_eq!(info.data_type, GwfType::I16);
+            assert_eq!(info.num_samples, 4);
+            assert_eq!(info.sample_rate, 4.0);
+            assert_eq!(info.gps_start_s, 1_000_000_001.0);
+            assert_eq!((info.bias, info.slope), (0.5, 2.0));
+
+            let mut data = [0i16; 4];
+            let mut samples = 0;
+            assert_eq!(
+                gwf_read_channel(
+                    f,
+                    1,
+                    name.as_ptr(),
+                    data.as_mut_ptr() as *mut u8,
+                    8,
+                    &mut samples
+                ),
+                GwfStatus::Ok
+            );
+            assert_eq!((data, samples), ([4, 5, 6, 7], 4));
+            let mut doubles = [0.0; 4];
+            assert_eq!(
+                gwf_read_channel_f64(f, 0, name.as_ptr(), doubles.as_mut_ptr(), 4, &mut samples),
+                GwfStatus::Ok
+            );
+            assert_eq!(doubles, [0.0, 1.0, 2.0, 3.0]);
+            samples = 0;
+            assert_eq!(
+                gwf_read_channel_f64(f, 0, name.as_ptr(), doubles.as_mut_ptr(), 3, &mut samples),
+                GwfStatus::BufferTooSmall
+            );
+            assert_eq!(samples, 4);
+
+            let missing = CString::new("H1:Z").unwrap();
+            assert_eq!(
+                gwf_channel_info(f, 0, missing.as_ptr(), &mut info),
+                GwfStatus::NotFound
+            );
+            assert_eq!(last_error(), "H1:Z: not in frame 0");
+            assert_eq!(
+                gwf_frame_count(std::ptr::null(), &mut count),
+                GwfStatus::InvalidArgument
+            );
+            gwf_close(f);
+        }
+
+        let mut f: *mut GwfFile = std::ptr::null_mut();
+        let status = unsafe { gwf_open(c_path.as_ptr(), &mut f) };
+        assert_eq!(status, GwfStatus::NotFound);
+        assert!(f.is_null());
+    }
+}
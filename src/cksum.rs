@@ -0,0 +1,79 @@
+/// Cksum is the POSIX.2 `cksum` CRC used for GWF checksums: CRC-32 with
+/// polynomial 0x04C11DB7, most significant bit first, followed by the
+/// length of the data.
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct Cksum {
+    crc: u32,
+    len: u64,
+}
+
+const TABLE: [u32; 256] = table();
+
+const fn table() -> [u32; 256] {
+    let mut t = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut c = (i as u32) << 24;
+        let mut k = 0;
+        while k < 8 {
+            c = if c & 0x8000_0000 != 0 {
+                (c << 1) ^ 0x04c1_1db7
+            } else {
+                c << 1
+            };
+            k += 1;
+        }
+        t[i] = c;
+        i += 1;
+    }
+    t
+}
+
+impl Cksum {
+    pub(crate) fn new() -> Cksum {
+        Cksum::default()
+    }
+
+    pub(crate) fn update(&mut self, buf: &[u8]) {
+        for b in buf {
+            self.crc = (self.crc << 8) ^ TABLE[((self.crc >> 24) as u8 ^ b) as usize];
+        }
+        self.len += buf.len() as u64;
+    }
+
+    /// sum is the checksum of everything updated so far.
+    pub(crate) fn sum(&self) -> u32 {
+        let mut crc = self.crc;
+        let mut len = self.len;
+        while len != 0 {
+            crc = (crc << 8) ^ TABLE[((crc >> 24) as u8 ^ len as u8) as usize];
+            len >>= 8;
+        }
+        !crc
+    }
+}
+
+/// cksum is the checksum of buf.
+pub(crate) fn cksum(buf: &[u8]) -> u32 {
+    let mut c = Cksum::new();
+    c.update(buf);
+    c.sum()
+}
+
+#[cfg(test)]
+mod cksum_tests {
+    use super::*;
+
+    #[test]
+    fn test_cksum() {
+        // values from the cksum utility
+        assert_eq!(cksum(b""), 4294967295);
+        assert_eq!(cksum(b"a"), 1220704766);
+        assert_eq!(cksum(b"123456789"), 930766865);
+
+        let mut c = Cksum::new();
+        c.update(b"1234");
+        c.update(b"56789");
+        assert_eq!(c.sum(), 930766865);
+    }
+}
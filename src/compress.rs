@@ -0,0 +1,270 @@
+use crate::decompress::{
+    code_bits, integer_size, low_bits, max_word, offset, put_word, word, Scheme,
+};
+use crate::structures::Endian;
+use crate::writer::Compression;
+use flate2::write::ZlibEncoder;
+use std::io::{Result, Write};
+
+/// BLOCK is the number of words zero suppression packs with the same width.
+const BLOCK: usize = 16;
+
+/// compress encodes data of the FrVect type class with compression and
+/// returns the scheme it used.  Strings are always stored raw.
+pub(crate) fn compress(
+    data: Vec<u8>,
+    class: u16,
+    compression: Compression,
+    endian: Endian,
+) -> Result<(Scheme, Vec<u8>)> {
+    if class == 8 {
+        return Ok((Scheme::Raw, data));
+    }
+    let integer = integer_size(class);
+    let zero_suppress = integer.and_then(zero_suppress_scheme);
+    Ok(match compression {
+        Compression::Raw => (Scheme::Raw, data),
+        Compression::Gzip(level) => (Scheme::Gzip, gzip(&data, level)?),
+        Compression::DiffGzip(level) => match integer {
+            Some(size) => (Scheme::DiffGzip, diff_gzip(&data, size, level, endian)?),
+            None => (Scheme::Gzip, gzip(&data, level)?),
+        },
+        Compression::ZeroSuppress => match zero_suppress {
+            Some(scheme) => (scheme, zero_suppress_words(&data, scheme, endian)),
+            None => (Scheme::Raw, data),
+        },
+        Compression::Auto(level) => {
+            let mut best = (Scheme::Gzip, gzip(&data, level)?);
+            if let Some(size) = integer {
+                let d = diff_gzip(&data, size, level, endian)?;
+                if d.len() < best.1.len() {
+                    best = (Scheme::DiffGzip, d);
+                }
+            }
+            if let Some(scheme) = zero_suppress {
+                let z = zero_suppress_words(&data, scheme, endian);
+                if z.len() < best.1.len() {
+                    best = (scheme, z);
+                }
+            }
+            if best.1.len() < data.len() {
+                best
+            } else {
+                (Scheme::Raw, data)
+            }
+        }
+    })
+}
+
+/// zero_suppress_scheme is the zero suppression of integers of size bytes;
+/// bytes are not zero suppressed.
+fn zero_suppress_scheme(size: usize) -> Option<Scheme> {
+    match size {
+        2 => Some(Scheme::ZeroSuppress2),
+        4 => Some(Scheme::ZeroSuppress4),
+        8 => Some(Scheme::ZeroSuppress8),
+        _ => None,
+    }
+}
+
+fn gzip(data: &[u8], level: u32) -> Result<Vec<u8>> {
+    let mut e = ZlibEncoder::new(Vec::new(), flate2::Compression::new(level.min(9)));
+    e.write_all(data)?;
+    e.finish()
+}
+
+fn diff_gzip(data: &[u8], size: usize, level: u32, endian: Endian) -> Result<Vec<u8>> {
+    let mut buf = data.to_vec();
+    differentiate(&mut buf, size, endian);
+    gzip(&buf, level)
+}
+
+/// differentiate replaces each word with its difference from the previous one.
+fn differentiate(buf: &mut [u8], size: usize, endian: Endian) {
+    let mut last: u64 = 0;
+    for chunk in buf.chunks_exact_mut(size) {
+        let w = word(chunk, endian);
+        put_word(chunk, w.wrapping_sub(last) & max_word(size), endian);
+        last = w;
+    }
+}
+
+/// zero_suppress_words packs the differences between words in blocks of
+/// BLOCK the way FrameL's FrVectZComp does, the inverse of decompress's
+/// zero_expand.
+fn zero_suppress_words(data: &[u8], scheme: Scheme, endian: Endian) -> Vec<u8> {
+    let size = scheme.word_size().unwrap_or(2);
+    let bits = 8 * size as u32;
+    let mut diffs = Vec::with_capacity(data.len() / size);
+    let mut last: u64 = 0;
+    for chunk in data.chunks_exact(size) {
+        let w = word(chunk, endian);
+        // sign extend so the difference is a signed value of the word size.
+        let d = (w.wrapping_sub(last) & max_word(size)) << (64 - bits);
+        diffs.push(((d as i64) >> (64 - bits)) as u64);
+        last = w;
+    }
+
+    let mut out = BitWriter {
+        words: vec![BLOCK as u64],
+        size: bits,
+        bit: bits,
+    };
+    for block in diffs.chunks(BLOCK) {
+        let num_bits = block.iter().map(|d| width(*d as i64)).max().unwrap_or(0);
+        if num_bits == 0 {
+            out.write(0, code_bits(size));
+            continue;
+        }
+        // the word's own bits hold any difference, which wraps around.
+        let num_bits = num_bits.min(bits);
+        out.write(num_bits as u64 - 1, code_bits(size));
+        for d in block {
+            out.write(d.wrapping_add(offset(num_bits)), num_bits);
+        }
+    }
+
+    let mut buf = vec![0; out.words.len() * size];
+    for (chunk, w) in buf.chunks_exact_mut(size).zip(out.words) {
+        put_word(chunk, w, endian);
+    }
+    buf
+}
+
+/// width is the number of bits that hold d offset by offset, or 0 when d is
+/// zero.  Like FrameL, it is chosen from the magnitude of d.
+fn width(d: i64) -> u32 {
+    if d == 0 {
+        return 0;
+    }
+    // n bits hold -(2^(n-1)-1) ..= 2^(n-1)-1 of either sign.
+    65 - d.unsigned_abs().leading_zeros()
+}
+
+/// BitWriter packs values from the least significant bit of each word.
+struct BitWriter {
+    words: Vec<u64>,
+    /// size is the number of bits in a word.
+    size: u32,
+    /// bit is the number of bits used in the last word.
+    bit: u32,
+}
+
+impl BitWriter {
+    fn write(&mut self, v: u64, num_bits: u32) {
+        let mut v = v & low_bits(num_bits);
+        let mut left = num_bits;
+        while left > 0 {
+            if self.bit == self.size {
+                self.words.push(0);
+                self.bit = 0;
+            }
+            let take = left.min(self.size - self.bit);
+            if let Some(w) = self.words.last_mut() {
+                *w |= (v & low_bits(take)) << self.bit;
+            }
+            v = v.checked_shr(take).unwrap_or(0);
+            left -= take;
+            self.bit += take;
+        }
+    }
+}
+
+#[cfg(test)]
+mod compress_tests {
+    use super::*;
+    use crate::decompress::Decompress;
+
+    fn round_trip(data: &[u8], class: u16, compression: Compression) -> Scheme {
+        let (scheme, buf) = compress(data.to_vec(), class, compression, Endian::Little).unwrap();
+        let size = match class {
+            2 | 5 | 11 => 8,
+            1 | 9 => 2,
+            _ => 4,
+        };
+        let n = data.len() / size;
+        let out = Decompress::new()
+            .decompress(
+                &buf,
+                scheme.compression(Endian::Little),
+                class,
+                n as u64,
+                Endian::Little,
+            )
+            .unwrap();
+        assert_eq!(out, data, "{}", scheme);
+        scheme
+    }
+
+    #[test]
+    fn test_schemes() {
+        let i16s: Vec<u8> = [0i16, 1, -1, 300, -32768, 32767, 7, 7, 7, 7]
+            .iter()
+            .cycle()
+            .take(100)
+            .flat_map(|x| x.to_le_bytes())
+            .collect();
+        let i32s: Vec<u8> = (0..100i32)
+            .map(|x| x * x * (1 - 2 * (x % 2)))
+            .chain([i32::MIN, i32::MAX, 0, 0])
+            .flat_map(|x| x.to_le_bytes())
+            .collect();
+        let u64s: Vec<u8> = (0..50u64)
+            .map(|x| if x % 7 == 0 { u64::MAX - x } else { x })
+            .flat_map(|x| x.to_le_bytes())
+            .collect();
+        let f64s: Vec<u8> = (0..50)
+            .flat_map(|x| (x as f64 / 3.0).to_le_bytes())
+            .collect();
+
+        assert_eq!(round_trip(&i16s, 1, Compression::Raw), Scheme::Raw);
+        assert_eq!(round_trip(&i16s, 1, Compression::Gzip(9)), Scheme::Gzip);
+        assert_eq!(
+            round_trip(&i32s, 4, Compression::DiffGzip(1)),
+            Scheme::DiffGzip
+        );
+        assert_eq!(round_trip(&f64s, 2, Compression::DiffGzip(6)), Scheme::Gzip);
+        assert_eq!(
+            round_trip(&i16s, 1, Compression::ZeroSuppress),
+            Scheme::ZeroSuppress2
+        );
+        assert_eq!(
+            round_trip(&i32s, 4, Compression::ZeroSuppress),
+            Scheme::ZeroSuppress4
+        );
+        assert_eq!(
+            round_trip(&u64s, 11, Compression::ZeroSuppress),
+            Scheme::ZeroSuppress8
+        );
+        assert_eq!(round_trip(&f64s, 2, Compression::ZeroSuppress), Scheme::Raw);
+        round_trip(&u64s, 11, Compression::Auto(6));
+
+        let zeros = vec![0; 400];
+        let (scheme, buf) = compress(zeros, 4, Compression::ZeroSuppress, Endian::Little).unwrap();
+        assert_eq!(scheme, Scheme::ZeroSuppress4);
+        // the block size then one 5 bit code for each of the 7 blocks.
+        assert_eq!(buf.len(), 12);
+    }
+
+    #[test]
+    fn test_zero_suppress_framel() {
+        // the words FrVectZComp stores for 10, 12, 11, 11 in one block: the
+        // block size, then the code 4 and the differences 10, 2, -1, 0 in
+        // 5 bits each, plus 15.
+        let data: Vec<u8> = [10i16, 12, 11, 11]
+            .iter()
+            .flat_map(|x| x.to_le_bytes())
+            .collect();
+        let (scheme, buf) = compress(data, 1, Compression::ZeroSuppress, Endian::Little).unwrap();
+        assert_eq!(scheme, Scheme::ZeroSuppress2);
+        assert_eq!(buf, vec![0x10, 0x00, 0x94, 0xa3, 0x7b, 0x00]);
+    }
+
+    #[test]
+    fn test_auto_falls_back_to_raw() {
+        let noise: Vec<u8> = (0u32..64)
+            .map(|x| x.wrapping_mul(2_654_435_761).rotate_left(x) as u8)
+            .collect();
+        assert_eq!(round_trip(&noise, 3, Compression::Auto(9)), Scheme::Raw);
+    }
+}
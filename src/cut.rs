@@ -0,0 +1,469 @@
+use crate::axis::Axis;
+use crate::gps::GpsTime;
+use crate::handler::Handler;
+use crate::structures::*;
+use crate::writer::{Frame, Writer};
+use std::io::{Error, ErrorKind, Result, Write};
+
+/// Cutter is a handler copying the chosen channels and time range of frame
+/// files into a writer.  Parse files in GPS order with the same cutter, then
+/// call finish.
+///
+/// Time series are cut to the samples inside each written frame.  Other
+/// vectors, such as spectra and serial data, are copied whole into the
+/// frame holding the start of the frame they were read from.
+pub struct Cutter<W: Write> {
+    writer: Writer<W>,
+    patterns: Vec<String>,
+    start: Option<GpsTime>,
+    end: Option<GpsTime>,
+    frame_length_s: Option<f64>,
+    /// origin is the start of the first written frame when re-framing.
+    origin: Option<GpsTime>,
+    /// input is the frame being read.
+    input: Option<Frame>,
+    /// output are the frames not yet written, in time order.
+    output: Vec<Frame>,
+    frames: u32,
+    error: Option<Error>,
+}
+
+impl<W: Write> Cutter<W> {
+    /// new copies the channels matching any of the patterns, where `*`
+    /// matches any run of characters and `?` any one character.  With no
+    /// patterns every channel is copied.
+    pub fn new(writer: Writer<W>, patterns: &[&str]) -> Self {
+        Cutter {
+            writer,
+            patterns: patterns.iter().map(|p| p.to_string()).collect(),
+            start: None,
+            end: None,
+            frame_length_s: None,
+            origin: None,
+            input: None,
+            output: Vec::new(),
+            frames: 0,
+            error: None,
+        }
+    }
+
+    /// window limits the copy to `[start, end)`.
+    pub fn window(&mut self, start: Option<GpsTime>, end: Option<GpsTime>) {
+        self.start = start;
+        self.end = end;
+    }
+
+    /// frame_length writes frames of frame_length_s seconds starting at the
+    /// window start, or the first frame read.  By default the frames read are
+    /// kept.
+    pub fn frame_length(&mut self, frame_length_s: f64) {
+        self.frame_length_s = Some(frame_length_s);
+    }
+
+    /// finish writes the remaining frames and the end of the file.
+    pub fn finish(mut self) -> Result<W> {
+        self.end_input();
+        while !self.output.is_empty() {
+            self.write_first();
+        }
+        if let Some(e) = self.error {
+            return Err(e);
+        }
+        self.writer.finish()
+    }
+
+    fn wants(&self, name: &str) -> bool {
+        self.patterns.is_empty() || self.patterns.iter().any(|p| matches(p, name))
+    }
+
+    /// end_input copies the frame read into the frames to write and writes
+    /// those that are complete.
+    fn end_input(&mut self) {
+        let input = match self.input.take() {
+            Some(f) => f,
+            None => return,
+        };
+        let start = match self.start {
+            Some(s) => s.max(input.header.start_time()),
+            None => input.header.start_time(),
+        };
+        let end = match self.end {
+            Some(e) => e.min(input.header.end_time()),
+            None => input.header.end_time(),
+        };
+        if start >= end {
+            return;
+        }
+
+        for (i, (slot_start, slot_end)) in self.slots(start, end).into_iter().enumerate() {
+            let j = self.slot(slot_start, slot_end, &input.header);
+            if let Err(e) = copy(&input, &mut self.output[j], slot_end, i == 0) {
+                self.error.get_or_insert(e);
+            }
+        }
+        while self
+            .output
+            .first()
+            .is_some_and(|f| f.header.end_time() <= end)
+        {
+            self.write_first();
+        }
+    }
+
+    /// slots are the frames to write covering `[start, end)`.
+    fn slots(&mut self, start: GpsTime, end: GpsTime) -> Vec<(GpsTime, GpsTime)> {
+        let length = match self.frame_length_s {
+            Some(l) => l,
+            None => return vec![(start, end)],
+        };
+        let origin = *self.origin.get_or_insert(self.start.unwrap_or(start));
+        let first = ((start - origin) / length + 1e-9).floor() as i64;
+        let last = ((end - origin) / length - 1e-9).ceil() as i64;
+        (first..last)
+            .map(|k| {
+                let s = origin + k as f64 * length;
+                let e = origin + (k + 1) as f64 * length;
+                let s = self.start.map_or(s, |w| w.max(s));
+                let e = self.end.map_or(e, |w| w.min(e));
+                (s, e)
+            })
+            .collect()
+    }
+
+    /// slot returns the index of the frame to write starting at start,
+    /// creating it from header.
+    fn slot(&mut self, start: GpsTime, end: GpsTime, header: &FrameHeader) -> usize {
+        if let Some(i) = self
+            .output
+            .iter()
+            .position(|f| f.header.start_time() == start)
+        {
+            self.output[i].header.data_quality.0 |= header.data_quality.0;
+            return i;
+        }
+        self.output.push(Frame::new(FrameHeader {
+            name: header.name.clone(),
+            run: header.run,
+            frame: self.frames,
+            data_quality: header.data_quality,
+            gps_start_time_s: start.seconds,
+            gps_residual_time_ns: start.nanoseconds,
+            gps_leap_s: header.gps_leap_s,
+            frame_length_s: end - start,
+        }));
+        self.frames += 1;
+        self.output.len() - 1
+    }
+
+    fn write_first(&mut self) {
+        let frame = self.output.remove(0);
+        if self.error.is_none() {
+            if let Err(e) = self.writer.write_frame(&frame) {
+                self.error = Some(e);
+            }
+        }
+    }
+}
+
+/// copy adds the part of input that belongs to output, which ends at end.
+/// Vectors that are not time series are copied whole when first is set.
+fn copy(input: &Frame, output: &mut Frame, end: GpsTime, first: bool) -> Result<()> {
+    for d in input.detectors.iter() {
+        if !output.detectors.iter().any(|o| o.name == d.name) {
+            output.detectors.push(d.clone());
+        }
+    }
+    for h in input.history.iter() {
+        if !output.history.contains(h) {
+            output.history.push(h.clone());
+        }
+    }
+    if output.raw.is_none() {
+        output.raw = input.raw.clone();
+    }
+
+    let start = output.header.start_time();
+    for (channel, data) in input.channels.iter() {
+        let existing = output
+            .channels
+            .iter_mut()
+            .find(|(c, _)| c.name() == channel.name() && c.kind() == channel.kind());
+        if time_axis(channel, data).is_none() {
+            if first && existing.is_none() {
+                output.channels.push((channel.clone(), data.clone()));
+            }
+            continue;
+        }
+        let (piece, time) = match cut(&input.header, channel, data, start, end) {
+            Some(p) => p,
+            None => continue,
+        };
+        match existing {
+            Some((_, v)) => {
+                let info = v.info();
+                let expected = start
+                    + offset(&input.header, channel)
+                    + info.x_origins[0]
+                    + v.len() as f64 * info.sample_spacing[0];
+                if (time - expected).abs() > info.sample_spacing[0] / 2.0 {
+                    return Err(Error::new(
+                        ErrorKind::InvalidData,
+                        format!(
+                            "{}: data missing at {}; a frame cannot hold a gap",
+                            channel.name(),
+                            expected
+                        ),
+                    ));
+                }
+                v.append(piece).map_err(|_| {
+                    Error::new(
+                        ErrorKind::InvalidData,
+                        format!("{}: sample type changed at {}", channel.name(), time),
+                    )
+                })?;
+            }
+            None => output.channels.push((channel.clone(), piece)),
+        }
+    }
+    Ok(())
+}
+
+/// cut returns the samples of a time series in `[start, end)` with the time
+/// of the first one, positioned in a frame starting at start.  Vectors that
+/// are not time series and series without samples in the range return None.
+pub(crate) fn cut(
+    header: &FrameHeader,
+    channel: &Channel,
+    data: &Vector,
+    start: GpsTime,
+    end: GpsTime,
+) -> Option<(Vector, GpsTime)> {
+    let axis = time_axis(channel, data)?;
+    let first = channel.start_time(header) + axis.origin;
+    let index = |t: GpsTime| {
+        let i = ((t - first) / axis.spacing - 1e-6).ceil();
+        i.max(0.0).min(data.len() as f64) as usize
+    };
+    let (i, j) = (index(start), index(end));
+    if i >= j {
+        return None;
+    }
+    let time = first + i as f64 * axis.spacing;
+    let mut piece = data.slice(i..j);
+    piece.info_mut().x_origins[0] = time - (start + offset(header, channel));
+    Some((piece, time))
+}
+
+/// time_axis returns the axis of a time series: one dimensional numbers
+/// sampled at a positive spacing in time.  Other vectors return None.
+pub(crate) fn time_axis(channel: &Channel, data: &Vector) -> Option<Axis> {
+    let info = data.info();
+    let axis = info.axis(0)?;
+    let time_series = match channel {
+        Channel::Serial(_) => false,
+        Channel::PostProcessed(c) => {
+            c.data_type == DataType::TimeSeries || c.data_type == DataType::Unknown
+        }
+        _ => true,
+    };
+    if !time_series
+        || info.num_dimensions != 1
+        || axis.spacing <= 0.0
+        || axis.is_frequency()
+        || matches!(data, Vector::Strings(_, _))
+    {
+        return None;
+    }
+    Some(axis)
+}
+
+/// offset is the time of a channel relative to the start of its frame.
+fn offset(header: &FrameHeader, channel: &Channel) -> f64 {
+    match channel {
+        Channel::Serial(_) => 0.0,
+        _ => channel.start_time(header) - header.start_time(),
+    }
+}
+
+/// matches reports whether name matches pattern, where `*` matches any run
+/// of characters and `?` any one character.
+fn matches(pattern: &str, name: &str) -> bool {
+    let p: Vec<char> = pattern.chars().collect();
+    let n: Vec<char> = name.chars().collect();
+    let (mut i, mut j) = (0, 0);
+    // star is the position after the last `*` and the name position it matched up to.
+    let mut star: Option<(usize, usize)> = None;
+    while j < n.len() {
+        if i < p.len() && (p[i] == '?' || p[i] == n[j]) {
+            i += 1;
+            j += 1;
+        } else if i < p.len() && p[i] == '*' {
+            i += 1;
+            star = Some((i, j));
+        } else if let Some((si, sj)) = star {
+            i = si;
+            j = sj + 1;
+            star = Some((si, sj + 1));
+        } else {
+            return false;
+        }
+    }
+    p[i..].iter().all(|c| *c == '*')
+}
+
+impl<W: Write> Handler for Cutter<W> {
+    fn begin_frame(&mut self) -> Option<fn(&mut Self, header: FrameHeader)> {
+        Some(|c, header| {
+            c.end_input();
+            c.input = Some(Frame::new(header));
+        })
+    }
+
+    fn eof(&mut self) -> Option<fn(&mut Self)> {
+        Some(|c| c.end_input())
+    }
+
+    fn detector(&mut self) -> Option<fn(&mut Self, data: Detector)> {
+        Some(|c, d| {
+            if let Some(f) = c.input.as_mut() {
+                f.detectors.push(d);
+            }
+        })
+    }
+
+    fn history(&mut self) -> Option<fn(&mut Self, data: History)> {
+        Some(|c, h| {
+            if let Some(f) = c.input.as_mut() {
+                f.history.push(h);
+            }
+        })
+    }
+
+    fn raw(&mut self) -> Option<fn(&mut Self, data: RawData)> {
+        Some(|c, raw| {
+            if let Some(f) = c.input.as_mut() {
+                f.raw = Some(raw);
+            }
+        })
+    }
+
+    fn channel(&mut self) -> Option<fn(&mut Self, channel: Channel, data: Vector)> {
+        Some(|c, channel, data| {
+            if !c.wants(channel.name()) {
+                return;
+            }
+            if let Some(f) = c.input.as_mut() {
+                f.channels.push((channel, data));
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod cut_tests {
+    use super::*;
+    use crate::fixtures::{adc, file, frame, info, write};
+    use std::io::Cursor;
+
+    #[derive(Default)]
+    struct Frames(Vec<Frame>);
+
+    impl Handler for Frames {
+        fn begin_frame(&mut self) -> Option<fn(&mut Self, header: FrameHeader)> {
+            Some(|f, h| f.0.push(Frame::new(h)))
+        }
+
+        fn channel(&mut self) -> Option<fn(&mut Self, channel: Channel, data: Vector)> {
+            Some(|f, c, v| f.0.last_mut().unwrap().channels.push((c, v)))
+        }
+    }
+
+    #[test]
+    fn test_cut() {
+        let input = file(4, &["H1:A", "H1:B"]);
+        let mut cutter = Cutter::new(Writer::new(Cursor::new(Vec::new())).unwrap(), &["*:A"]);
+        cutter.window(
+            Some(GpsTime::new(1_000_000_000, 500_000_000)),
+            Some(GpsTime::new(1_000_000_003, 250_000_000)),
+        );
+        cutter.frame_length(2.0);
+        crate::parse(&mut Cursor::new(&input), &mut cutter).unwrap();
+        let output = cutter.finish().unwrap().into_inner();
+
+        let mut frames = Frames::default();
+        crate::parse(&mut Cursor::new(&output), &mut frames).unwrap();
+        let frames = frames.0;
+        assert_eq!(frames.len(), 2);
+        assert_eq!(
+            frames[0].header.start_time(),
+            GpsTime::new(1_000_000_000, 500_000_000)
+        );
+        assert_eq!(frames[0].header.frame_length_s, 2.0);
+        assert_eq!(frames[1].header.frame_length_s, 0.75);
+        for f in frames.iter() {
+            assert_eq!(f.channels.len(), 1);
+            assert_eq!(f.channels[0].0.name(), "H1:A");
+            assert_eq!(f.channels[0].1.info().x_origins, vec![0.0]);
+        }
+        let samples = |f: &Frame| match &f.channels[0].1 {
+            Vector::I16(info, v) => {
+                assert_eq!(info.dimension_lengths, vec![v.len() as u64]);
+                v.clone()
+            }
+            v => panic!("unexpected {:?}", v),
+        };
+        assert_eq!(samples(&frames[0]), (2..10).collect::<Vec<i16>>());
+        assert_eq!(samples(&frames[1]), vec![10, 11, 12]);
+    }
+
+    #[test]
+    fn test_empty_range() {
+        // H1:SLOW has its one sample at the start of the frame, outside the window.
+        let mut input = frame(0, &["H1:A"]);
+        input.channels.push((
+            Channel::ADC(ADC {
+                sample_rate: 1.0,
+                ..adc("H1:SLOW")
+            }),
+            Vector::I16(
+                VectorInfo {
+                    sample_spacing: vec![1.0],
+                    ..info("H1:SLOW", 1)
+                },
+                vec![7],
+            ),
+        ));
+        let input = write(&[input]);
+        let mut cutter = Cutter::new(Writer::new(Vec::new()).unwrap(), &[]);
+        cutter.window(
+            Some(GpsTime::new(1_000_000_000, 200_000_000)),
+            Some(GpsTime::new(1_000_000_000, 600_000_000)),
+        );
+        crate::parse(&mut Cursor::new(&input), &mut cutter).unwrap();
+        let output = cutter.finish().unwrap();
+
+        let mut frames = Frames::default();
+        crate::parse(&mut Cursor::new(&output), &mut frames).unwrap();
+        let channels = &frames.0[0].channels;
+        assert_eq!(channels.len(), 1);
+        assert_eq!(channels[0].0.name(), "H1:A");
+        match &channels[0].1 {
+            Vector::I16(info, v) => {
+                assert_eq!(v, &vec![1, 2]);
+                assert!((info.x_origins[0] - 0.05).abs() < 1e-9);
+            }
+            v => panic!("unexpected {:?}", v),
+        }
+    }
+
+    #[test]
+    fn test_matches() {
+        assert!(matches("H1:*", "H1:GDS-CALIB_STRAIN"));
+        assert!(matches("*STRAIN", "H1:GDS-CALIB_STRAIN"));
+        assert!(matches("?1:*-*_STRAIN", "L1:GDS-CALIB_STRAIN"));
+        assert!(matches("H1:A", "H1:A"));
+        assert!(!matches("H1:A", "H1:AB"));
+        assert!(!matches("H1:*X", "H1:AB"));
+        assert!(matches("*", ""));
+    }
+}
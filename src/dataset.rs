@@ -0,0 +1,304 @@
+use crate::handler::Handler;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Error, ErrorKind, Result};
+use std::path::{Path, PathBuf};
+
+/// FrameFile describes a single GWF file covering `[gps_start_s, gps_start_s + duration_s)`.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FrameFile {
+    /// observatory is the site prefix, e.g. `H`, `L` or `HL` for multi-detector files.
+    pub observatory: String,
+    /// frame_type is the description field, e.g. `H1_GWOSC_4KHZ_R1`.
+    pub frame_type: String,
+    pub gps_start_s: u32,
+    pub duration_s: u32,
+    pub path: PathBuf,
+}
+
+impl FrameFile {
+    /// from_path parses the `IFO-DESCRIPTION-GPSSTART-DURATION.gwf` naming convention.
+    /// Files not following the convention return None.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Option<FrameFile> {
+        let path = path.as_ref();
+        if path.extension()? != "gwf" {
+            return None;
+        }
+        let stem = path.file_stem()?.to_str()?;
+
+        let fields: Vec<&str> = stem.split('-').collect();
+        if fields.len() != 4 || fields[0].is_empty() || fields[1].is_empty() {
+            return None;
+        }
+
+        Some(FrameFile {
+            observatory: fields[0].to_string(),
+            frame_type: fields[1].to_string(),
+            gps_start_s: fields[2].parse().ok()?,
+            duration_s: fields[3].parse().ok()?,
+            path: path.to_path_buf(),
+        })
+    }
+
+    /// gps_end_s is the first GPS second not covered by this file.
+    #[inline]
+    pub fn gps_end_s(&self) -> u32 {
+        self.gps_start_s.saturating_add(self.duration_s)
+    }
+
+    /// overlaps is true if any part of the file is within `[start_s, end_s)`.
+    #[inline]
+    pub fn overlaps(&self, start_s: u32, end_s: u32) -> bool {
+        self.gps_start_s < end_s && start_s < self.gps_end_s()
+    }
+}
+
+/// Dataset is a collection of frame files located either through a LAL frame
+/// cache (.lcf) or by scanning directories for the GWF naming convention.
+#[derive(Debug, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Dataset {
+    files: Vec<FrameFile>,
+}
+
+impl Dataset {
+    pub fn new() -> Self {
+        Dataset { files: Vec::new() }
+    }
+
+    /// from_cache reads a LAL frame cache file.
+    pub fn from_cache<P: AsRef<Path>>(filename: P) -> Result<Dataset> {
+        let file = File::open(filename)?;
+        Dataset::parse_cache(BufReader::new(file))
+    }
+
+    /// parse_cache reads LAL cache lines of the form
+    /// `OBSERVATORY TYPE GPSSTART DURATION URL`.
+    /// Blank lines and lines starting with `#` are ignored.  A `-` start or
+    /// duration is filled in from the filename when possible.
+    pub fn parse_cache<R: BufRead>(reader: R) -> Result<Dataset> {
+        let mut dataset = Dataset::new();
+        for (num, line) in reader.lines().enumerate() {
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() != 5 {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!(
+                        "cache line {}: expected 5 fields, got {}",
+                        num + 1,
+                        fields.len()
+                    ),
+                ));
+            }
+
+            let path = url_to_path(fields[4]);
+            let from_name = FrameFile::from_path(&path);
+            let start = cache_number(fields[2], from_name.as_ref().map(|f| f.gps_start_s));
+            let duration = cache_number(fields[3], from_name.as_ref().map(|f| f.duration_s));
+            match (start, duration) {
+                (Some(gps_start_s), Some(duration_s)) => dataset.insert(FrameFile {
+                    observatory: fields[0].to_string(),
+                    frame_type: fields[1].to_string(),
+                    gps_start_s,
+                    duration_s,
+                    path,
+                }),
+                _ => {
+                    return Err(Error::new(
+                        ErrorKind::InvalidData,
+                        format!("cache line {}: invalid gps start or duration", num + 1),
+                    ))
+                }
+            }
+        }
+        Ok(dataset)
+    }
+
+    /// scan recursively walks directory adding every file following the GWF naming convention.
+    pub fn scan<P: AsRef<Path>>(directory: P) -> Result<Dataset> {
+        let mut dataset = Dataset::new();
+        dataset.scan_dir(directory.as_ref())?;
+        Ok(dataset)
+    }
+
+    fn scan_dir(&mut self, directory: &Path) -> Result<()> {
+        for entry in std::fs::read_dir(directory)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                self.scan_dir(&path)?;
+            } else if let Some(file) = FrameFile::from_path(&path) {
+                self.insert(file);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn insert(&mut self, file: FrameFile) {
+        self.files.push(file);
+    }
+
+    /// all returns every file in the dataset.
+    pub fn all(&self) -> &[FrameFile] {
+        &self.files
+    }
+
+    /// files returns the files for observatory and frame type that cover any of
+    /// `[start_s, end_s)` ordered by GPS start time.
+    pub fn files(
+        &self,
+        observatory: &str,
+        frame_type: &str,
+        start_s: u32,
+        end_s: u32,
+    ) -> Vec<&FrameFile> {
+        let mut files: Vec<&FrameFile> = self
+            .files
+            .iter()
+            .filter(|f| {
+                f.observatory == observatory
+                    && f.frame_type == frame_type
+                    && f.overlaps(start_s, end_s)
+            })
+            .collect();
+        files.sort_by(|a, b| {
+            a.gps_start_s
+                .cmp(&b.gps_start_s)
+                .then_with(|| a.path.cmp(&b.path))
+        });
+        files
+    }
+
+    /// parse calls `gwf::parse_file` with handler on each file covering
+    /// `[start_s, end_s)` in GPS order.
+    pub fn parse<T: Handler>(
+        &self,
+        observatory: &str,
+        frame_type: &str,
+        start_s: u32,
+        end_s: u32,
+        handler: &mut T,
+    ) -> Result<()> {
+        for file in self.files(observatory, frame_type, start_s, end_s) {
+            let filename = file.path.to_str().ok_or_else(|| {
+                Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("invalid filename: {:?}", file.path),
+                )
+            })?;
+            crate::parse_file(filename, handler)?;
+        }
+        Ok(())
+    }
+}
+
+/// url_to_path strips the `file://` and `file://localhost` prefixes used by LAL caches.
+fn url_to_path(url: &str) -> PathBuf {
+    let path = url
+        .strip_prefix("file://localhost")
+        .or_else(|| url.strip_prefix("file://"))
+        .unwrap_or(url);
+    PathBuf::from(path)
+}
+
+/// cache_number parses a whole, non-negative number of seconds; "-" takes the
+/// fallback from the file name.
+fn cache_number(field: &str, fallback: Option<u32>) -> Option<u32> {
+    match field {
+        "-" => fallback,
+        _ => field
+            .parse::<f64>()
+            .ok()
+            .filter(|n| n.fract() == 0.0 && (0.0..=u32::MAX as f64).contains(n))
+            .map(|n| n as u32),
+    }
+}
+
+#[cfg(test)]
+mod dataset_tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_from_path() {
+        let file = FrameFile::from_path("assets/H-CAL_FAC_V03-729273600-5094000.gwf").unwrap();
+        assert_eq!(
+            file,
+            FrameFile {
+                observatory: "H".to_string(),
+                frame_type: "CAL_FAC_V03".to_string(),
+                gps_start_s: 729273600,
+                duration_s: 5094000,
+                path: PathBuf::from("assets/H-CAL_FAC_V03-729273600-5094000.gwf"),
+            }
+        );
+        assert_eq!(file.gps_end_s(), 734367600);
+
+        assert!(FrameFile::from_path("assets/PyCBC_T3_0.gwf").is_none());
+        assert!(FrameFile::from_path("assets/F-TEST-600000000-60.txt").is_none());
+    }
+
+    #[test]
+    fn test_parse_cache() -> Result<()> {
+        let cache = "\
+# observatory type start duration url
+H H1_HOFT 1000 100 file://localhost/data/H-H1_HOFT-1000-100.gwf
+
+H H1_HOFT 1100 100 file:///data/H-H1_HOFT-1100-100.gwf
+L L1_HOFT - - /data/L-L1_HOFT-1000-100.gwf
+";
+        let dataset = Dataset::parse_cache(Cursor::new(cache))?;
+        assert_eq!(dataset.all().len(), 3);
+        assert_eq!(
+            dataset.all()[0].path,
+            PathBuf::from("/data/H-H1_HOFT-1000-100.gwf")
+        );
+        assert_eq!(
+            dataset.all()[1].path,
+            PathBuf::from("/data/H-H1_HOFT-1100-100.gwf")
+        );
+        assert_eq!(dataset.all()[2].gps_start_s, 1000);
+        assert_eq!(dataset.all()[2].duration_s, 100);
+
+        assert!(Dataset::parse_cache(Cursor::new("H H1_HOFT 1000 100")).is_err());
+        for (start, duration) in &[("-1000", "100"), ("1000.5", "100"), ("1000", "1e10")] {
+            let line = format!("H H1_HOFT {} {} /data/x.gwf", start, duration);
+            assert!(Dataset::parse_cache(Cursor::new(line)).is_err());
+        }
+        let line = "H H1_HOFT 1000.0 100 /data/x.gwf";
+        assert_eq!(
+            Dataset::parse_cache(Cursor::new(line))?.all()[0].gps_start_s,
+            1000
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_files() {
+        let mut dataset = Dataset::new();
+        for path in &[
+            "/data/H-H1_HOFT-1200-100.gwf",
+            "/data/H-H1_HOFT-1000-100.gwf",
+            "/data/H-H1_HOFT-1100-100.gwf",
+            "/data/L-L1_HOFT-1100-100.gwf",
+            "/data/H-H1_RAW-1100-100.gwf",
+        ] {
+            dataset.insert(FrameFile::from_path(path).unwrap());
+        }
+
+        let starts: Vec<u32> = dataset
+            .files("H", "H1_HOFT", 1050, 1200)
+            .iter()
+            .map(|f| f.gps_start_s)
+            .collect();
+        assert_eq!(starts, vec![1000, 1100]);
+
+        assert!(dataset.files("H", "H1_HOFT", 1300, 1400).is_empty());
+        assert_eq!(dataset.files("L", "L1_HOFT", 0, u32::MAX).len(), 1);
+    }
+}
@@ -1,3 +1,80 @@
+use crate::structures::Endian;
+use std::fmt;
+use std::io::{Error, ErrorKind, Result};
+
+/// LITTLE_ENDIAN is set in the stored compression of little endian data.
+pub(crate) const LITTLE_ENDIAN: u16 = 0x100;
+
+/// Scheme is an FrVect compression scheme.  The compression stored in a
+/// vector is the scheme's code, with LITTLE_ENDIAN set for little endian data.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Scheme {
+    Raw,
+    Gzip,
+    /// DiffGzip stores the differences between consecutive integers, gzipped.
+    DiffGzip,
+    /// ZeroSuppress2 stores the differences between consecutive 2 byte
+    /// words in blocks using as few bits as the largest difference needs.
+    ZeroSuppress2,
+    ZeroSuppress4,
+    ZeroSuppress8,
+}
+
+impl Scheme {
+    /// from_compression returns the scheme of a vector's stored compression.
+    pub fn from_compression(compression: u16) -> Option<Scheme> {
+        match compression & 0xff {
+            0 => Some(Scheme::Raw),
+            1 => Some(Scheme::Gzip),
+            3 => Some(Scheme::DiffGzip),
+            5 => Some(Scheme::ZeroSuppress2),
+            8 => Some(Scheme::ZeroSuppress4),
+            10 => Some(Scheme::ZeroSuppress8),
+            _ => None,
+        }
+    }
+
+    /// compression is the value stored for data in the endian byte order.
+    pub fn compression(self, endian: Endian) -> u16 {
+        let code = match self {
+            Scheme::Raw => 0,
+            Scheme::Gzip => 1,
+            Scheme::DiffGzip => 3,
+            Scheme::ZeroSuppress2 => 5,
+            Scheme::ZeroSuppress4 => 8,
+            Scheme::ZeroSuppress8 => 10,
+        };
+        match endian {
+            Endian::Little => code | LITTLE_ENDIAN,
+            Endian::Big => code,
+        }
+    }
+
+    /// word_size is the size of the words a zero suppression scheme packs.
+    pub(crate) fn word_size(self) -> Option<usize> {
+        match self {
+            Scheme::ZeroSuppress2 => Some(2),
+            Scheme::ZeroSuppress4 => Some(4),
+            Scheme::ZeroSuppress8 => Some(8),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Scheme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Scheme::Raw => write!(f, "raw"),
+            Scheme::Gzip => write!(f, "gzip"),
+            Scheme::DiffGzip => write!(f, "diff_gzip"),
+            Scheme::ZeroSuppress2 => write!(f, "zero_suppress_2"),
+            Scheme::ZeroSuppress4 => write!(f, "zero_suppress_4"),
+            Scheme::ZeroSuppress8 => write!(f, "zero_suppress_8"),
+        }
+    }
+}
+
 pub struct Decompress {
     gunzipper: flate2::Decompress,
 }
@@ -15,38 +92,45 @@ impl Decompress {
         compression: u16,
         vector_class: u16,
         num_samples: u64,
-    ) -> std::io::Result<Vec<u8>> {
-        let size = Self::decompressed_size(vector_class, num_samples);
-        let mut decompressed_buf = vec![0; size];
-        self.dec(&raw_buf, compression, &mut decompressed_buf)?;
-        Ok(decompressed_buf)
-    }
-
-    pub fn dec(
-        &mut self,
-        data_buf: &[u8],
-        compression: u16,
-        output: &mut [u8],
-    ) -> std::io::Result<()> {
-        match compression {
-            1 | 257 => Ok(self.gunzip(data_buf, output)?),
-            _ => std::result::Result::Err(std::io::Error::new(
-                std::io::ErrorKind::Other,
+        endian: Endian,
+    ) -> Result<Vec<u8>> {
+        let scheme = Scheme::from_compression(compression).ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidData,
                 format!("compression {} not yet handled", compression),
-            )),
+            )
+        })?;
+        let size = Self::decompressed_size(vector_class, num_samples);
+        match scheme {
+            Scheme::Raw => Ok(raw_buf.to_vec()),
+            Scheme::Gzip => {
+                let mut decompressed_buf = vec![0; size];
+                self.gunzip(raw_buf, &mut decompressed_buf)?;
+                Ok(decompressed_buf)
+            }
+            Scheme::DiffGzip => {
+                let mut decompressed_buf = vec![0; size];
+                self.gunzip(raw_buf, &mut decompressed_buf)?;
+                if let Some(size) = integer_size(vector_class) {
+                    integrate(&mut decompressed_buf, size, endian);
+                }
+                Ok(decompressed_buf)
+            }
+            _ => {
+                let size = scheme.word_size().unwrap_or(1);
+                zero_expand(raw_buf, size, num_samples as usize, endian)
+            }
         }
     }
 
-    fn gunzip(&mut self, input: &[u8], output: &mut [u8]) -> std::io::Result<()> {
+    fn gunzip(&mut self, input: &[u8], output: &mut [u8]) -> Result<()> {
+        self.gunzipper.reset(true);
         match self
             .gunzipper
             .decompress(input, output, flate2::FlushDecompress::Finish)
         {
             Ok(_) => Ok(()),
-            Err(e) => std::result::Result::Err(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                e.to_string(),
-            )),
+            Err(e) => Err(Error::new(ErrorKind::InvalidData, e.to_string())),
         }
     }
 
@@ -70,3 +154,205 @@ impl Decompress {
     }
     }
 }
+
+/// integer_size is the size of an integer vector class; other classes return None.
+pub(crate) fn integer_size(class: u16) -> Option<usize> {
+    match class {
+        0 | 12 => Some(1),
+        1 | 9 => Some(2),
+        4 | 10 => Some(4),
+        5 | 11 => Some(8),
+        _ => None,
+    }
+}
+
+/// max_word is the largest word of size bytes; words are handled as u64.
+pub(crate) fn max_word(size: usize) -> u64 {
+    if size >= 8 {
+        u64::MAX
+    } else {
+        (1u64 << (8 * size)) - 1
+    }
+}
+
+/// word reads a word of buf.len() bytes.
+pub(crate) fn word(buf: &[u8], endian: Endian) -> u64 {
+    let mut w = 0;
+    match endian {
+        Endian::Little => {
+            for b in buf.iter().rev() {
+                w = w << 8 | *b as u64;
+            }
+        }
+        Endian::Big => {
+            for b in buf.iter() {
+                w = w << 8 | *b as u64;
+            }
+        }
+    }
+    w
+}
+
+/// put_word writes the low buf.len() bytes of w.
+pub(crate) fn put_word(buf: &mut [u8], w: u64, endian: Endian) {
+    let n = buf.len();
+    for (i, b) in buf.iter_mut().enumerate() {
+        let shift = match endian {
+            Endian::Little => 8 * i,
+            Endian::Big => 8 * (n - 1 - i),
+        };
+        *b = (w >> shift) as u8;
+    }
+}
+
+/// integrate replaces each difference with the running sum, undoing differentiate.
+pub(crate) fn integrate(buf: &mut [u8], size: usize, endian: Endian) {
+    let mut sum: u64 = 0;
+    for chunk in buf.chunks_exact_mut(size) {
+        sum = sum.wrapping_add(word(chunk, endian)) & max_word(size);
+        put_word(chunk, sum, endian);
+    }
+}
+
+/// code_bits is the width of the field holding the number of bits of a zero
+/// suppressed block.
+pub(crate) fn code_bits(size: usize) -> u32 {
+    match size {
+        2 => 4,
+        4 => 5,
+        _ => 6,
+    }
+}
+
+/// offset is added to a difference stored in num_bits bits so it is never
+/// negative: FrameL's wMax, 2^(num_bits-1)-1.
+pub(crate) fn offset(num_bits: u32) -> u64 {
+    low_bits(num_bits - 1)
+}
+
+/// zero_expand undoes zero suppression as FrameL's FrVectZExpand does.  The
+/// first word is the block size; each block starts with a code of code_bits
+/// bits: 0 when all differences are zero, otherwise one less than the bits
+/// of each difference, stored plus offset.  Bits fill each word from the
+/// least significant end.
+fn zero_expand(input: &[u8], size: usize, num_samples: usize, endian: Endian) -> Result<Vec<u8>> {
+    let truncated = || Error::new(ErrorKind::InvalidData, "zero suppressed data is truncated");
+    let words: Vec<u64> = input.chunks_exact(size).map(|c| word(c, endian)).collect();
+    let block = *words.first().ok_or_else(truncated)? as usize;
+    if block == 0 && num_samples > 0 {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            "zero suppressed data has no block size",
+        ));
+    }
+
+    let mut bits = BitReader {
+        words: &words[1..],
+        size: 8 * size as u32,
+        word: 0,
+        bit: 0,
+    };
+    let mut out = vec![0; num_samples * size];
+    let mut chunks = out.chunks_exact_mut(size);
+    let mut sum: u64 = 0;
+    let mut n = 0;
+    while n < num_samples {
+        let len = block.min(num_samples - n);
+        let code = bits.read(code_bits(size)).ok_or_else(truncated)?;
+        for _ in 0..len {
+            let diff = if code == 0 {
+                0
+            } else {
+                let num_bits = code as u32 + 1;
+                let v = bits.read(num_bits).ok_or_else(truncated)?;
+                v.wrapping_sub(offset(num_bits))
+            };
+            sum = sum.wrapping_add(diff) & max_word(size);
+            if let Some(chunk) = chunks.next() {
+                put_word(chunk, sum, endian);
+            }
+        }
+        n += len;
+    }
+    Ok(out)
+}
+
+/// BitReader reads values packed from the least significant bit of each word.
+struct BitReader<'a> {
+    words: &'a [u64],
+    /// size is the number of bits in a word.
+    size: u32,
+    word: usize,
+    bit: u32,
+}
+
+impl BitReader<'_> {
+    fn read(&mut self, num_bits: u32) -> Option<u64> {
+        let mut v = 0;
+        let mut got = 0;
+        while got < num_bits {
+            let w = *self.words.get(self.word)?;
+            let take = (num_bits - got).min(self.size - self.bit);
+            let bits = (w >> self.bit) & low_bits(take);
+            v |= bits << got;
+            got += take;
+            self.bit += take;
+            if self.bit == self.size {
+                self.word += 1;
+                self.bit = 0;
+            }
+        }
+        Some(v)
+    }
+}
+
+/// low_bits is a mask of the n least significant bits.
+pub(crate) fn low_bits(n: u32) -> u64 {
+    if n >= 64 {
+        u64::MAX
+    } else {
+        (1 << n) - 1
+    }
+}
+
+#[cfg(test)]
+mod decompress_tests {
+    use super::*;
+
+    fn expand(input: &[u8], class: u16, scheme: Scheme, n: u64) -> Vec<u8> {
+        Decompress::new()
+            .decompress(
+                input,
+                scheme.compression(Endian::Little),
+                class,
+                n,
+                Endian::Little,
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn test_zero_expand_framel() {
+        // FrVectZComp with a block size of 4 on 10, 12, 11, 11, 11, 11, 11,
+        // 11: the differences 10, 2, -1, 0 take 5 bits each, stored plus 15
+        // after the code 4, then the second block is the code 0.
+        let words: Vec<u8> = [0x0004u16, 0xa394, 0x007b]
+            .iter()
+            .flat_map(|w| w.to_le_bytes())
+            .collect();
+        let want: Vec<u8> = [10i16, 12, 11, 11, 11, 11, 11, 11]
+            .iter()
+            .flat_map(|x| x.to_le_bytes())
+            .collect();
+        assert_eq!(expand(&words, 1, Scheme::ZeroSuppress2, 8), want);
+
+        // 4 byte words with a block size of 2 on 1, -1, -1: the differences
+        // 1 and -2 take 3 bits each, stored plus 3 after the 5 bit code 2.
+        let words: Vec<u8> = [2u32, 0x182].iter().flat_map(|w| w.to_le_bytes()).collect();
+        let want: Vec<u8> = [1i32, -1, -1]
+            .iter()
+            .flat_map(|x| x.to_le_bytes())
+            .collect();
+        assert_eq!(expand(&words, 4, Scheme::ZeroSuppress4, 3), want);
+    }
+}
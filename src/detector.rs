@@ -0,0 +1,203 @@
+use crate::structures::Detector;
+
+/// WGS-84 semi-major axis in meters.
+const WGS84_A: f64 = 6_378_137.0;
+/// WGS-84 first eccentricity squared.
+const WGS84_E2: f64 = 6.694_379_990_14e-3;
+
+/// Observatory identifies the well-known interferometers.
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Observatory {
+    /// H1 is the LIGO Hanford 4 km interferometer.
+    H1,
+    /// L1 is the LIGO Livingston 4 km interferometer.
+    L1,
+    /// V1 is Virgo.
+    V1,
+    /// K1 is KAGRA.
+    K1,
+    /// G1 is GEO 600.
+    G1,
+    Unknown,
+}
+
+impl Observatory {
+    /// from_prefix decodes the two character detector prefix, e.g. `H1`.
+    pub fn from_prefix(prefix: &str) -> Observatory {
+        match prefix {
+            "H1" => Observatory::H1,
+            "L1" => Observatory::L1,
+            "V1" => Observatory::V1,
+            "K1" => Observatory::K1,
+            "G1" => Observatory::G1,
+            _ => Observatory::Unknown,
+        }
+    }
+
+    /// from_name decodes the detector names written by the frame libraries and LALSuite.
+    pub fn from_name(name: &str) -> Observatory {
+        let name = name.to_ascii_uppercase();
+        if name.starts_with("LHO_4K") || name.starts_with("LIGO_HANFORD") || name == "H1" {
+            Observatory::H1
+        } else if name.starts_with("LLO_4K") || name.starts_with("LIGO_LIVINGSTON") || name == "L1"
+        {
+            Observatory::L1
+        } else if name == "VIRGO" || name == "V1" {
+            Observatory::V1
+        } else if name.starts_with("KAGRA") || name == "K1" {
+            Observatory::K1
+        } else if name.starts_with("GEO") || name == "G1" {
+            Observatory::G1
+        } else {
+            Observatory::Unknown
+        }
+    }
+
+    pub fn prefix(&self) -> Option<&'static str> {
+        match self {
+            Observatory::H1 => Some("H1"),
+            Observatory::L1 => Some("L1"),
+            Observatory::V1 => Some("V1"),
+            Observatory::K1 => Some("K1"),
+            Observatory::G1 => Some("G1"),
+            Observatory::Unknown => None,
+        }
+    }
+}
+
+impl Detector {
+    /// prefix returns the two character detector prefix, e.g. `H1`.
+    pub fn prefix(&self) -> String {
+        self.prefix
+            .iter()
+            .take_while(|c| **c != 0)
+            .map(|c| *c as u8 as char)
+            .collect()
+    }
+
+    /// observatory decodes the prefix, falling back to the detector name.
+    pub fn observatory(&self) -> Observatory {
+        match Observatory::from_prefix(&self.prefix()) {
+            Observatory::Unknown => Observatory::from_name(&self.name),
+            o => o,
+        }
+    }
+
+    /// position is the Earth-fixed (ECEF) location of the vertex in meters
+    /// using the WGS-84 ellipsoid.
+    pub fn position(&self) -> [f64; 3] {
+        let (sin_lat, cos_lat) = self.latitude_radians.sin_cos();
+        let (sin_lon, cos_lon) = self.longitude_radians.sin_cos();
+        let h = self.elevation_meters as f64;
+
+        let n = WGS84_A / (1.0 - WGS84_E2 * sin_lat * sin_lat).sqrt();
+        [
+            (n + h) * cos_lat * cos_lon,
+            (n + h) * cos_lat * sin_lon,
+            (n * (1.0 - WGS84_E2) + h) * sin_lat,
+        ]
+    }
+
+    /// arm_x is the Earth-fixed unit vector along the X arm.
+    pub fn arm_x(&self) -> [f64; 3] {
+        self.arm(
+            self.arm_x_azimuth_radians as f64,
+            self.arm_x_altitude_radians as f64,
+        )
+    }
+
+    /// arm_y is the Earth-fixed unit vector along the Y arm.
+    pub fn arm_y(&self) -> [f64; 3] {
+        self.arm(
+            self.arm_y_azimuth_radians as f64,
+            self.arm_y_altitude_radians as f64,
+        )
+    }
+
+    /// arm converts an azimuth (radians East of North) and altitude (radians
+    /// above the horizon) at the vertex into an Earth-fixed unit vector.
+    fn arm(&self, azimuth: f64, altitude: f64) -> [f64; 3] {
+        let (sin_lat, cos_lat) = self.latitude_radians.sin_cos();
+        let (sin_lon, cos_lon) = self.longitude_radians.sin_cos();
+        let (sin_az, cos_az) = azimuth.sin_cos();
+        let (sin_alt, cos_alt) = altitude.sin_cos();
+
+        let east = cos_alt * sin_az;
+        let north = cos_alt * cos_az;
+        let up = sin_alt;
+        [
+            -sin_lon * east - sin_lat * cos_lon * north + cos_lat * cos_lon * up,
+            cos_lon * east - sin_lat * sin_lon * north + cos_lat * sin_lon * up,
+            cos_lat * north + sin_lat * up,
+        ]
+    }
+}
+
+#[cfg(test)]
+mod detector_tests {
+    use super::*;
+
+    fn lho() -> Detector {
+        Detector {
+            name: "LHO_4k".to_string(),
+            prefix: [b'H' as i8, b'1' as i8],
+            longitude_radians: -2.084_056_769_17,
+            latitude_radians: 0.810_795_263_83,
+            elevation_meters: 142.554,
+            arm_x_azimuth_radians: 5.654_877,
+            arm_y_azimuth_radians: 4.084_081,
+            arm_x_altitude_radians: -0.000_619_5,
+            arm_y_altitude_radians: 0.000_012_5,
+            arm_x_midpoint_meters: 1_997.542,
+            arm_y_midpoint_meters: 1_997.522,
+            local_time_utc_offset_s: -28800,
+        }
+    }
+
+    fn assert_close(got: [f64; 3], want: [f64; 3], tolerance: f64) {
+        for i in 0..3 {
+            assert!(
+                (got[i] - want[i]).abs() < tolerance,
+                "{:?} != {:?}",
+                got,
+                want
+            );
+        }
+    }
+
+    #[test]
+    fn test_observatory() {
+        let mut d = lho();
+        assert_eq!(d.prefix(), "H1");
+        assert_eq!(d.observatory(), Observatory::H1);
+
+        d.prefix = [0, 0];
+        assert_eq!(d.observatory(), Observatory::H1);
+
+        d.name = "Virgo".to_string();
+        assert_eq!(d.observatory(), Observatory::V1);
+        assert_eq!(Observatory::from_prefix("X1"), Observatory::Unknown);
+    }
+
+    #[test]
+    fn test_geometry() {
+        // LALSuite's LAL_LHO_4K_DETECTOR location and arms.
+        let d = lho();
+        assert_close(
+            d.position(),
+            [-2.161_414_926_36e6, -3.834_695_178_89e6, 4.600_350_226_64e6],
+            1.0,
+        );
+        assert_close(
+            d.arm_x(),
+            [-0.223_892_661_54, 0.799_830_627_46, 0.556_904_878_31],
+            1e-6,
+        );
+        assert_close(
+            d.arm_y(),
+            [-0.913_978_185_74, 0.026_094_039_89, -0.404_923_421_25],
+            1e-6,
+        );
+    }
+}
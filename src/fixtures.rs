@@ -0,0 +1,136 @@
+//! fixtures builds frame files for tests.
+
+use crate::structures::*;
+use crate::writer::{Frame, Writer};
+
+/// Raw builds a little endian version 8 file structure by structure, for
+/// files the writer cannot produce.  Classes are those given to dictionary.
+pub(crate) struct Raw {
+    buf: Vec<u8>,
+}
+
+impl Raw {
+    /// new starts a file with its header.
+    pub(crate) fn new() -> Raw {
+        let mut buf = b"IGWD\0".to_vec();
+        buf.extend_from_slice(&[8, 0, 2, 4, 8, 4, 8, 0x34, 0x12]);
+        buf.resize(crate::structures::Header::size_of(), 0);
+        Raw { buf }
+    }
+
+    /// dictionary assigns class to the structure called name.
+    pub(crate) fn dictionary(&mut self, name: &str, class: u8) -> &mut Raw {
+        let mut body = string(name);
+        body.extend_from_slice(&(class as u16).to_le_bytes());
+        body.extend_from_slice(&string(""));
+        self.structure(1, 0, &body)
+    }
+
+    /// structure appends a structure whose fields are encoded in body.
+    pub(crate) fn structure(&mut self, class: u8, instance: u32, body: &[u8]) -> &mut Raw {
+        let length = 14 + body.len() as u64;
+        self.buf.extend_from_slice(&length.to_le_bytes());
+        self.buf.push(0);
+        self.buf.push(class);
+        self.buf.extend_from_slice(&instance.to_le_bytes());
+        self.buf.extend_from_slice(body);
+        self
+    }
+
+    pub(crate) fn bytes(&self) -> Vec<u8> {
+        self.buf.clone()
+    }
+}
+
+/// string encodes a STRING: its length with the NUL, then the bytes and NUL.
+pub(crate) fn string(s: &str) -> Vec<u8> {
+    let mut buf = ((s.len() + 1) as u16).to_le_bytes().to_vec();
+    buf.extend_from_slice(s.as_bytes());
+    buf.push(0);
+    buf
+}
+
+/// reference encodes a PTR_STRUCT; a class of 0 is the null pointer.
+pub(crate) fn reference(class: u16, instance: u32) -> Vec<u8> {
+    let mut buf = class.to_le_bytes().to_vec();
+    buf.extend_from_slice(&instance.to_le_bytes());
+    buf
+}
+
+/// header is the header of frame s, one second from GPS 1_000_000_000 + s.
+pub(crate) fn header(s: u32) -> FrameHeader {
+    FrameHeader {
+        name: "TEST".to_string(),
+        run: 0,
+        frame: s,
+        data_quality: DataQuality(0),
+        gps_start_time_s: 1_000_000_000 + s,
+        gps_residual_time_ns: 0,
+        gps_leap_s: 18,
+        frame_length_s: 1.0,
+    }
+}
+
+/// adc is a 16 bit ADC sampled at 4 Hz.
+pub(crate) fn adc(name: &str) -> ADC {
+    ADC {
+        name: name.to_string(),
+        comment: String::new(),
+        channel_group: 0,
+        channel_number: 0,
+        num_bits: 16,
+        bias: 0.0,
+        slope: 1.0,
+        units: None,
+        sample_rate: 4.0,
+        time_offset_s: 0.0,
+        f_shift: 0.0,
+        phase: 0.0,
+        data_valid: DataValid(0),
+    }
+}
+
+/// info describes n samples in counts, 0.25 s apart.
+pub(crate) fn info(name: &str, n: usize) -> VectorInfo {
+    VectorInfo {
+        name: name.to_string(),
+        num_samples: n as u64,
+        num_dimensions: 1,
+        dimension_lengths: vec![n as u64],
+        sample_spacing: vec![0.25],
+        x_origins: vec![0.0],
+        unit_x_scale_factors: vec!["s".to_string()],
+        unit_y: "counts".to_string(),
+        compression: Scheme::Raw.compression(Endian::Little),
+    }
+}
+
+/// frame is frame s of file: an ADC per name with the four samples
+/// 4s to 4s+3.
+pub(crate) fn frame(s: u32, names: &[&str]) -> Frame {
+    let mut f = Frame::new(header(s));
+    let samples: Vec<i16> = (0..4).map(|i| (4 * s + i) as i16).collect();
+    for name in names {
+        f.channels.push((
+            Channel::ADC(adc(name)),
+            Vector::I16(info(name, samples.len()), samples.clone()),
+        ));
+    }
+    f
+}
+
+/// write writes frames to a file in memory.
+pub(crate) fn write(frames: &[Frame]) -> Vec<u8> {
+    let mut w = Writer::new(Vec::new()).unwrap();
+    for f in frames {
+        w.write_frame(f).unwrap();
+    }
+    w.finish().unwrap()
+}
+
+/// file writes n frames of one second whose ADCs, one per name, count up
+/// across frames.
+pub(crate) fn file(n: u32, names: &[&str]) -> Vec<u8> {
+    let frames: Vec<Frame> = (0..n).map(|s| frame(s, names)).collect();
+    write(&frames)
+}
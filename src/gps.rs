@@ -0,0 +1,479 @@
+use std::convert::TryFrom;
+use std::fmt;
+use std::io::{Error, ErrorKind};
+use std::ops::{Add, Sub};
+use std::str::FromStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const NANOSECONDS: u32 = 1_000_000_000;
+
+/// GPS_EPOCH_UNIX_S is 1980-01-06T00:00:00Z as seconds since the Unix epoch.
+const GPS_EPOCH_UNIX_S: i64 = 315_964_800;
+
+/// TAI_GPS_S is the constant offset between TAI and GPS time.
+const TAI_GPS_S: u16 = 19;
+
+/// LEAP_SECONDS are the GPS times at which each leap second since the GPS epoch took effect.
+const LEAP_SECONDS: [u32; 18] = [
+    46_828_801,    // 1981-07-01
+    78_364_802,    // 1982-07-01
+    109_900_803,   // 1983-07-01
+    173_059_204,   // 1985-07-01
+    252_028_805,   // 1988-01-01
+    315_187_206,   // 1990-01-01
+    346_723_207,   // 1991-01-01
+    393_984_008,   // 1992-07-01
+    425_520_009,   // 1993-07-01
+    457_056_010,   // 1994-07-01
+    504_489_611,   // 1996-01-01
+    551_750_412,   // 1997-07-01
+    599_184_013,   // 1999-01-01
+    820_108_814,   // 2006-01-01
+    914_803_215,   // 2009-01-01
+    1_025_136_016, // 2012-07-01
+    1_119_744_017, // 2015-07-01
+    1_167_264_018, // 2017-01-01
+];
+
+/// GpsTime is a GPS time with nanosecond resolution.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GpsTime {
+    pub seconds: u32,
+    /// nanoseconds is always less than one second.
+    pub nanoseconds: u32,
+}
+
+impl GpsTime {
+    /// new creates a time normalizing nanoseconds larger than one second,
+    /// saturating at the last representable time.
+    pub fn new(seconds: u32, nanoseconds: u32) -> Self {
+        GpsTime::saturating(seconds as i128 * NANOSECONDS as i128 + nanoseconds as i128)
+    }
+
+    /// from_seconds converts floating point GPS seconds, rounding to the nearest
+    /// nanosecond.  Seconds that are negative, not finite or past the last
+    /// representable time are invalid.
+    pub fn from_seconds(seconds: f64) -> std::io::Result<Self> {
+        let invalid = || {
+            Error::new(
+                ErrorKind::InvalidInput,
+                format!("invalid gps time: {}", seconds),
+            )
+        };
+        if !(seconds >= 0.0 && seconds < u32::MAX as f64 + 1.0) {
+            return Err(invalid());
+        }
+        // the fraction is scaled alone as seconds * 1e9 is past f64's integer precision.
+        let whole = seconds.floor();
+        let ns = ((seconds - whole) * NANOSECONDS as f64).round() as i128;
+        GpsTime::from_nanoseconds(whole as i128 * NANOSECONDS as i128 + ns).ok_or_else(invalid)
+    }
+
+    /// checked_add adds seconds, rounded to the nearest nanosecond, returning
+    /// None if they are not finite or the sum is not a GPS time.
+    pub fn checked_add(self, seconds: f64) -> Option<GpsTime> {
+        if !seconds.is_finite() {
+            return None;
+        }
+        GpsTime::from_nanoseconds(self.as_nanoseconds().saturating_add(nanoseconds(seconds)))
+    }
+
+    /// checked_sub subtracts seconds like checked_add.
+    pub fn checked_sub(self, seconds: f64) -> Option<GpsTime> {
+        self.checked_add(-seconds)
+    }
+
+    pub fn as_seconds(&self) -> f64 {
+        self.seconds as f64 + self.nanoseconds as f64 * 1e-9
+    }
+
+    fn as_nanoseconds(&self) -> i128 {
+        self.seconds as i128 * NANOSECONDS as i128 + self.nanoseconds as i128
+    }
+
+    /// from_nanoseconds returns None for times before the GPS epoch or past
+    /// the last second a u32 holds.
+    fn from_nanoseconds(ns: i128) -> Option<Self> {
+        if ns < 0 || ns / (NANOSECONDS as i128) > u32::MAX as i128 {
+            return None;
+        }
+        Some(GpsTime {
+            seconds: (ns / NANOSECONDS as i128) as u32,
+            nanoseconds: (ns % NANOSECONDS as i128) as u32,
+        })
+    }
+
+    /// saturating is from_nanoseconds clamped to the GPS epoch and the last
+    /// representable time.
+    fn saturating(ns: i128) -> Self {
+        let last = (u32::MAX as i128 + 1) * NANOSECONDS as i128 - 1;
+        GpsTime::from_nanoseconds(ns.clamp(0, last)).unwrap_or_default()
+    }
+
+    /// leap_seconds is TAI-UTC at this time; the same quantity as the frame header's ULeapS.
+    pub fn leap_seconds(&self) -> u16 {
+        TAI_GPS_S + self.gps_utc_offset() as u16
+    }
+
+    /// gps_utc_offset is the number of leap seconds inserted between the GPS epoch and this time.
+    fn gps_utc_offset(&self) -> u32 {
+        LEAP_SECONDS
+            .iter()
+            .take_while(|leap| **leap <= self.seconds)
+            .count() as u32
+    }
+
+    /// unix_seconds converts to seconds since 1970-01-01T00:00:00Z ignoring leap seconds.
+    /// A time within a leap second maps to the following second.
+    pub fn unix_seconds(&self) -> i64 {
+        self.seconds as i64 + GPS_EPOCH_UNIX_S - self.gps_utc_offset() as i64
+    }
+
+    /// from_unix_seconds converts Unix time into GPS time.
+    pub fn from_unix_seconds(seconds: i64, nanoseconds: u32) -> std::io::Result<GpsTime> {
+        let gps = seconds - GPS_EPOCH_UNIX_S;
+        if gps < 0 || gps > u32::MAX as i64 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("unix time {} is not representable in GPS", seconds),
+            ));
+        }
+        // A leap second applies once the time including it and all earlier leap seconds reaches it.
+        let mut leaps = 0;
+        for leap in LEAP_SECONDS.iter() {
+            if gps + leaps + 1 >= *leap as i64 {
+                leaps += 1;
+            }
+        }
+        let seconds = u32::try_from(gps + leaps).map_err(|_| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                format!("unix time {} is not representable in GPS", seconds),
+            )
+        })?;
+        Ok(GpsTime::new(seconds, nanoseconds))
+    }
+
+    pub fn to_system_time(&self) -> SystemTime {
+        let unix = self.unix_seconds();
+        UNIX_EPOCH + Duration::new(unix as u64, self.nanoseconds)
+    }
+
+    pub fn from_system_time(time: SystemTime) -> std::io::Result<GpsTime> {
+        let since = time
+            .duration_since(UNIX_EPOCH)
+            .map_err(|e| Error::new(ErrorKind::InvalidInput, e.to_string()))?;
+        GpsTime::from_unix_seconds(since.as_secs() as i64, since.subsec_nanos())
+    }
+
+    /// to_utc formats the time as an ISO 8601 UTC string, e.g. `2015-09-14T09:50:45.391Z`.
+    pub fn to_utc(&self) -> String {
+        let unix = self.unix_seconds();
+        let days = unix.div_euclid(86_400);
+        let secs = unix.rem_euclid(86_400);
+        let (year, month, day) = civil_from_days(days);
+        let mut utc = format!(
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+            year,
+            month,
+            day,
+            secs / 3600,
+            (secs % 3600) / 60,
+            secs % 60
+        );
+        utc.push_str(&fraction(self.nanoseconds));
+        utc.push('Z');
+        utc
+    }
+
+    /// from_utc parses an ISO 8601 UTC string such as `2015-09-14T09:50:45.391Z`.
+    pub fn from_utc(utc: &str) -> std::io::Result<GpsTime> {
+        let invalid = || Error::new(ErrorKind::InvalidInput, format!("invalid utc: {}", utc));
+        let utc = utc.trim().trim_end_matches('Z');
+        let (date, time) = match utc.find(&['T', ' '][..]) {
+            Some(i) => (&utc[..i], &utc[i + 1..]),
+            None => (utc, "00:00:00"),
+        };
+
+        let date: Vec<&str> = date.split('-').collect();
+        let time: Vec<&str> = time.split(':').collect();
+        if date.len() != 3 || time.len() != 3 {
+            return Err(invalid());
+        }
+        let year: i64 = date[0].parse().map_err(|_| invalid())?;
+        let month: u32 = date[1].parse().map_err(|_| invalid())?;
+        let day: u32 = date[2].parse().map_err(|_| invalid())?;
+        let hour: u32 = time[0].parse().map_err(|_| invalid())?;
+        let minute: u32 = time[1].parse().map_err(|_| invalid())?;
+        let (second, nanoseconds) = split_seconds(time[2]).ok_or_else(invalid)?;
+        if !(1..=12).contains(&month)
+            || !(1..=days_in_month(year, month)).contains(&day)
+            || hour > 23
+            || minute > 59
+        {
+            return Err(invalid());
+        }
+
+        let unix =
+            days_from_civil(year, month, day) * 86_400 + hour as i64 * 3600 + minute as i64 * 60;
+        match second {
+            // 23:59:60 is the leap second itself.
+            60 => {
+                let gps = GpsTime::from_unix_seconds(unix + 59, nanoseconds)?;
+                Ok(gps + Duration::from_secs(1))
+            }
+            s if s < 60 => GpsTime::from_unix_seconds(unix + s as i64, nanoseconds),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+/// nanoseconds rounds seconds to the nearest nanosecond, saturating at the
+/// limits of i128.
+fn nanoseconds(seconds: f64) -> i128 {
+    (seconds * NANOSECONDS as f64).round() as i128
+}
+
+/// civil_from_days converts days since 1970-01-01 into a proleptic Gregorian date.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+/// days_from_civil converts a proleptic Gregorian date into days since 1970-01-01.
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = if month > 2 { month - 3 } else { month + 9 } as i64;
+    let doy = (153 * mp + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// days_in_month is the number of days of month in a proleptic Gregorian year.
+fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// fraction formats nanoseconds as a decimal fraction without trailing zeros.
+fn fraction(nanoseconds: u32) -> String {
+    if nanoseconds == 0 {
+        return String::new();
+    }
+    let digits = format!(".{:09}", nanoseconds);
+    digits.trim_end_matches('0').to_string()
+}
+
+/// split_seconds parses `SS[.fffffffff]` into seconds and nanoseconds.
+fn split_seconds(s: &str) -> Option<(u32, u32)> {
+    let (whole, frac) = match s.find('.') {
+        Some(i) => (&s[..i], &s[i + 1..]),
+        None => (s, ""),
+    };
+    if frac.len() > 9 || !frac.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let seconds = whole.parse().ok()?;
+    let nanoseconds = if frac.is_empty() {
+        0
+    } else {
+        frac.parse::<u32>().ok()? * 10u32.pow(9 - frac.len() as u32)
+    };
+    Some((seconds, nanoseconds))
+}
+
+impl fmt::Display for GpsTime {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", self.seconds, fraction(self.nanoseconds))
+    }
+}
+
+impl FromStr for GpsTime {
+    type Err = Error;
+
+    /// from_str parses decimal GPS seconds such as `1126259462.391`.
+    fn from_str(s: &str) -> std::io::Result<GpsTime> {
+        split_seconds(s.trim())
+            .map(|(s, ns)| GpsTime::new(s, ns))
+            .ok_or_else(|| Error::new(ErrorKind::InvalidInput, format!("invalid gps time: {}", s)))
+    }
+}
+
+/// Adding or subtracting a duration saturates at the GPS epoch and the last
+/// representable time.
+impl Add<Duration> for GpsTime {
+    type Output = GpsTime;
+    fn add(self, rhs: Duration) -> GpsTime {
+        GpsTime::saturating(self.as_nanoseconds() + rhs.as_nanos() as i128)
+    }
+}
+
+impl Sub<Duration> for GpsTime {
+    type Output = GpsTime;
+    fn sub(self, rhs: Duration) -> GpsTime {
+        GpsTime::saturating(self.as_nanoseconds() - rhs.as_nanos() as i128)
+    }
+}
+
+/// Adding seconds rounds to the nearest nanosecond; the seconds may be negative.
+/// Like durations, the sum saturates at the GPS epoch and the last
+/// representable time; NaN leaves the time unchanged.  Use checked_add to
+/// detect either.
+impl Add<f64> for GpsTime {
+    type Output = GpsTime;
+    fn add(self, rhs: f64) -> GpsTime {
+        if rhs.is_nan() {
+            return self;
+        }
+        GpsTime::saturating(self.as_nanoseconds().saturating_add(nanoseconds(rhs)))
+    }
+}
+
+impl Sub<f64> for GpsTime {
+    type Output = GpsTime;
+    fn sub(self, rhs: f64) -> GpsTime {
+        self + -rhs
+    }
+}
+
+/// Subtracting two times gives the seconds between them.
+impl Sub<GpsTime> for GpsTime {
+    type Output = f64;
+    fn sub(self, rhs: GpsTime) -> f64 {
+        (self.as_nanoseconds() - rhs.as_nanoseconds()) as f64 * 1e-9
+    }
+}
+
+#[cfg(test)]
+mod gps_tests {
+    use super::*;
+
+    #[test]
+    fn test_utc() -> std::io::Result<()> {
+        // GW150914
+        let t: GpsTime = "1126259462.391".parse()?;
+        assert_eq!(t, GpsTime::new(1126259462, 391_000_000));
+        assert_eq!(t.to_string(), "1126259462.391");
+        assert_eq!(t.to_utc(), "2015-09-14T09:50:45.391Z");
+        assert_eq!(GpsTime::from_utc("2015-09-14T09:50:45.391Z")?, t);
+        assert_eq!(t.leap_seconds(), 36);
+
+        assert_eq!(GpsTime::new(0, 0).to_utc(), "1980-01-06T00:00:00Z");
+        assert_eq!(GpsTime::new(0, 0).leap_seconds(), 19);
+        Ok(())
+    }
+
+    #[test]
+    fn test_leap_second() -> std::io::Result<()> {
+        let leap = GpsTime::from_utc("2016-12-31T23:59:60Z")?;
+        assert_eq!(leap, GpsTime::new(1167264017, 0));
+        assert_eq!(
+            GpsTime::from_utc("2017-01-01T00:00:00Z")?,
+            GpsTime::new(1167264018, 0)
+        );
+        assert_eq!(
+            GpsTime::from_utc("2016-12-31T23:59:59Z")?,
+            GpsTime::new(1167264016, 0)
+        );
+        assert_eq!(GpsTime::new(1167264018, 0).leap_seconds(), 37);
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_utc() -> std::io::Result<()> {
+        for utc in &[
+            "2021-02-29T00:00:00Z",
+            "2021-02-31T00:00:00Z",
+            "2021-04-31T00:00:00Z",
+            "1900-02-29T00:00:00Z",
+            "2021-01-01T-1:00:00Z",
+            "2021-01-01T00:-1:00Z",
+            "2021-01-01T24:00:00Z",
+        ] {
+            assert!(GpsTime::from_utc(utc).is_err(), "{}", utc);
+        }
+        assert_eq!(
+            GpsTime::from_utc("2020-02-29T00:00:00Z")?.to_utc(),
+            "2020-02-29T00:00:00Z"
+        );
+        assert_eq!(
+            GpsTime::from_utc("2000-02-29T12:00:00Z")?.to_utc(),
+            "2000-02-29T12:00:00Z"
+        );
+        assert_eq!(
+            GpsTime::from_utc("2021-04-30T23:59:59Z")?.to_utc(),
+            "2021-04-30T23:59:59Z"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let t = GpsTime::new(1000, 900_000_000);
+        assert_eq!(t + 0.2, GpsTime::new(1001, 100_000_000));
+        assert_eq!(t - 1.0, GpsTime::new(999, 900_000_000));
+        assert_eq!(t + Duration::from_millis(100), GpsTime::new(1001, 0));
+        assert!(((t + 2.5) - t - 2.5).abs() < 1e-9);
+        assert!(t < t + 1e-9);
+        assert!("abc".parse::<GpsTime>().is_err());
+        assert_eq!(
+            GpsTime::from_seconds(1_000_000_002.937_5).unwrap(),
+            GpsTime::new(1_000_000_002, 937_500_000)
+        );
+    }
+
+    #[test]
+    fn test_range() {
+        let last = GpsTime::new(u32::MAX, 999_999_999);
+        assert_eq!(GpsTime::new(u32::MAX, NANOSECONDS), last);
+        assert_eq!(GpsTime::new(0, 0) - 1.0, GpsTime::new(0, 0));
+        assert_eq!(last + 1.0, last);
+        assert_eq!(last + Duration::from_secs(1), last);
+        assert_eq!(
+            GpsTime::new(0, 0) - Duration::from_secs(1),
+            GpsTime::new(0, 0)
+        );
+        assert_eq!(GpsTime::new(5, 0) + f64::NAN, GpsTime::new(5, 0));
+        assert_eq!(GpsTime::new(5, 0) + f64::INFINITY, last);
+
+        assert_eq!(GpsTime::new(0, 0).checked_sub(1.0), None);
+        assert_eq!(last.checked_add(1e-9), None);
+        assert_eq!(GpsTime::new(5, 0).checked_add(f64::NAN), None);
+        assert_eq!(GpsTime::new(5, 0).checked_add(1e300), None);
+        assert_eq!(
+            GpsTime::new(5, 0).checked_sub(0.5),
+            Some(GpsTime::new(4, 500_000_000))
+        );
+
+        for s in &[-1.0, f64::NAN, f64::INFINITY, 4_294_967_296.0] {
+            assert!(GpsTime::from_seconds(*s).is_err(), "{}", s);
+        }
+        assert_eq!(
+            GpsTime::from_seconds(4_294_967_295.5).unwrap(),
+            GpsTime::new(u32::MAX, 500_000_000)
+        );
+        assert_eq!(
+            GpsTime::from_seconds(1.999_999_999_9).unwrap(),
+            GpsTime::new(2, 0)
+        );
+
+        let unix = GPS_EPOCH_UNIX_S + u32::MAX as i64;
+        assert!(GpsTime::from_unix_seconds(unix, 0).is_err());
+        assert!(GpsTime::from_unix_seconds(unix - 18, 0).is_ok());
+    }
+}
@@ -1,4 +1,5 @@
 use crate::structures::*;
+use crate::toc::Toc;
 
 /// Handler will be called when the parser sees one of the the data structures.
 pub trait Handler {
@@ -14,6 +15,21 @@ pub trait Handler {
         None
     }
 
+    /// end_frame is called at the end of each frame of a version 8 file.
+    fn end_frame(&mut self) -> Option<fn(&mut Self, data: EndOfFrame)> {
+        None
+    }
+
+    /// toc is called with the table of contents of a version 8 file.
+    fn toc(&mut self) -> Option<fn(&mut Self, data: Toc)> {
+        None
+    }
+
+    /// end_of_file is called at the end of a version 8 file, before eof.
+    fn end_of_file(&mut self) -> Option<fn(&mut Self, data: EndOfFile)> {
+        None
+    }
+
     fn adc(&mut self) -> Option<fn(&mut Self, data: ADC)> {
         None
     }
@@ -69,4 +85,10 @@ pub trait Handler {
     fn vector(&mut self) -> Option<fn(&mut Self, data: Vector)> {
         None
     }
+
+    /// channel is called with an ADC, post-processed, simulated or serial
+    /// structure together with the data vector it points to.
+    fn channel(&mut self) -> Option<fn(&mut Self, channel: Channel, data: Vector)> {
+        None
+    }
 }
@@ -0,0 +1,204 @@
+use crate::handler::Handler;
+use crate::parser::Parser;
+use crate::structures::*;
+use crate::toc::{self, Toc};
+use std::collections::HashMap;
+use std::io::{Error, ErrorKind, Read, Result, Seek};
+
+/// Inventory lists the channels of a file.
+#[derive(Debug, PartialEq, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Inventory {
+    /// frames is the number of frames in the file.
+    pub frames: usize,
+    pub channels: Vec<ChannelSummary>,
+}
+
+/// ChannelSummary describes a channel from the first frame that has it.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChannelSummary {
+    pub name: String,
+    pub kind: ChannelKind,
+    pub sample_rate: f64,
+    /// data_type is the sample type of the data vector, e.g. `i16`.
+    pub data_type: String,
+    pub unit: String,
+    /// compression is the FrVect compression scheme of the data vector.
+    pub compression: u16,
+    /// frames is the number of frames that have the channel.
+    pub frames: usize,
+}
+
+impl ChannelSummary {
+    fn new(channel: &Channel, data: &Vector, frames: usize) -> ChannelSummary {
+        let info = data.info();
+        let sample_rate = match channel {
+            Channel::ADC(c) => c.sample_rate,
+            Channel::Simulation(c) => c.sample_rate,
+            Channel::Serial(c) => c.sample_rate,
+            Channel::PostProcessed(_) => match info.axis(0) {
+                Some(axis) if axis.is_time() && axis.spacing > 0.0 => 1.0 / axis.spacing,
+                _ => 0.0,
+            },
+        };
+        ChannelSummary {
+            name: channel.name().to_string(),
+            kind: channel.kind(),
+            sample_rate,
+            data_type: data.type_name().to_string(),
+            unit: info.unit_y.clone(),
+            compression: info.compression,
+            frames,
+        }
+    }
+}
+
+/// read lists the channels of reader.  The table of contents is used when
+/// the file has one; otherwise every frame is scanned.
+pub fn read<R: Read + Seek>(reader: &mut R) -> Result<Inventory> {
+    match toc::read(reader)? {
+        Some(toc) => from_toc(reader, &toc),
+        None => scan(reader),
+    }
+}
+
+/// from_toc reads each channel's first appearance at the position in the table of contents.
+fn from_toc<R: Read + Seek>(reader: &mut R, toc: &Toc) -> Result<Inventory> {
+    let header = crate::parse_header(reader)?;
+    let mut lookup = StructureLookup::new();
+    for (class, name) in &toc.dictionary {
+        // structures this crate does not know are skipped while parsing.
+        let _ = lookup.insert(name, *class);
+    }
+
+    let mut parser = Parser::new();
+    let mut channels = Vec::with_capacity(toc.channels.len());
+    for c in &toc.channels {
+        let position = match c.positions.iter().find(|p| **p != 0) {
+            Some(p) => *p,
+            None => continue,
+        };
+        let mut first = First {
+            name: &c.name,
+            found: None,
+        };
+        parser.parse_at(&header, reader, position, &mut lookup, &mut first, |f| {
+            f.found.is_some()
+        })?;
+        match first.found {
+            Some((channel, data)) => {
+                channels.push(ChannelSummary::new(&channel, &data, c.frames()))
+            }
+            None => {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("{}: no data at byte {}", c.name, position),
+                ))
+            }
+        }
+    }
+
+    Ok(Inventory {
+        frames: toc.frames.len(),
+        channels,
+    })
+}
+
+/// First keeps the named channel and its data.
+struct First<'a> {
+    name: &'a str,
+    found: Option<(Channel, Vector)>,
+}
+
+impl Handler for First<'_> {
+    fn channel(&mut self) -> Option<fn(&mut Self, channel: Channel, data: Vector)> {
+        Some(|f, channel, data| {
+            if f.found.is_none() && channel.name() == f.name {
+                f.found = Some((channel, data));
+            }
+        })
+    }
+}
+
+fn scan<R: Read + Seek>(reader: &mut R) -> Result<Inventory> {
+    let mut scanner = Scanner {
+        inventory: Inventory::default(),
+        index: HashMap::new(),
+    };
+    crate::parse(reader, &mut scanner)?;
+    Ok(scanner.inventory)
+}
+
+/// Scanner builds an inventory from every frame of a file.
+struct Scanner {
+    inventory: Inventory,
+    /// index is the position of each channel in the inventory.
+    index: HashMap<(String, ChannelKind), usize>,
+}
+
+impl Handler for Scanner {
+    fn begin_frame(&mut self) -> Option<fn(&mut Self, header: FrameHeader)> {
+        Some(|s, _| s.inventory.frames += 1)
+    }
+
+    fn channel(&mut self) -> Option<fn(&mut Self, channel: Channel, data: Vector)> {
+        Some(|s, channel, data| {
+            let key = (channel.name().to_string(), channel.kind());
+            match s.index.get(&key) {
+                Some(i) => s.inventory.channels[*i].frames += 1,
+                None => {
+                    s.index.insert(key, s.inventory.channels.len());
+                    s.inventory
+                        .channels
+                        .push(ChannelSummary::new(&channel, &data, 1));
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod inventory_tests {
+    use super::*;
+    use crate::fixtures::{frame, write};
+    use std::io::Cursor;
+
+    /// file has H1:A in both frames and H1:B in the second only.
+    fn file() -> Vec<u8> {
+        write(&[frame(0, &["H1:A"]), frame(1, &["H1:A", "H1:B"])])
+    }
+
+    #[test]
+    fn test_toc() -> Result<()> {
+        let buf = file();
+        assert!(toc::read(&mut Cursor::new(&buf))?.is_some());
+        let inventory = read(&mut Cursor::new(&buf))?;
+        assert_eq!(inventory.frames, 2);
+        let found: Vec<(&str, usize)> = inventory
+            .channels
+            .iter()
+            .map(|c| (c.name.as_str(), c.frames))
+            .collect();
+        assert_eq!(found, vec![("H1:A", 2), ("H1:B", 1)]);
+        let a = &inventory.channels[0];
+        assert_eq!(a.kind, ChannelKind::ADC);
+        assert_eq!(a.sample_rate, 4.0);
+        assert_eq!(a.data_type, "i16");
+        assert_eq!(a.unit, "counts");
+        Ok(())
+    }
+
+    #[test]
+    fn test_scan() -> Result<()> {
+        let mut buf = file();
+        let want = read(&mut Cursor::new(&buf))?;
+
+        // clearing seekTOC in the FrEndOfFile hides the table of contents.
+        let n = buf.len();
+        buf[n - 20..n - 12].fill(0);
+        assert!(toc::read(&mut Cursor::new(&buf))?.is_none());
+        assert_eq!(read(&mut Cursor::new(&buf))?, want);
+        Ok(())
+    }
+}
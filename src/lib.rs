@@ -1,15 +1,40 @@
 #![feature(test)]
 #![feature(vec_into_raw_parts)]
 
-use std::io::{Error, ErrorKind, Read, Result, Seek};
+use std::io::{Error, ErrorKind, Read, Result, Seek, SeekFrom};
 use std::{fs::File, io::BufReader};
 
+#[cfg(feature = "ndarray")]
+pub mod array;
+#[cfg(feature = "arrow")]
+pub mod arrow;
+pub mod axis;
+#[cfg(feature = "capi")]
+pub mod capi;
+pub mod cut;
+pub mod dataset;
+pub mod detector;
+pub mod gps;
 pub mod handler;
+pub mod inventory;
+pub mod npy;
+pub mod staticdata;
 pub mod structures;
+pub mod timeseries;
+pub mod toc;
+pub mod units;
+pub mod verify;
+pub mod wav;
+pub mod writer;
 
+mod cksum;
+mod compress;
 mod decompress;
 mod endian;
+#[cfg(test)]
+mod fixtures;
 mod header;
+mod links;
 mod parser;
 mod strings;
 mod v6;
@@ -21,11 +46,22 @@ pub fn parse_file<T: handler::Handler>(filename: &str, handler: &mut T) -> Resul
     parse(&mut reader, handler)
 }
 
+/// parse_header reads the file header at the start of reader.
+pub fn parse_header<R: Read + Seek>(reader: &mut R) -> Result<structures::Header> {
+    reader.seek(SeekFrom::Start(0))?;
+    header::parse(reader)
+        .ok_or_else(|| Error::new(ErrorKind::InvalidData, "unknown file type".to_string()))
+}
+
+/// parse reads every structure of the file into handler.  It always starts
+/// from the beginning of reader, whatever its position, so a reader already
+/// used by parse_header can be passed without rewinding it.
 pub fn parse<R, T>(reader: &mut R, handler: &mut T) -> Result<()>
 where
     R: Read + Seek,
     T: handler::Handler,
 {
+    reader.seek(SeekFrom::Start(0))?;
     // TODO(goller): handle error
     let hdr = match header::parse(reader) {
         Some(h) => h,
@@ -58,6 +94,50 @@ mod tests {
     struct NopHandler {}
     impl handler::Handler for NopHandler {}
 
+    #[derive(Default)]
+    struct EofHandler {
+        eof: bool,
+    }
+
+    impl handler::Handler for EofHandler {
+        fn eof(&mut self) -> Option<fn(&mut Self)> {
+            Some(|h| h.eof = true)
+        }
+    }
+
+    /// empty_file is a little endian version 8 file holding only the
+    /// dictionary entry of FrEndOfFile and the FrEndOfFile itself.
+    fn empty_file() -> Vec<u8> {
+        let mut buf = b"IGWD\0".to_vec();
+        buf.extend_from_slice(&[8, 0, 2, 4, 8, 4, 8, 0x34, 0x12]);
+        buf.resize(structures::Header::size_of(), 0);
+
+        let name = b"FrEndOfFile\0";
+        let length = 14 + 2 + name.len() + 2 + 2 + 1;
+        buf.extend_from_slice(&(length as u64).to_le_bytes());
+        buf.extend_from_slice(&[0, 1, 0, 0, 0, 0]);
+        buf.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        buf.extend_from_slice(name);
+        buf.extend_from_slice(&3u16.to_le_bytes());
+        buf.extend_from_slice(&[1, 0, 0]);
+
+        buf.extend_from_slice(&14u64.to_le_bytes());
+        buf.extend_from_slice(&[0, 3, 0, 0, 0, 0]);
+        buf
+    }
+
+    #[test]
+    fn test_parse_after_parse_header() -> Result<()> {
+        let mut reader = std::io::Cursor::new(empty_file());
+        let header = parse_header(&mut reader)?;
+        assert_eq!(header.version.major, structures::Major::Release8);
+
+        let mut handler = EofHandler::default();
+        parse(&mut reader, &mut handler)?;
+        assert!(handler.eof);
+        Ok(())
+    }
+
     #[bench]
     fn bench_gw_open_data_workshop_t3_0(b: &mut Bencher) {
         let filename = "assets/PyCBC_T3_0.gwf";
@@ -0,0 +1,124 @@
+use crate::structures::*;
+use std::collections::HashMap;
+
+/// Links associates structures with the data vectors they point to.
+/// Owners are written before the vectors they reference, so each owner
+/// waits here until the vector with its data reference is parsed.
+pub(crate) struct Links {
+    channels: HashMap<Reference, Channel>,
+    tables: Vec<(Reference, Table)>,
+    detectors: HashMap<Reference, Detector>,
+    statics: Vec<PendingStatic>,
+}
+
+/// PendingStatic is static data waiting for its detector or data vector.
+pub(crate) struct PendingStatic {
+    pub data: StaticData,
+    pub detector_ref: Reference,
+    pub data_ref: Reference,
+}
+
+impl PendingStatic {
+    fn is_complete(&self) -> bool {
+        self.detector_ref.is_null() && self.data_ref.is_null()
+    }
+}
+
+impl Links {
+    pub(crate) fn new() -> Links {
+        Links {
+            channels: HashMap::new(),
+            tables: Vec::new(),
+            detectors: HashMap::new(),
+            statics: Vec::new(),
+        }
+    }
+
+    /// expect records that the vector referenced by data belongs to channel.
+    pub(crate) fn expect(&mut self, data: Reference, channel: Channel) {
+        if !data.is_null() {
+            self.channels.insert(data, channel);
+        }
+    }
+
+    /// channel returns the owner of the vector described by common, if any.
+    pub(crate) fn channel(&mut self, common: &Common) -> Option<Channel> {
+        if self.channels.is_empty() {
+            return None;
+        }
+        self.channels.remove(&Reference {
+            class: common.class() as u16,
+            instance: common.instance(),
+        })
+    }
+
+    /// expect_column records that the vector referenced by column is the next column of table.
+    pub(crate) fn expect_column(&mut self, column: Reference, table: Table) {
+        if !column.is_null() {
+            self.tables.push((column, table));
+        }
+    }
+
+    /// table returns the table waiting for the vector described by common, if any.
+    pub(crate) fn table(&mut self, common: &Common) -> Option<Table> {
+        let i = self.tables.iter().position(|(r, _)| {
+            r.class == common.class() as u16 && r.instance == common.instance()
+        })?;
+        Some(self.tables.remove(i).1)
+    }
+
+    /// detector remembers d for static data and returns the static data that became complete.
+    pub(crate) fn detector(&mut self, common: &Common, d: Detector) -> Vec<StaticData> {
+        let reference = Reference {
+            class: common.class() as u16,
+            instance: common.instance(),
+        };
+        for pending in self.statics.iter_mut() {
+            if pending.detector_ref == reference {
+                pending.data.detector = Some(d.clone());
+                pending.detector_ref = Reference::default();
+            }
+        }
+        self.detectors.insert(reference, d);
+        self.complete()
+    }
+
+    /// expect_static attaches an already parsed detector and returns the static
+    /// data if nothing else is outstanding.
+    pub(crate) fn expect_static(&mut self, mut pending: PendingStatic) -> Option<StaticData> {
+        if let Some(d) = self.detectors.get(&pending.detector_ref) {
+            pending.data.detector = Some(d.clone());
+            pending.detector_ref = Reference::default();
+        }
+        if pending.is_complete() {
+            return Some(pending.data);
+        }
+        self.statics.push(pending);
+        None
+    }
+
+    /// static_data removes the static data waiting for the vector described by common, if any.
+    pub(crate) fn static_data(&mut self, common: &Common) -> Option<PendingStatic> {
+        let i = self.statics.iter().position(|p| {
+            p.data_ref.class == common.class() as u16 && p.data_ref.instance == common.instance()
+        })?;
+        Some(self.statics.remove(i))
+    }
+
+    fn complete(&mut self) -> Vec<StaticData> {
+        let (done, waiting) = self.statics.drain(..).partition(|p| p.is_complete());
+        self.statics = waiting;
+        done.into_iter().map(|p: PendingStatic| p.data).collect()
+    }
+
+    /// unresolved returns the static data whose detector or vector never appeared.
+    pub(crate) fn unresolved(&mut self) -> Vec<StaticData> {
+        self.statics.drain(..).map(|p| p.data).collect()
+    }
+
+    /// incomplete returns the tables still waiting for columns in the order
+    /// they were parsed.
+    pub(crate) fn incomplete(&mut self) -> Vec<Table> {
+        self.tables.drain(..).map(|(_, t)| t).collect()
+    }
+}
@@ -0,0 +1,474 @@
+use crate::gps::GpsTime;
+use crate::handler::Handler;
+use crate::structures::*;
+use std::io::{Error, ErrorKind, Result, Write};
+
+/// dtype is the NumPy type of the data.  Samples are written little endian.
+pub fn dtype(data: &Vector) -> Result<&'static str> {
+    Ok(match data {
+        Vector::I8(_, _) => "|i1",
+        Vector::U8(_, _) => "|u1",
+        Vector::I16(_, _) => "<i2",
+        Vector::U16(_, _) => "<u2",
+        Vector::I32(_, _) => "<i4",
+        Vector::U32(_, _) => "<u4",
+        Vector::U64(_, _) => "<u8",
+        Vector::I64(_, _) => "<i8",
+        Vector::F32(_, _) => "<f4",
+        Vector::F64(_, _) => "<f8",
+        Vector::Complexes(_, _) => "<c16",
+        Vector::Strings(info, _) => {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("{}: strings cannot be written as .npy", info.name),
+            ))
+        }
+    })
+}
+
+/// shape is the dimension lengths of the data, the first varying slowest.
+fn shape(data: &Vector) -> Result<Vec<u64>> {
+    let info = data.info();
+    if info.dimension_lengths.is_empty() {
+        return Ok(vec![data.len() as u64]);
+    }
+    let n: u64 = info.dimension_lengths.iter().product();
+    if n != data.len() as u64 {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!(
+                "{}: {} samples do not fit shape {:?}",
+                info.name,
+                data.len(),
+                info.dimension_lengths
+            ),
+        ));
+    }
+    Ok(info.dimension_lengths.clone())
+}
+
+/// write_npy writes the data as a version 1.0 .npy file.
+pub fn write_npy<W: Write>(out: &mut W, data: &Vector) -> Result<()> {
+    let descr = dtype(data)?;
+    let shape = shape(data)?;
+    let shape = match shape.len() {
+        1 => format!("({},)", shape[0]),
+        _ => {
+            let s: Vec<String> = shape.iter().map(|n| n.to_string()).collect();
+            format!("({})", s.join(", "))
+        }
+    };
+    let mut header = format!(
+        "{{'descr': '{}', 'fortran_order': False, 'shape': {}, }}",
+        descr, shape
+    );
+    // the magic, version and header length take 10 bytes; the data starts
+    // on a multiple of 64 after the header's closing newline.
+    while (10 + header.len() + 1) % 64 != 0 {
+        header.push(' ');
+    }
+    header.push('\n');
+
+    out.write_all(b"\x93NUMPY\x01\x00")?;
+    out.write_all(&(header.len() as u16).to_le_bytes())?;
+    out.write_all(header.as_bytes())?;
+
+    macro_rules! samples {
+        ($($variant:ident),*) => {
+            match data {
+                $(Vector::$variant(_, v) => {
+                    let mut buf = Vec::with_capacity(std::mem::size_of_val(&v[..]));
+                    for x in v.iter() {
+                        buf.extend_from_slice(&x.to_le_bytes());
+                    }
+                    out.write_all(&buf)
+                })*
+                Vector::Complexes(_, v) => {
+                    let mut buf = Vec::with_capacity(v.len() * 16);
+                    for c in v.iter() {
+                        buf.extend_from_slice(&c.real.to_le_bytes());
+                        buf.extend_from_slice(&c.imaginary.to_le_bytes());
+                    }
+                    out.write_all(&buf)
+                }
+                Vector::Strings(_, _) => unreachable!(),
+            }
+        };
+    }
+    samples!(I8, U8, I16, U16, I32, U32, U64, I64, F32, F64)
+}
+
+/// Entry is an array written to a .npz file.
+struct Entry {
+    name: String,
+    crc: u32,
+    size: u32,
+    offset: u32,
+}
+
+/// NpzWriter writes arrays into a .npz file: an uncompressed zip archive
+/// with a .npy member per array, as written by numpy.savez.
+pub struct NpzWriter<W: Write> {
+    out: W,
+    offset: u64,
+    entries: Vec<Entry>,
+}
+
+impl<W: Write> NpzWriter<W> {
+    pub fn new(out: W) -> Self {
+        NpzWriter {
+            out,
+            offset: 0,
+            entries: Vec::new(),
+        }
+    }
+
+    /// add writes the data as the array named name, which numpy.load
+    /// returns under that name.
+    pub fn add(&mut self, name: &str, data: &Vector) -> Result<()> {
+        let name = format!("{}.npy", name);
+        if self.entries.iter().any(|e| e.name == name) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("{} added twice", name),
+            ));
+        }
+        let mut npy = Vec::new();
+        write_npy(&mut npy, data)?;
+        let mut crc = flate2::Crc::new();
+        crc.update(&npy);
+        let entry = Entry {
+            crc: crc.sum(),
+            size: small(npy.len() as u64)?,
+            offset: small(self.offset)?,
+            name,
+        };
+
+        let mut header = Vec::with_capacity(30 + entry.name.len());
+        header.extend_from_slice(&0x0403_4b50u32.to_le_bytes());
+        header.extend_from_slice(&20u16.to_le_bytes()); // version needed
+        header.extend_from_slice(&0u16.to_le_bytes()); // flags
+        header.extend_from_slice(&0u16.to_le_bytes()); // stored
+        header.extend_from_slice(&0u16.to_le_bytes()); // time
+        header.extend_from_slice(&DATE.to_le_bytes());
+        header.extend_from_slice(&entry.crc.to_le_bytes());
+        header.extend_from_slice(&entry.size.to_le_bytes());
+        header.extend_from_slice(&entry.size.to_le_bytes());
+        header.extend_from_slice(&(entry.name.len() as u16).to_le_bytes());
+        header.extend_from_slice(&0u16.to_le_bytes()); // extra length
+        header.extend_from_slice(entry.name.as_bytes());
+        self.out.write_all(&header)?;
+        self.out.write_all(&npy)?;
+        self.offset += (header.len() + npy.len()) as u64;
+        self.entries.push(entry);
+        Ok(())
+    }
+
+    /// finish writes the zip central directory.
+    pub fn finish(mut self) -> Result<W> {
+        let start = small(self.offset)?;
+        let mut directory = Vec::new();
+        for e in self.entries.iter() {
+            directory.extend_from_slice(&0x0201_4b50u32.to_le_bytes());
+            directory.extend_from_slice(&20u16.to_le_bytes()); // version made by
+            directory.extend_from_slice(&20u16.to_le_bytes()); // version needed
+            directory.extend_from_slice(&0u16.to_le_bytes()); // flags
+            directory.extend_from_slice(&0u16.to_le_bytes()); // stored
+            directory.extend_from_slice(&0u16.to_le_bytes()); // time
+            directory.extend_from_slice(&DATE.to_le_bytes());
+            directory.extend_from_slice(&e.crc.to_le_bytes());
+            directory.extend_from_slice(&e.size.to_le_bytes());
+            directory.extend_from_slice(&e.size.to_le_bytes());
+            directory.extend_from_slice(&(e.name.len() as u16).to_le_bytes());
+            directory.extend_from_slice(&[0; 8]); // extra, comment, disk, attributes
+            directory.extend_from_slice(&0u32.to_le_bytes()); // external attributes
+            directory.extend_from_slice(&e.offset.to_le_bytes());
+            directory.extend_from_slice(e.name.as_bytes());
+        }
+        let count = self.entries.len() as u16;
+        directory.extend_from_slice(&0x0605_4b50u32.to_le_bytes());
+        directory.extend_from_slice(&[0; 4]); // disk numbers
+        directory.extend_from_slice(&count.to_le_bytes());
+        directory.extend_from_slice(&count.to_le_bytes());
+        directory.extend_from_slice(&small(directory.len() as u64 - 12)?.to_le_bytes());
+        directory.extend_from_slice(&start.to_le_bytes());
+        directory.extend_from_slice(&0u16.to_le_bytes()); // comment length
+        self.out.write_all(&directory)?;
+        Ok(self.out)
+    }
+}
+
+/// DATE is 1980-01-01, the earliest MS-DOS date a zip entry can carry.
+const DATE: u16 = 0x21;
+
+/// small checks that a size or offset fits a zip without the zip64 extension.
+fn small(n: u64) -> Result<u32> {
+    if n > u32::MAX as u64 {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "the .npz file would exceed 4 GiB",
+        ));
+    }
+    Ok(n as u32)
+}
+
+/// Excerpt is a handler gathering the named channels of frame files to
+/// write as arrays.  Parse files in GPS order with the same excerpt, then
+/// call finish.
+///
+/// Time series of consecutive frames are joined and cut to the window; the
+/// origin of their first dimension becomes the GPS time of the first
+/// sample.  Other vectors, such as spectra, are kept whole and may only
+/// appear in one frame of the window.
+pub struct Excerpt {
+    names: Vec<String>,
+    /// all is set when no names were given.
+    all: bool,
+    start: Option<GpsTime>,
+    end: Option<GpsTime>,
+    frame: Option<FrameHeader>,
+    vectors: Vec<Option<(Channel, Vector)>>,
+    /// next is the time expected of the next sample of each time series.
+    next: Vec<Option<GpsTime>>,
+    error: Option<Error>,
+}
+
+impl Excerpt {
+    /// new gathers the named channels.  With no names every channel is
+    /// gathered in the order first read.
+    pub fn new(names: &[&str]) -> Self {
+        Excerpt {
+            names: names.iter().map(|n| n.to_string()).collect(),
+            all: names.is_empty(),
+            start: None,
+            end: None,
+            frame: None,
+            vectors: vec![None; names.len()],
+            next: vec![None; names.len()],
+            error: None,
+        }
+    }
+
+    /// window limits the excerpt to `[start, end)`.
+    pub fn window(&mut self, start: Option<GpsTime>, end: Option<GpsTime>) {
+        self.start = start;
+        self.end = end;
+    }
+
+    /// finish returns each channel, as first read, and its data in the
+    /// order named.  Named channels without data in the window are errors.
+    pub fn finish(self) -> Result<Vec<(Channel, Vector)>> {
+        if let Some(e) = self.error {
+            return Err(e);
+        }
+        let all = self.all;
+        self.names
+            .into_iter()
+            .zip(self.vectors)
+            .filter(|(_, v)| !all || v.is_some())
+            .map(|(name, v)| {
+                v.ok_or_else(|| {
+                    Error::new(
+                        ErrorKind::NotFound,
+                        format!("{}: no data in the time range", name),
+                    )
+                })
+            })
+            .collect()
+    }
+
+    fn add(&mut self, i: usize, channel: Channel, data: Vector) -> Result<()> {
+        let frame = match &self.frame {
+            Some(f) => f,
+            None => return Ok(()),
+        };
+        let axis = match crate::cut::time_axis(&channel, &data) {
+            Some(a) => a,
+            None => {
+                let overlaps = self.start.is_none_or(|s| s < frame.end_time())
+                    && self.end.is_none_or(|e| e > frame.start_time());
+                if !overlaps {
+                    return Ok(());
+                }
+                if self.vectors[i].is_some() {
+                    return Err(Error::new(
+                        ErrorKind::InvalidData,
+                        format!(
+                            "{}: is not a time series and is in more than one frame; \
+                             choose one frame with the time range",
+                            self.names[i]
+                        ),
+                    ));
+                }
+                self.vectors[i] = Some((channel, data));
+                return Ok(());
+            }
+        };
+
+        let start = self.start.unwrap_or_else(|| GpsTime::new(0, 0));
+        let end = self.end.unwrap_or_else(|| GpsTime::new(u32::MAX, 0));
+        let (mut piece, time) = match crate::cut::cut(frame, &channel, &data, start, end) {
+            Some(p) => p,
+            None => return Ok(()),
+        };
+        piece.info_mut().x_origins[0] = time.as_seconds();
+        let expected = self.next[i].replace(time + piece.len() as f64 * axis.spacing);
+        let joined = match self.vectors[i].as_mut() {
+            Some((_, v)) => v,
+            None => {
+                self.vectors[i] = Some((channel, piece));
+                return Ok(());
+            }
+        };
+        if expected.is_some_and(|t| (time - t).abs() > axis.spacing / 2.0) {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("{}: gap before {}", self.names[i], time),
+            ));
+        }
+        joined.append(piece).map_err(|_| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("{}: the sample type changes at {}", self.names[i], time),
+            )
+        })
+    }
+}
+
+impl Handler for Excerpt {
+    fn begin_frame(&mut self) -> Option<fn(&mut Self, header: FrameHeader)> {
+        Some(|e, h| e.frame = Some(h))
+    }
+
+    fn channel(&mut self) -> Option<fn(&mut Self, channel: Channel, data: Vector)> {
+        Some(|e, c, v| {
+            let i = match e.names.iter().position(|n| n == c.name()) {
+                Some(i) => i,
+                None if e.all => {
+                    e.names.push(c.name().to_string());
+                    e.vectors.push(None);
+                    e.next.push(None);
+                    e.names.len() - 1
+                }
+                None => return,
+            };
+            if let Err(err) = e.add(i, c, v) {
+                e.error.get_or_insert(err);
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod npy_tests {
+    use super::*;
+
+    fn info(dimension_lengths: Vec<u64>) -> VectorInfo {
+        VectorInfo {
+            name: "H1:A".to_string(),
+            num_samples: dimension_lengths.iter().product(),
+            num_dimensions: dimension_lengths.len() as u32,
+            sample_spacing: vec![1.0; dimension_lengths.len()],
+            x_origins: vec![0.0; dimension_lengths.len()],
+            unit_x_scale_factors: vec![String::new(); dimension_lengths.len()],
+            unit_y: String::new(),
+            compression: 0,
+            dimension_lengths,
+        }
+    }
+
+    #[test]
+    fn test_write_npy() -> Result<()> {
+        let mut out = Vec::new();
+        write_npy(
+            &mut out,
+            &Vector::I16(info(vec![2, 3]), vec![0, 1, 2, 3, 4, -1]),
+        )?;
+        assert_eq!(&out[..8], b"\x93NUMPY\x01\x00");
+        let len = u16::from_le_bytes([out[8], out[9]]) as usize;
+        assert_eq!((10 + len) % 64, 0);
+        let header = std::str::from_utf8(&out[10..10 + len]).unwrap();
+        assert!(header.starts_with("{'descr': '<i2', 'fortran_order': False, 'shape': (2, 3), }"));
+        assert!(header.ends_with(" \n"));
+        assert_eq!(out.len(), 10 + len + 12);
+        assert_eq!(&out[out.len() - 2..], &[0xff, 0xff]);
+
+        let c = Complex {
+            real: 1.0,
+            imaginary: 2.0,
+        };
+        let mut out = Vec::new();
+        write_npy(&mut out, &Vector::Complexes(info(vec![1]), vec![c]))?;
+        let header = std::str::from_utf8(&out[10..128]).unwrap();
+        assert!(header.contains("'descr': '<c16'"));
+        assert!(header.contains("'shape': (1,)"));
+        assert_eq!(&out[128..136], &1.0f64.to_le_bytes());
+        assert_eq!(&out[136..], &2.0f64.to_le_bytes());
+
+        let bad = Vector::F32(info(vec![2, 2]), vec![0.0; 3]);
+        assert!(write_npy(&mut Vec::new(), &bad).is_err());
+        let strings = Vector::Strings(info(vec![1]), vec![String::new()]);
+        assert!(write_npy(&mut Vec::new(), &strings).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_npz() -> Result<()> {
+        let mut npz = NpzWriter::new(Vec::new());
+        let a = Vector::F64(info(vec![2]), vec![0.5, 1.5]);
+        npz.add("H1:A", &a)?;
+        npz.add("H1:B", &Vector::U8(info(vec![1]), vec![7]))?;
+        assert!(npz.add("H1:A", &a).is_err());
+        let out = npz.finish()?;
+
+        let mut npy = Vec::new();
+        write_npy(&mut npy, &a)?;
+        assert_eq!(&out[..4], b"PK\x03\x04");
+        assert_eq!(&out[30..38], b"H1:A.npy");
+        assert_eq!(&out[38..38 + npy.len()], &npy[..]);
+        let crc = u32::from_le_bytes([out[14], out[15], out[16], out[17]]);
+        let mut check = flate2::Crc::new();
+        check.update(&npy);
+        assert_eq!(crc, check.sum());
+
+        let end = &out[out.len() - 22..];
+        assert_eq!(&end[..4], b"PK\x05\x06");
+        assert_eq!(u16::from_le_bytes([end[10], end[11]]), 2);
+        let start = u32::from_le_bytes([end[16], end[17], end[18], end[19]]) as usize;
+        assert_eq!(&out[start..start + 4], b"PK\x01\x02");
+        Ok(())
+    }
+
+    #[test]
+    fn test_excerpt() -> Result<()> {
+        let input = crate::fixtures::file(3, &["H1:A"]);
+
+        let mut excerpt = Excerpt::new(&["H1:A"]);
+        excerpt.window(
+            Some(GpsTime::new(1_000_000_000, 500_000_000)),
+            Some(GpsTime::new(1_000_000_002, 250_000_000)),
+        );
+        crate::parse(&mut std::io::Cursor::new(&input), &mut excerpt)?;
+        let vectors = excerpt.finish()?;
+        assert_eq!(vectors.len(), 1);
+        assert_eq!(vectors[0].0.name(), "H1:A");
+        match &vectors[0].1 {
+            Vector::I16(info, v) => {
+                assert_eq!(v, &vec![2, 3, 4, 5, 6, 7, 8]);
+                assert_eq!(info.dimension_lengths, vec![7]);
+                assert_eq!(info.x_origins, vec![1_000_000_000.5]);
+            }
+            v => panic!("unexpected {:?}", v),
+        }
+
+        let mut missing = Excerpt::new(&["H1:Z"]);
+        crate::parse(&mut std::io::Cursor::new(&input), &mut missing)?;
+        assert!(missing.finish().is_err());
+
+        let mut all = Excerpt::new(&[]);
+        all.window(Some(GpsTime::new(1_000_000_003, 0)), None);
+        crate::parse(&mut std::io::Cursor::new(&input), &mut all)?;
+        assert!(all.finish()?.is_empty());
+        Ok(())
+    }
+}
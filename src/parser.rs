@@ -1,3 +1,4 @@
+use crate::links::Links;
 use crate::structures::*;
 use crate::v6;
 use crate::v8;
@@ -35,8 +36,89 @@ impl Parser {
         T: Handler,
     {
         let mut struct_lookup = StructureLookup::new();
+        let mut links = Links::new();
 
         reader.seek(SeekFrom::Start(Header::size_of() as u64))?;
+        self.structures(
+            header,
+            reader,
+            handler,
+            &mut struct_lookup,
+            &mut links,
+            |_, _| false,
+        )?;
+
+        if let Some(handle_static) = handler.static_data() {
+            for s in links.unresolved() {
+                handle_static(handler, s);
+            }
+        }
+
+        // tables whose columns never appeared are delivered with the ones
+        // found, like tables whose chain of columns ends early.
+        if let Some(handle_table) = handler.table() {
+            for t in links.incomplete() {
+                handle_table(handler, t);
+            }
+        }
+
+        if let Some(eof) = handler.eof() {
+            eof(handler);
+        }
+
+        Ok(())
+    }
+
+    /// parse_at parses the structures of one frame starting at position,
+    /// e.g. a channel found through the table of contents.  lookup must
+    /// already hold the file's dictionary.  Parsing stops at the end of the
+    /// frame or once done returns true.
+    pub(crate) fn parse_at<R, T>(
+        &mut self,
+        header: &Header,
+        reader: &mut R,
+        position: u64,
+        lookup: &mut StructureLookup,
+        handler: &mut T,
+        done: fn(&T) -> bool,
+    ) -> std::io::Result<()>
+    where
+        R: Read + Seek,
+        T: Handler,
+    {
+        let mut links = Links::new();
+        reader.seek(SeekFrom::Start(position))?;
+        self.structures(
+            header,
+            reader,
+            handler,
+            lookup,
+            &mut links,
+            |handler, structure| {
+                done(handler)
+                    || structure == Structures::FrEndOfFrame
+                    || structure == Structures::FrEndOfFile
+            },
+        )
+    }
+
+    /// structures parses each structure from the current position until end
+    /// returns true for the handler and the next structure, or until the end
+    /// of the file.
+    fn structures<R, T, F>(
+        &mut self,
+        header: &Header,
+        reader: &mut R,
+        handler: &mut T,
+        lookup: &mut StructureLookup,
+        links: &mut Links,
+        mut end: F,
+    ) -> std::io::Result<()>
+    where
+        R: Read + Seek,
+        T: Handler,
+        F: FnMut(&T, Structures) -> bool,
+    {
         loop {
             let common = match header.version.major {
                 Major::Release8 => v8::common(header, reader, &mut self.common_buf)?,
@@ -44,68 +126,82 @@ impl Parser {
                 _ => unimplemented!("no support for version: {:?}", header.version),
             };
 
-            let structure = struct_lookup.structure(common.class());
+            let structure = lookup.structure(common.class());
 
-            if structure == Structures::FrEndOfFile {
-                break;
+            if end(handler, structure) {
+                return Ok(());
             }
 
-            if !self.handles(&structure, handler) {
+            if !self.handles(&structure, header, handler) {
                 reader.seek(common.seek_past())?;
-                continue;
-            }
-            {
+            } else {
                 let mut struct_reader = reader.by_ref().take(common.struct_length());
                 self.handle(
                     structure,
                     header,
+                    &common,
                     &mut struct_reader,
                     handler,
-                    &mut struct_lookup,
+                    lookup,
+                    links,
                 )?;
 
                 // try to consume the rest of the struct reader.
                 let limit = struct_reader.limit() as i64;
                 struct_reader.into_inner().seek(SeekFrom::Current(limit))?;
             }
-        }
 
-        if let Some(eof) = handler.eof() {
-            eof(handler);
+            if structure == Structures::FrEndOfFile {
+                return Ok(());
+            }
         }
-
-        Ok(())
     }
 
-    fn handles<T: Handler>(&self, class: &Structures, handler: &mut T) -> bool {
+    fn handles<T: Handler>(&self, class: &Structures, header: &Header, handler: &mut T) -> bool {
+        let v8 = header.version.major == Major::Release8;
         match class {
             Structures::FrSH => true,
             Structures::FrameH => handler.begin_frame().is_some(),
-            Structures::FrDetector => handler.detector().is_some(),
-            Structures::FrAdcData => handler.adc().is_some(),
+            Structures::FrDetector => {
+                handler.detector().is_some() || handler.static_data().is_some()
+            }
+            Structures::FrAdcData => handler.adc().is_some() || handler.channel().is_some(),
             Structures::FrMsg => handler.message().is_some(),
             Structures::FrHistory => handler.history().is_some(),
             Structures::FrRawData => handler.raw().is_some(),
-            Structures::FrProcData => handler.post_processed().is_some(),
-            Structures::FrSimData => handler.simulated().is_some(),
+            Structures::FrProcData => {
+                handler.post_processed().is_some() || handler.channel().is_some()
+            }
+            Structures::FrSimData => handler.simulated().is_some() || handler.channel().is_some(),
             Structures::FrSimEvent => handler.simulated_event().is_some(),
-            Structures::FrSerData => handler.serial().is_some(),
+            Structures::FrSerData => handler.serial().is_some() || handler.channel().is_some(),
             Structures::FrStatData => handler.static_data().is_some(),
             Structures::FrSummary => handler.summary().is_some(),
             Structures::FrTable => handler.table().is_some(),
-            Structures::FrVect => handler.vector().is_some(),
+            Structures::FrVect => {
+                handler.vector().is_some()
+                    || handler.channel().is_some()
+                    || handler.table().is_some()
+                    || handler.static_data().is_some()
+            }
             Structures::FrEvent => handler.event().is_some(),
+            Structures::FrEndOfFrame => v8 && handler.end_frame().is_some(),
+            Structures::FrTOC => v8 && handler.toc().is_some(),
+            Structures::FrEndOfFile => v8 && handler.end_of_file().is_some(),
             _ => false,
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn handle<R: Read, T: Handler>(
         &mut self,
         class: Structures,
         header: &Header,
+        common: &Common,
         reader: &mut Take<R>,
         handler: &mut T,
         lookup: &mut StructureLookup,
+        links: &mut Links,
     ) -> std::io::Result<()> {
         match class {
             Structures::FrSH => v8::structure_header(header, reader, lookup, &mut self.buf16),
@@ -117,13 +213,20 @@ impl Parser {
                 &mut self.buf32,
                 &mut self.buf64,
             ),
-            Structures::FrDetector => {
-                v8::detector(header, reader, handler, &mut self.buf32, &mut self.buf64)
-            }
+            Structures::FrDetector => v8::detector(
+                header,
+                common,
+                reader,
+                handler,
+                links,
+                &mut self.buf32,
+                &mut self.buf64,
+            ),
             Structures::FrAdcData => v8::adc(
                 header,
                 reader,
                 handler,
+                links,
                 &mut self.buf16,
                 &mut self.buf32,
                 &mut self.buf64,
@@ -135,15 +238,22 @@ impl Parser {
                 header,
                 reader,
                 handler,
+                links,
                 &mut self.buf16,
                 &mut self.buf32,
                 &mut self.buf64,
             ),
             Structures::FrSimData => match header.version.major {
-                Major::Release8 => {
-                    v8::simulated(header, reader, handler, &mut self.buf32, &mut self.buf64)
-                }
-                Major::Release6 => v6::simulated(header, reader, handler),
+                Major::Release8 => v8::simulated(
+                    header,
+                    reader,
+                    handler,
+                    links,
+                    &mut self.buf16,
+                    &mut self.buf32,
+                    &mut self.buf64,
+                ),
+                Major::Release6 => v6::simulated(header, reader, handler, links),
                 _ => unreachable!(),
             },
             Structures::FrSimEvent => match header.version.major {
@@ -159,21 +269,41 @@ impl Parser {
                 _ => unreachable!(),
             },
             Structures::FrSerData => match header.version.major {
-                Major::Release8 => {
-                    v8::serial(header, reader, handler, &mut self.buf32, &mut self.buf64)
-                }
-                Major::Release6 => v6::serial(header, reader, handler),
+                Major::Release8 => v8::serial(
+                    header,
+                    reader,
+                    handler,
+                    links,
+                    &mut self.buf16,
+                    &mut self.buf32,
+                    &mut self.buf64,
+                ),
+                Major::Release6 => v6::serial(header, reader, handler, links),
                 _ => unreachable!(),
             },
-            Structures::FrStatData => v8::static_data(header, reader, handler, &mut self.buf32),
+            Structures::FrStatData => v8::static_data(
+                header,
+                reader,
+                handler,
+                links,
+                &mut self.buf16,
+                &mut self.buf32,
+            ),
             Structures::FrSummary => v8::summary(header, reader, handler, &mut self.buf32),
-            Structures::FrTable => {
-                v8::table(header, reader, handler, &mut self.buf16, &mut self.buf32)
-            }
+            Structures::FrTable => v8::table(
+                header,
+                reader,
+                handler,
+                links,
+                &mut self.buf16,
+                &mut self.buf32,
+            ),
             Structures::FrVect => v8::vector(
                 header,
+                common,
                 reader,
                 handler,
+                links,
                 &mut self.buf16,
                 &mut self.buf32,
                 &mut self.buf64,
@@ -191,7 +321,151 @@ impl Parser {
                 Major::Release6 => v6::event(header, reader, handler),
                 _ => unreachable!(),
             },
+            Structures::FrEndOfFrame => v8::end_of_frame(header, reader, handler, &mut self.buf32),
+            Structures::FrTOC => {
+                let toc = v8::toc(header, reader)?;
+                if let Some(handle_toc) = handler.toc() {
+                    handle_toc(handler, toc);
+                }
+                Ok(())
+            }
+            Structures::FrEndOfFile => {
+                v8::end_of_file(header, reader, handler, &mut self.buf32, &mut self.buf64)
+            }
             _ => Ok(()),
         }
     }
 }
+
+#[cfg(test)]
+mod parser_tests {
+    use super::*;
+    use crate::fixtures::{reference, string, Raw};
+
+    const TABLE: u8 = 3;
+    const VECT: u8 = 4;
+    const END: u8 = 5;
+
+    #[derive(Default)]
+    struct Tables {
+        tables: Vec<Table>,
+    }
+
+    impl Handler for Tables {
+        fn table(&mut self) -> Option<fn(&mut Self, data: Table)> {
+            Some(|h, t| h.tables.push(t))
+        }
+    }
+
+    /// table encodes an FrTable of rows whose first column is vector
+    /// instance first.
+    fn table(name: &str, rows: u32, columns: &[&str], first: u32) -> Vec<u8> {
+        let mut b = string(name);
+        b.extend(string(""));
+        b.extend_from_slice(&(columns.len() as u16).to_le_bytes());
+        b.extend_from_slice(&rows.to_le_bytes());
+        for c in columns {
+            b.extend(string(c));
+        }
+        b.extend(reference(VECT as u16, first));
+        b
+    }
+
+    /// column encodes a raw FrVect of INT_4S values followed by next, or by
+    /// the null pointer.
+    fn column(name: &str, values: &[i32], next: Option<u32>) -> Vec<u8> {
+        let n = values.len() as u64;
+        let mut b = string(name);
+        b.extend_from_slice(&256u16.to_le_bytes());
+        b.extend_from_slice(&4u16.to_le_bytes());
+        b.extend_from_slice(&n.to_le_bytes());
+        b.extend_from_slice(&(4 * n).to_le_bytes());
+        for v in values {
+            b.extend_from_slice(&v.to_le_bytes());
+        }
+        b.extend_from_slice(&1u32.to_le_bytes());
+        b.extend_from_slice(&n.to_le_bytes());
+        b.extend_from_slice(&1f64.to_le_bytes());
+        b.extend_from_slice(&0f64.to_le_bytes());
+        b.extend(string(""));
+        b.extend(string(""));
+        match next {
+            Some(i) => b.extend(reference(VECT as u16, i)),
+            None => b.extend(reference(0, 0)),
+        }
+        b
+    }
+
+    #[test]
+    fn test_missing_columns() -> std::io::Result<()> {
+        // "pending" waits for vector 2, which is not in the file; the chain
+        // of "short" ends after its first column.
+        let file = Raw::new()
+            .dictionary("FrTable", TABLE)
+            .dictionary("FrVect", VECT)
+            .dictionary("FrEndOfFile", END)
+            .structure(TABLE, 0, &table("pending", 1, &["a", "b", "c"], 0))
+            .structure(VECT, 0, &column("a", &[1], Some(1)))
+            .structure(VECT, 1, &column("b", &[2], Some(2)))
+            .structure(TABLE, 1, &table("short", 1, &["x", "y"], 3))
+            .structure(VECT, 3, &column("x", &[3], None))
+            .structure(END, 0, &[])
+            .bytes();
+
+        let mut tables = Tables::default();
+        crate::parse(&mut std::io::Cursor::new(file), &mut tables)?;
+        tables.tables.sort_by(|a, b| a.name.cmp(&b.name));
+        let found: Vec<(&str, usize, &[String])> = tables
+            .tables
+            .iter()
+            .map(|t| (t.name.as_str(), t.columns.len(), t.missing_columns()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("pending", 2, &["c".to_string()][..]),
+                ("short", 1, &["y".to_string()][..]),
+            ]
+        );
+        assert_eq!(
+            tables.tables[0].column("b").and_then(|c| c.value(0)),
+            Some(Value::I32(2))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_column_rows() -> std::io::Result<()> {
+        // "b" of "wrong" has two rows instead of one; "later" and "first"
+        // wait for vectors that are not in the file.
+        let file = Raw::new()
+            .dictionary("FrTable", TABLE)
+            .dictionary("FrVect", VECT)
+            .dictionary("FrEndOfFile", END)
+            .structure(TABLE, 0, &table("wrong", 1, &["a", "b", "c"], 0))
+            .structure(VECT, 0, &column("a", &[1], Some(1)))
+            .structure(VECT, 1, &column("b", &[2, 3], Some(2)))
+            .structure(VECT, 2, &column("c", &[4], None))
+            .structure(TABLE, 1, &table("later", 1, &["x"], 9))
+            .structure(TABLE, 2, &table("first", 1, &["y"], 8))
+            .structure(END, 0, &[])
+            .bytes();
+
+        let mut tables = Tables::default();
+        crate::parse(&mut std::io::Cursor::new(file), &mut tables)?;
+        let found: Vec<(&str, usize, &[String])> = tables
+            .tables
+            .iter()
+            .map(|t| (t.name.as_str(), t.columns.len(), t.missing_columns()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("wrong", 1, &["b".to_string(), "c".to_string()][..]),
+                ("later", 0, &["x".to_string()][..]),
+                ("first", 0, &["y".to_string()][..]),
+            ]
+        );
+        Ok(())
+    }
+}
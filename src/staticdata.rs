@@ -0,0 +1,123 @@
+use crate::gps::GpsTime;
+use crate::handler::Handler;
+use crate::structures::*;
+
+/// StaticDataStore collects static data, such as calibrations, and finds the
+/// version valid at a GPS time.
+#[derive(Debug, Default)]
+pub struct StaticDataStore {
+    data: Vec<StaticData>,
+}
+
+impl StaticDataStore {
+    pub fn new() -> Self {
+        StaticDataStore { data: Vec::new() }
+    }
+
+    /// insert adds s unless the same name, version and detector is already stored.
+    pub fn insert(&mut self, s: StaticData) {
+        let exists = self.data.iter().any(|d| {
+            d.name == s.name
+                && d.version == s.version
+                && d.gps_time_start_s == s.gps_time_start_s
+                && detector_prefix(d) == detector_prefix(&s)
+        });
+        if !exists {
+            self.data.push(s);
+        }
+    }
+
+    pub fn all(&self) -> &[StaticData] {
+        &self.data
+    }
+
+    /// versions returns every stored version of name for the detector, in
+    /// increasing version order.  detector is a prefix such as `H1`; an empty
+    /// detector matches static data that has none.
+    pub fn versions(&self, detector: &str, name: &str) -> Vec<&StaticData> {
+        let mut v: Vec<&StaticData> = self
+            .data
+            .iter()
+            .filter(|d| d.name == name && detector_prefix(d) == detector)
+            .collect();
+        v.sort_by_key(|d| (d.version, d.gps_time_start_s));
+        v
+    }
+
+    /// valid_at returns the highest version of name for the detector whose
+    /// validity window contains gps.
+    pub fn valid_at(&self, detector: &str, name: &str, gps: GpsTime) -> Option<&StaticData> {
+        self.versions(detector, name)
+            .into_iter()
+            .rev()
+            .find(|d| d.is_valid_at(gps))
+    }
+}
+
+fn detector_prefix(s: &StaticData) -> String {
+    s.detector.as_ref().map(|d| d.prefix()).unwrap_or_default()
+}
+
+impl Handler for StaticDataStore {
+    fn static_data(&mut self) -> Option<fn(&mut Self, data: StaticData)> {
+        Some(StaticDataStore::insert)
+    }
+}
+
+#[cfg(test)]
+mod staticdata_tests {
+    use super::*;
+
+    fn detector(prefix: &str) -> Detector {
+        let p = prefix.as_bytes();
+        Detector {
+            name: String::new(),
+            prefix: [p[0] as i8, p[1] as i8],
+            longitude_radians: 0.0,
+            latitude_radians: 0.0,
+            elevation_meters: 0.0,
+            arm_x_azimuth_radians: 0.0,
+            arm_y_azimuth_radians: 0.0,
+            arm_x_altitude_radians: 0.0,
+            arm_y_altitude_radians: 0.0,
+            arm_x_midpoint_meters: 0.0,
+            arm_y_midpoint_meters: 0.0,
+            local_time_utc_offset_s: 0,
+        }
+    }
+
+    fn cal(prefix: &str, version: u32, start: u32, end: u32) -> StaticData {
+        StaticData {
+            name: "CAL".to_string(),
+            comment: String::new(),
+            representation: String::new(),
+            gps_time_start_s: start,
+            gps_time_end_s: end,
+            version,
+            detector: Some(detector(prefix)),
+            data: None,
+        }
+    }
+
+    #[test]
+    fn test_valid_at() {
+        let mut store = StaticDataStore::new();
+        store.insert(cal("H1", 1, 1000, 2000));
+        store.insert(cal("H1", 2, 1500, 0));
+        store.insert(cal("L1", 3, 1000, 0));
+        store.insert(cal("H1", 1, 1000, 2000));
+        assert_eq!(store.all().len(), 3);
+
+        let at = |t| {
+            store
+                .valid_at("H1", "CAL", GpsTime::new(t, 0))
+                .map(|s| s.version)
+        };
+        assert_eq!(at(999), None);
+        assert_eq!(at(1200), Some(1));
+        assert_eq!(at(1600), Some(2));
+        assert_eq!(at(5000), Some(2));
+        assert_eq!(store.versions("L1", "CAL").len(), 1);
+        assert!(store.valid_at("V1", "CAL", GpsTime::new(1200, 0)).is_none());
+    }
+}
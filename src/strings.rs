@@ -22,6 +22,20 @@ pub(crate) fn string<R: Read>(header: &Header, reader: &mut R) -> std::io::Resul
     }
 }
 
+/// strings decodes the data of a string vector; each element is encoded like a GWF string.
+pub(crate) fn strings(mut buf: &[u8], endian: Endian) -> Vec<String> {
+    let mut v = Vec::new();
+    while buf.len() >= core::mem::size_of::<u16>() {
+        let len = u16::from_bytes(&buf[..2], endian) as usize;
+        let end = (2 + len).min(buf.len());
+        let s = &buf[2..end];
+        let s = s.split(|b| *b == 0).next().unwrap_or(s);
+        v.push(String::from_utf8_lossy(s).to_string());
+        buf = &buf[end..];
+    }
+    v
+}
+
 #[cfg(test)]
 mod string_tests {
     use super::*;
@@ -56,4 +70,13 @@ mod string_tests {
         assert_eq!(buf.len(), 3941577);
         Ok(())
     }
+
+    #[test]
+    fn test_strings() {
+        let buf = [3, 0, b'a', b'b', 0, 1, 0, 0, 2, 0, b'c', 0];
+        assert_eq!(
+            strings(&buf, Endian::Little),
+            vec!["ab".to_string(), String::new(), "c".to_string()]
+        );
+    }
 }
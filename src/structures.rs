@@ -1,15 +1,22 @@
-use std::{io::SeekFrom, str::FromStr};
+pub use crate::decompress::Scheme;
+use crate::gps::GpsTime;
+use std::{fmt, io::SeekFrom, ops::Range, str::FromStr};
 use strum_macros::EnumString;
 
 #[derive(Debug, PartialEq, Default)]
 pub(crate) struct Common {
-    length: u64, // Byte length of this structure, including byte count of this variable
-    class: u8,   // Structure class for this particular structure.
+    length: u64,   // Byte length of this structure, including byte count of this variable
+    class: u8,     // Structure class for this particular structure.
+    instance: u32, // Instance of this class; pointers refer to structures by class and instance.
 }
 
 impl Common {
-    pub fn new(length: u64, class: u8) -> Self {
-        Common { length, class }
+    pub fn new(length: u64, class: u8, instance: u32) -> Self {
+        Common {
+            length,
+            class,
+            instance,
+        }
     }
 
     #[inline]
@@ -31,22 +38,54 @@ impl Common {
     pub fn class(&self) -> u8 {
         self.class
     }
+
+    #[inline]
+    pub fn instance(&self) -> u32 {
+        self.instance
+    }
 }
 
-#[derive(Debug, PartialEq)]
+/// Reference is a pointer from one structure to another.
+/// The class is the file's dictionary id of the structure pointed to.
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone, Default)]
+pub(crate) struct Reference {
+    pub class: u16,
+    pub instance: u32,
+}
+
+impl Reference {
+    /// is_null is true when the pointer does not refer to any structure.
+    #[inline]
+    pub fn is_null(&self) -> bool {
+        self.class == 0
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FrameHeader {
     pub name: String,
     pub run: i32,
     pub frame: u32,
-    // TODO(goller): split these up like Appendix A
-    pub data_quality: u32,
+    pub data_quality: DataQuality,
     pub gps_start_time_s: u32,
     pub gps_residual_time_ns: u32,
     pub gps_leap_s: u16,
     pub frame_length_s: f64,
 }
 
-#[derive(Debug, PartialEq)]
+impl FrameHeader {
+    pub fn start_time(&self) -> GpsTime {
+        GpsTime::new(self.gps_start_time_s, self.gps_residual_time_ns)
+    }
+
+    pub fn end_time(&self) -> GpsTime {
+        self.start_time() + self.frame_length_s
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ADC {
     pub name: String,
     pub comment: String,
@@ -60,13 +99,134 @@ pub struct ADC {
     pub time_offset_s: f64,
     pub f_shift: f64,
     pub phase: f32,
-    pub data_valid: bool,
+    pub data_valid: DataValid,
 }
 
-#[derive(Debug, PartialEq)]
+/// DataQuality is the frame's dataQuality word.  Appendix A of the
+/// specification assigns two bits to each detector.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DataQuality(pub u32);
+
+/// DATA_QUALITY_BITS lists the Appendix A detector prefixes and the lowest of their two bits.
+pub const DATA_QUALITY_BITS: [(&str, u32); 13] = [
+    ("T1", 0),  // TAMA 300
+    ("V0", 2),  // Virgo CITF
+    ("V1", 4),  // Virgo
+    ("G1", 6),  // GEO 600
+    ("H2", 8),  // LHO 2k
+    ("H1", 10), // LHO 4k
+    ("L1", 12), // LLO 4k
+    ("C1", 14), // CIT 40
+    ("A1", 16), // ALLEGRO
+    ("O1", 18), // AURIGA
+    ("E1", 20), // EXPLORER
+    ("B1", 22), // NIOBE
+    ("N1", 24), // NAUTILUS
+];
+
+impl DataQuality {
+    /// detector returns the two data quality bits of the detector with prefix,
+    /// or None if the prefix has no Appendix A assignment.
+    pub fn detector(&self, prefix: &str) -> Option<u8> {
+        DATA_QUALITY_BITS
+            .iter()
+            .find(|(p, _)| *p == prefix)
+            .map(|(_, bit)| ((self.0 >> bit) & 0b11) as u8)
+    }
+
+    /// flagged returns the prefixes and bits of every detector with a bit set.
+    pub fn flagged(&self) -> Vec<(&'static str, u8)> {
+        DATA_QUALITY_BITS
+            .iter()
+            .map(|(p, bit)| (*p, ((self.0 >> bit) & 0b11) as u8))
+            .filter(|(_, bits)| *bits != 0)
+            .collect()
+    }
+
+    #[inline]
+    pub fn is_set(&self, bit: u32) -> bool {
+        bit < 32 && self.0 & (1 << bit) != 0
+    }
+}
+
+/// DataValid is the ADC dataValid word.  Zero means the data are valid;
+/// otherwise each bit flags one reason the data are suspect.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DataValid(pub u16);
+
+impl DataValid {
+    pub const NOT_A_NUMBER: DataValid = DataValid(1 << 0);
+    pub const INFINITE: DataValid = DataValid(1 << 1);
+    pub const OUT_OF_RANGE: DataValid = DataValid(1 << 2);
+    pub const DENORMALIZED: DataValid = DataValid(1 << 3);
+
+    #[inline]
+    pub fn is_valid(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// contains is true if every bit of flags is set.
+    #[inline]
+    pub fn contains(&self, flags: DataValid) -> bool {
+        self.0 & flags.0 == flags.0
+    }
+
+    /// other returns the bits that have no named flag.
+    #[inline]
+    pub fn other(&self) -> u16 {
+        let known = DataValid::NOT_A_NUMBER
+            | DataValid::INFINITE
+            | DataValid::OUT_OF_RANGE
+            | DataValid::DENORMALIZED;
+        self.0 & !known.0
+    }
+}
+
+impl std::ops::BitOr for DataValid {
+    type Output = DataValid;
+    fn bitor(self, rhs: DataValid) -> DataValid {
+        DataValid(self.0 | rhs.0)
+    }
+}
+
+impl ADC {
+    /// calibrate converts the ADC's raw counts into physical units as `slope * (x + bias)`.
+    pub fn calibrate(&self, data: Vector) -> std::io::Result<Calibrated> {
+        let slope = self.slope as f64;
+        let bias = self.bias as f64;
+        match data.into_f64() {
+            Some(mut samples) => {
+                for x in samples.iter_mut() {
+                    *x = slope * (*x + bias);
+                }
+                Ok(Calibrated {
+                    units: self.units.clone(),
+                    samples,
+                })
+            }
+            None => Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("{}: only real numbers can be calibrated", self.name),
+            )),
+        }
+    }
+}
+
+/// Calibrated are ADC samples in physical units.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Calibrated {
+    pub units: Option<String>,
+    pub samples: Vec<f64>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Detector {
-    pub name: String,    // TODO(this should be an enum)
-    pub prefix: [i8; 2], // impl Detector fn prefix
+    pub name: String,    // see Detector::observatory
+    pub prefix: [i8; 2], // see Detector::prefix
     pub longitude_radians: f64,
     pub latitude_radians: f64,
     pub elevation_meters: f32,
@@ -79,7 +239,8 @@ pub struct Detector {
     pub local_time_utc_offset_s: i32,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Event {
     pub name: String,
     pub comment: String,
@@ -95,20 +256,35 @@ pub struct Event {
     pub parameters: Vec<EventParameter>,
 }
 
-#[derive(Debug, PartialEq)]
+impl Event {
+    pub fn time(&self) -> GpsTime {
+        GpsTime::new(self.gps_time_s, self.gps_residual_time_ns)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EventParameter {
     pub value: f64,
     pub name: String,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct History {
     pub name: String,
     pub gps_time_s: u32,
     pub comment: String,
 }
 
-#[derive(Debug, PartialEq)]
+impl History {
+    pub fn time(&self) -> GpsTime {
+        GpsTime::new(self.gps_time_s, 0)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Message {
     pub alarm: String,
     pub message: String,
@@ -117,12 +293,19 @@ pub struct Message {
     pub gps_residual_time_ns: u32,
 }
 
-#[derive(Debug, PartialEq)]
+impl Message {
+    pub fn time(&self) -> GpsTime {
+        GpsTime::new(self.gps_time_s, self.gps_residual_time_ns)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PostProcessed {
     pub name: String,
     pub comment: String,
-    pub data_type: u16, // TODO(goller): turn this into an enum
-    pub sub_type: u16,
+    pub data_type: DataType,
+    pub sub_type: SubType,
     pub time_offset_s: f64,
     pub time_range_s: f64,
     pub f_shift: f64,
@@ -132,18 +315,113 @@ pub struct PostProcessed {
     pub auxiliary_parameters: Vec<AuxiliaryParameter>,
 }
 
-#[derive(Debug, PartialEq)]
+/// DataType is the kind of product stored in a PostProcessed structure.
+/// Unsupported will return the value reported in the file.
+#[derive(Debug, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DataType {
+    Unknown,
+    TimeSeries,
+    FrequencySeries,
+    /// Other1D is a one dimensional series with an x axis other than time or frequency.
+    Other1D,
+    TimeFrequency,
+    Wavelets,
+    MultiDimensional,
+    Unsupported(u16),
+}
+
+impl From<u16> for DataType {
+    fn from(t: u16) -> DataType {
+        match t {
+            0 => DataType::Unknown,
+            1 => DataType::TimeSeries,
+            2 => DataType::FrequencySeries,
+            3 => DataType::Other1D,
+            4 => DataType::TimeFrequency,
+            5 => DataType::Wavelets,
+            6 => DataType::MultiDimensional,
+            _ => DataType::Unsupported(t),
+        }
+    }
+}
+
+impl From<DataType> for u16 {
+    fn from(t: DataType) -> u16 {
+        match t {
+            DataType::Unknown => 0,
+            DataType::TimeSeries => 1,
+            DataType::FrequencySeries => 2,
+            DataType::Other1D => 3,
+            DataType::TimeFrequency => 4,
+            DataType::Wavelets => 5,
+            DataType::MultiDimensional => 6,
+            DataType::Unsupported(t) => t,
+        }
+    }
+}
+
+/// SubType refines a frequency series DataType.
+/// Unsupported will return the value reported in the file.
+#[derive(Debug, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SubType {
+    Unknown,
+    /// DFT is a discrete Fourier transform.
+    DFT,
+    AmplitudeSpectralDensity,
+    PowerSpectralDensity,
+    CrossSpectralDensity,
+    Coherence,
+    TransferFunction,
+    Unsupported(u16),
+}
+
+impl From<u16> for SubType {
+    fn from(t: u16) -> SubType {
+        match t {
+            0 => SubType::Unknown,
+            1 => SubType::DFT,
+            2 => SubType::AmplitudeSpectralDensity,
+            3 => SubType::PowerSpectralDensity,
+            4 => SubType::CrossSpectralDensity,
+            5 => SubType::Coherence,
+            6 => SubType::TransferFunction,
+            _ => SubType::Unsupported(t),
+        }
+    }
+}
+
+impl From<SubType> for u16 {
+    fn from(t: SubType) -> u16 {
+        match t {
+            SubType::Unknown => 0,
+            SubType::DFT => 1,
+            SubType::AmplitudeSpectralDensity => 2,
+            SubType::PowerSpectralDensity => 3,
+            SubType::CrossSpectralDensity => 4,
+            SubType::Coherence => 5,
+            SubType::TransferFunction => 6,
+            SubType::Unsupported(t) => t,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AuxiliaryParameter {
     pub value: f64,
     pub name: String,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RawData {
     pub name: String,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Serial {
     pub name: String,
     pub gps_time_s: u32,
@@ -152,7 +430,14 @@ pub struct Serial {
     pub data: String,
 }
 
-#[derive(Debug, PartialEq)]
+impl Serial {
+    pub fn time(&self) -> GpsTime {
+        GpsTime::new(self.gps_time_s, self.gps_residual_time_ns)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Simulation {
     pub name: String,
     pub comment: String,
@@ -162,7 +447,8 @@ pub struct Simulation {
     pub phase: f32,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SimulatedEvent {
     pub name: String,
     pub comment: String,
@@ -175,7 +461,14 @@ pub struct SimulatedEvent {
     pub parameters: Vec<EventParameter>,
 }
 
-#[derive(Debug, PartialEq)]
+impl SimulatedEvent {
+    pub fn time(&self) -> GpsTime {
+        GpsTime::new(self.gps_event_max_time_s, self.gps_residual_time_ns)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct StaticData {
     pub name: String,
     pub comment: String,
@@ -183,9 +476,56 @@ pub struct StaticData {
     pub gps_time_start_s: u32,
     pub gps_time_end_s: u32,
     pub version: u32,
+    /// detector is the detector this data describes, if any.
+    pub detector: Option<Detector>,
+    pub data: Option<Vector>,
 }
 
-#[derive(Debug, PartialEq)]
+impl StaticData {
+    pub fn start_time(&self) -> GpsTime {
+        GpsTime::new(self.gps_time_start_s, 0)
+    }
+
+    /// end_time is None when the data has no end.
+    pub fn end_time(&self) -> Option<GpsTime> {
+        match self.gps_time_end_s {
+            0 => None,
+            s => Some(GpsTime::new(s, 0)),
+        }
+    }
+
+    /// is_valid_at is true if gps is within [start, end).
+    pub fn is_valid_at(&self, gps: GpsTime) -> bool {
+        self.start_time() <= gps && self.end_time().is_none_or(|end| gps < end)
+    }
+}
+
+/// EndOfFrame closes the frame with the same run and frame number.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EndOfFrame {
+    pub run: i32,
+    pub frame: u32,
+    pub gps_time_s: u32,
+    pub gps_residual_time_ns: u32,
+}
+
+/// EndOfFile closes the file.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EndOfFile {
+    pub num_frames: u32,
+    pub num_bytes: u64,
+    /// seek_toc is the distance back from the end of the file to the table
+    /// of contents, or 0 without one.
+    pub seek_toc: u64,
+    pub header_checksum: u32,
+    pub checksum: u32,
+    pub file_checksum: u32,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Summary {
     pub name: String,
     pub comment: String,
@@ -194,21 +534,98 @@ pub struct Summary {
     pub gps_residual_time_ns: u32,
 }
 
-#[derive(Debug, PartialEq)]
+impl Summary {
+    pub fn time(&self) -> GpsTime {
+        GpsTime::new(self.gps_time_s, self.gps_residual_time_ns)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Table {
     pub name: String,
     pub comment: String,
     pub num_rows: u32,
-    pub column_names: Vec<String>, // would be nice to have a 2-d table;
+    pub column_names: Vec<String>,
+    /// columns are in the same order as column_names and each has num_rows
+    /// elements.  A column whose vector is not in the file or has another
+    /// number of elements is left off the end with the columns after it.
+    pub columns: Vec<Vector>,
 }
 
-#[derive(Debug, PartialEq)]
+impl Table {
+    /// missing_columns names the columns left off the end of columns.
+    pub fn missing_columns(&self) -> &[String] {
+        self.column_names
+            .get(self.columns.len()..)
+            .unwrap_or_default()
+    }
+
+    /// column looks up a column by name.
+    pub fn column(&self, name: &str) -> Option<&Vector> {
+        let i = self.column_names.iter().position(|n| n == name)?;
+        self.columns.get(i)
+    }
+
+    /// row returns the values of every column at index i.
+    pub fn row(&self, i: usize) -> Option<Vec<Value<'_>>> {
+        if i >= self.num_rows as usize {
+            return None;
+        }
+        self.columns.iter().map(|c| c.value(i)).collect()
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = Vec<Value<'_>>> + '_ {
+        (0..self.num_rows as usize).map_while(move |i| self.row(i))
+    }
+}
+
+/// Value is a single element of a Vector.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum Value<'a> {
+    I8(i8),
+    U8(u8),
+    I16(i16),
+    U16(u16),
+    I32(i32),
+    U32(u32),
+    U64(u64),
+    I64(i64),
+    F32(f32),
+    F64(f64),
+    String(&'a str),
+    Complex(&'a Complex),
+}
+
+impl fmt::Display for Value<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::I8(v) => write!(f, "{}", v),
+            Value::U8(v) => write!(f, "{}", v),
+            Value::I16(v) => write!(f, "{}", v),
+            Value::U16(v) => write!(f, "{}", v),
+            Value::I32(v) => write!(f, "{}", v),
+            Value::U32(v) => write!(f, "{}", v),
+            Value::U64(v) => write!(f, "{}", v),
+            Value::I64(v) => write!(f, "{}", v),
+            Value::F32(v) => write!(f, "{}", v),
+            Value::F64(v) => write!(f, "{}", v),
+            Value::String(v) => write!(f, "{}", v),
+            Value::Complex(v) => write!(f, "{}{:+}i", v.real, v.imaginary),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Complex {
     pub real: f64,
     pub imaginary: f64,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct VectorInfo {
     pub name: String,
     pub num_samples: u64,
@@ -218,9 +635,12 @@ pub struct VectorInfo {
     pub x_origins: Vec<f64>,
     pub unit_x_scale_factors: Vec<String>,
     pub unit_y: String,
+    /// compression is the FrVect compression scheme the data was stored with.
+    pub compression: u16,
 }
 
-#[derive(Debug, PartialEq)]
+/// Vector serializes as `{"type": "f64", "info": {...}, "data": [...]}`.
+#[derive(Debug, PartialEq, Clone)]
 pub enum Vector {
     I8(VectorInfo, Vec<i8>),
     U8(VectorInfo, Vec<u8>),
@@ -238,8 +658,280 @@ pub enum Vector {
     Complexes(VectorInfo, Vec<Complex>),
 }
 
+impl Vector {
+    pub fn info(&self) -> &VectorInfo {
+        match self {
+            Vector::I8(info, _) => info,
+            Vector::U8(info, _) => info,
+            Vector::I16(info, _) => info,
+            Vector::U16(info, _) => info,
+            Vector::I32(info, _) => info,
+            Vector::U32(info, _) => info,
+            Vector::U64(info, _) => info,
+            Vector::I64(info, _) => info,
+            Vector::F32(info, _) => info,
+            Vector::F64(info, _) => info,
+            Vector::Strings(info, _) => info,
+            Vector::Complexes(info, _) => info,
+        }
+    }
+
+    pub fn info_mut(&mut self) -> &mut VectorInfo {
+        match self {
+            Vector::I8(info, _) => info,
+            Vector::U8(info, _) => info,
+            Vector::I16(info, _) => info,
+            Vector::U16(info, _) => info,
+            Vector::I32(info, _) => info,
+            Vector::U32(info, _) => info,
+            Vector::U64(info, _) => info,
+            Vector::I64(info, _) => info,
+            Vector::F32(info, _) => info,
+            Vector::F64(info, _) => info,
+            Vector::Strings(info, _) => info,
+            Vector::Complexes(info, _) => info,
+        }
+    }
+
+    /// type_name is the name of the sample type, e.g. `i16` or `complex`.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Vector::I8(_, _) => "i8",
+            Vector::U8(_, _) => "u8",
+            Vector::I16(_, _) => "i16",
+            Vector::U16(_, _) => "u16",
+            Vector::I32(_, _) => "i32",
+            Vector::U32(_, _) => "u32",
+            Vector::U64(_, _) => "u64",
+            Vector::I64(_, _) => "i64",
+            Vector::F32(_, _) => "f32",
+            Vector::F64(_, _) => "f64",
+            Vector::Strings(_, _) => "string",
+            Vector::Complexes(_, _) => "complex",
+        }
+    }
+
+    /// len is the number of decoded samples.
+    pub fn len(&self) -> usize {
+        match self {
+            Vector::I8(_, v) => v.len(),
+            Vector::U8(_, v) => v.len(),
+            Vector::I16(_, v) => v.len(),
+            Vector::U16(_, v) => v.len(),
+            Vector::I32(_, v) => v.len(),
+            Vector::U32(_, v) => v.len(),
+            Vector::U64(_, v) => v.len(),
+            Vector::I64(_, v) => v.len(),
+            Vector::F32(_, v) => v.len(),
+            Vector::F64(_, v) => v.len(),
+            Vector::Strings(_, v) => v.len(),
+            Vector::Complexes(_, v) => v.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// value returns the element at index i.
+    pub fn value(&self, i: usize) -> Option<Value<'_>> {
+        match self {
+            Vector::I8(_, v) => v.get(i).map(|x| Value::I8(*x)),
+            Vector::U8(_, v) => v.get(i).map(|x| Value::U8(*x)),
+            Vector::I16(_, v) => v.get(i).map(|x| Value::I16(*x)),
+            Vector::U16(_, v) => v.get(i).map(|x| Value::U16(*x)),
+            Vector::I32(_, v) => v.get(i).map(|x| Value::I32(*x)),
+            Vector::U32(_, v) => v.get(i).map(|x| Value::U32(*x)),
+            Vector::U64(_, v) => v.get(i).map(|x| Value::U64(*x)),
+            Vector::I64(_, v) => v.get(i).map(|x| Value::I64(*x)),
+            Vector::F32(_, v) => v.get(i).map(|x| Value::F32(*x)),
+            Vector::F64(_, v) => v.get(i).map(|x| Value::F64(*x)),
+            Vector::Strings(_, v) => v.get(i).map(|x| Value::String(x)),
+            Vector::Complexes(_, v) => v.get(i).map(Value::Complex),
+        }
+    }
+
+    /// slice copies the samples in range of a one dimensional vector.
+    pub fn slice(&self, range: Range<usize>) -> Vector {
+        macro_rules! slice {
+            ($($variant:ident),*) => {
+                match self {
+                    $(Vector::$variant(info, v) => Vector::$variant(info.clone(), v[range].to_vec()),)*
+                }
+            };
+        }
+        let mut v = slice!(I8, U8, I16, U16, I32, U32, U64, I64, F32, F64, Strings, Complexes);
+        v.set_len();
+        v
+    }
+
+    /// append moves the samples of other onto the end of a one dimensional
+    /// vector.  Vectors of different types are not joined; other is returned.
+    pub fn append(&mut self, other: Vector) -> std::result::Result<(), Vector> {
+        macro_rules! append {
+            ($($variant:ident),*) => {
+                match (&mut *self, other) {
+                    $((Vector::$variant(_, a), Vector::$variant(_, mut b)) => a.append(&mut b),)*
+                    (_, other) => return Err(other),
+                }
+            };
+        }
+        append!(I8, U8, I16, U16, I32, U32, U64, I64, F32, F64, Strings, Complexes);
+        self.set_len();
+        Ok(())
+    }
+
+    fn set_len(&mut self) {
+        let len = self.len() as u64;
+        let info = self.info_mut();
+        info.num_samples = len;
+        if info.dimension_lengths.len() == 1 {
+            info.dimension_lengths[0] = len;
+        }
+    }
+
+    /// into_f64 converts real numeric data into f64.
+    /// Strings and complex numbers return None.
+    pub fn into_f64(self) -> Option<Vec<f64>> {
+        match self {
+            Vector::I8(_, v) => Some(v.into_iter().map(f64::from).collect()),
+            Vector::U8(_, v) => Some(v.into_iter().map(f64::from).collect()),
+            Vector::I16(_, v) => Some(v.into_iter().map(f64::from).collect()),
+            Vector::U16(_, v) => Some(v.into_iter().map(f64::from).collect()),
+            Vector::I32(_, v) => Some(v.into_iter().map(f64::from).collect()),
+            Vector::U32(_, v) => Some(v.into_iter().map(f64::from).collect()),
+            Vector::U64(_, v) => Some(v.into_iter().map(|x| x as f64).collect()),
+            Vector::I64(_, v) => Some(v.into_iter().map(|x| x as f64).collect()),
+            Vector::F32(_, v) => Some(v.into_iter().map(f64::from).collect()),
+            Vector::F64(_, v) => Some(v),
+            Vector::Strings(_, _) | Vector::Complexes(_, _) => None,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+mod vector_serde {
+    use super::{Complex, Vector, VectorInfo};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    macro_rules! vector_repr (( $($variant:ident($t:ty) = $name:literal),* ) => {
+        #[derive(Serialize)]
+        #[serde(tag = "type")]
+        enum VectorRef<'a> {
+            $(
+                #[serde(rename = $name)]
+                $variant { info: &'a VectorInfo, data: &'a [$t] },
+            )*
+        }
+
+        #[derive(Deserialize)]
+        #[serde(tag = "type")]
+        enum VectorOwned {
+            $(
+                #[serde(rename = $name)]
+                $variant { info: VectorInfo, data: Vec<$t> },
+            )*
+        }
+
+        impl Serialize for Vector {
+            fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+                match self {
+                    $(Vector::$variant(info, data) => VectorRef::$variant { info, data }.serialize(s),)*
+                }
+            }
+        }
+
+        impl<'de> Deserialize<'de> for Vector {
+            fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Vector, D::Error> {
+                Ok(match VectorOwned::deserialize(d)? {
+                    $(VectorOwned::$variant { info, data } => Vector::$variant(info, data),)*
+                })
+            }
+        }
+    });
+
+    vector_repr!(
+        I8(i8) = "i8",
+        U8(u8) = "u8",
+        I16(i16) = "i16",
+        U16(u16) = "u16",
+        I32(i32) = "i32",
+        U32(u32) = "u32",
+        U64(u64) = "u64",
+        I64(i64) = "i64",
+        F32(f32) = "f32",
+        F64(f64) = "f64",
+        Strings(String) = "string",
+        Complexes(Complex) = "complex"
+    );
+}
+
+/// Channel is the metadata structure that owns a data vector.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Channel {
+    ADC(ADC),
+    PostProcessed(PostProcessed),
+    Simulation(Simulation),
+    Serial(Serial),
+}
+
+impl Channel {
+    pub fn name(&self) -> &str {
+        match self {
+            Channel::ADC(c) => &c.name,
+            Channel::PostProcessed(c) => &c.name,
+            Channel::Simulation(c) => &c.name,
+            Channel::Serial(c) => &c.name,
+        }
+    }
+
+    pub fn kind(&self) -> ChannelKind {
+        match self {
+            Channel::ADC(_) => ChannelKind::ADC,
+            Channel::PostProcessed(_) => ChannelKind::PostProcessed,
+            Channel::Simulation(_) => ChannelKind::Simulation,
+            Channel::Serial(_) => ChannelKind::Serial,
+        }
+    }
+
+    /// start_time is the frame start plus the channel's time offset.
+    /// Serial data carries its own absolute time instead.
+    pub fn start_time(&self, frame: &FrameHeader) -> GpsTime {
+        match self {
+            Channel::ADC(c) => frame.start_time() + c.time_offset_s,
+            Channel::PostProcessed(c) => frame.start_time() + c.time_offset_s,
+            Channel::Simulation(c) => frame.start_time() + c.time_offset_s,
+            Channel::Serial(c) => c.time(),
+        }
+    }
+}
+
+/// ChannelKind is the structure type of a channel.  It displays as the
+/// suffix of the structure name, e.g. `Proc` for FrProcData.
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ChannelKind {
+    ADC,
+    PostProcessed,
+    Simulation,
+    Serial,
+}
+
+impl fmt::Display for ChannelKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChannelKind::ADC => write!(f, "ADC"),
+            ChannelKind::PostProcessed => write!(f, "Proc"),
+            ChannelKind::Simulation => write!(f, "Sim"),
+            ChannelKind::Serial => write!(f, "Ser"),
+        }
+    }
+}
+
 /// Version is the version of the GWF file.
 #[derive(Debug, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Version {
     pub major: Major,
     pub minor: Minor,
@@ -258,6 +950,11 @@ impl From<[u8; 2]> for Version {
 /// Unsupported means that this software does not know how to parse the file.
 /// Unsupported will return the value reported in the file.
 #[derive(Debug, PartialEq, Copy, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(into = "u8", from = "u8")
+)]
 pub enum Major {
     Release4,
     Release6,
@@ -276,8 +973,24 @@ impl From<u8> for Major {
     }
 }
 
+impl From<Major> for u8 {
+    fn from(m: Major) -> u8 {
+        match m {
+            Major::Release4 => 4,
+            Major::Release6 => 6,
+            Major::Release8 => 8,
+            Major::Unsupported(m) => m,
+        }
+    }
+}
+
 /// Minor is the Frame library minor version used to write this frame file.
 #[derive(Debug, PartialEq, Copy, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(into = "u8", from = "u8")
+)]
 pub enum Minor {
     Minor(u8),
     /// Beta represents an unreleased or provisional version.
@@ -293,7 +1006,17 @@ impl From<u8> for Minor {
     }
 }
 
+impl From<Minor> for u8 {
+    fn from(m: Minor) -> u8 {
+        match m {
+            Minor::Minor(m) => m,
+            Minor::Beta => 255,
+        }
+    }
+}
+
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 /// Checksum is the checksum type recorded in the end-of-file structure.
 pub enum Checksum {
     SumNone,
@@ -318,6 +1041,7 @@ impl Default for Checksum {
 
 /// Endian describes the endianness of the file.
 #[derive(Debug, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Endian {
     Big,
     Little,
@@ -340,7 +1064,12 @@ impl Default for Endian {
 }
 
 /// Library indicates the software that produced the GWF file.
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Copy, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(into = "u8", from = "u8")
+)]
 pub enum Library {
     /// Library::LibUnknown indicates the GWF file was not producted by the C or CPP frame libraries.
     /// It's value is the byte from the file itself.
@@ -361,6 +1090,16 @@ impl From<u8> for Library {
     }
 }
 
+impl From<Library> for u8 {
+    fn from(l: Library) -> u8 {
+        match l {
+            Library::LibUnknown(l) => l,
+            Library::LibFrameL => 1,
+            Library::LibFrameCPP => 2,
+        }
+    }
+}
+
 impl Default for Library {
     fn default() -> Self {
         Library::LibUnknown(0)
@@ -368,11 +1107,13 @@ impl Default for Library {
 }
 
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 /// Header is the header of a GWF file containing metadata, notably version, endianness, and checksums
 pub struct Header {
     pub version: Version,
     pub library: Library,
     pub endian: Endian,
+    #[cfg_attr(feature = "serde", serde(with = "data_model"))]
     pub machine_data_model: data_models::DataModel,
     pub checksum: Checksum,
 }
@@ -383,6 +1124,32 @@ impl Header {
     }
 }
 
+/// data_model serializes the machine data model by name, e.g. `LP64`.
+#[cfg(feature = "serde")]
+mod data_model {
+    use data_models::DataModel;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(m: &DataModel, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(&format!("{:?}", m))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<DataModel, D::Error> {
+        let name = String::deserialize(d)?;
+        Ok(match name.as_str() {
+            "IP16" => DataModel::IP16,
+            "IP16L32" => DataModel::IP16L32,
+            "LP32" => DataModel::LP32,
+            "ILP32" => DataModel::ILP32,
+            "LLP64" => DataModel::LLP64,
+            "LP64" => DataModel::LP64,
+            "ILP64" => DataModel::ILP64,
+            "SILP64" => DataModel::SILP64,
+            _ => DataModel::Unknown,
+        })
+    }
+}
+
 #[derive(EnumString, Copy, Clone, PartialEq)]
 #[repr(u8)]
 pub(crate) enum Structures {
@@ -470,13 +1237,179 @@ mod struct_tests {
 
     #[test]
     fn test_struct_length() {
-        let common = Common::new(78, 1);
+        let common = Common::new(78, 1, 0);
         assert_eq!(common.struct_length(), 64);
     }
 
     #[test]
     fn test_seek_past() {
-        let common = Common::new(78, 1);
+        let common = Common::new(78, 1, 0);
         assert_eq!(common.seek_past(), SeekFrom::Current(64));
     }
+
+    #[test]
+    fn test_data_quality() {
+        let dq = DataQuality(0b11 << 10 | 0b01 << 12);
+        assert_eq!(dq.detector("H1"), Some(3));
+        assert_eq!(dq.detector("L1"), Some(1));
+        assert_eq!(dq.detector("V1"), Some(0));
+        assert_eq!(dq.detector("XX"), None);
+        assert_eq!(dq.flagged(), vec![("H1", 3), ("L1", 1)]);
+        assert!(dq.is_set(11));
+        assert!(!dq.is_set(32));
+    }
+
+    #[test]
+    fn test_data_valid() {
+        assert!(DataValid(0).is_valid());
+
+        let valid = DataValid(0b1_0011);
+        assert!(!valid.is_valid());
+        assert!(valid.contains(DataValid::NOT_A_NUMBER | DataValid::INFINITE));
+        assert!(!valid.contains(DataValid::OUT_OF_RANGE));
+        assert_eq!(valid.other(), 0b1_0000);
+    }
+
+    #[test]
+    fn test_post_processed_types() {
+        assert_eq!(DataType::from(1), DataType::TimeSeries);
+        assert_eq!(DataType::from(4), DataType::TimeFrequency);
+        assert_eq!(DataType::from(9), DataType::Unsupported(9));
+        assert_eq!(SubType::from(3), SubType::PowerSpectralDensity);
+        assert_eq!(SubType::from(6), SubType::TransferFunction);
+        assert_eq!(SubType::from(7), SubType::Unsupported(7));
+        assert_eq!(u16::from(DataType::Unsupported(9)), 9);
+        assert_eq!(u16::from(SubType::Coherence), 5);
+    }
+
+    #[test]
+    fn test_table() {
+        let info = |name: &str| VectorInfo {
+            name: name.to_string(),
+            num_samples: 2,
+            num_dimensions: 1,
+            dimension_lengths: vec![2],
+            sample_spacing: vec![1.0],
+            x_origins: vec![0.0],
+            unit_x_scale_factors: vec![String::new()],
+            unit_y: String::new(),
+            compression: 0,
+        };
+        let table = Table {
+            name: "triggers".to_string(),
+            comment: String::new(),
+            num_rows: 2,
+            column_names: vec!["ifo".to_string(), "snr".to_string()],
+            columns: vec![
+                Vector::Strings(info("ifo"), vec!["H1".to_string(), "L1".to_string()]),
+                Vector::F32(info("snr"), vec![8.5, 9.0]),
+            ],
+        };
+
+        assert_eq!(table.column("snr").map(|c| c.len()), Some(2));
+        assert!(table.column("mass").is_none());
+        assert!(table.missing_columns().is_empty());
+        assert_eq!(
+            table.row(1),
+            Some(vec![Value::String("L1"), Value::F32(9.0)])
+        );
+        assert!(table.row(2).is_none());
+
+        let rows: Vec<String> = table
+            .rows()
+            .map(|r| {
+                r.iter()
+                    .map(|v| v.to_string())
+                    .collect::<Vec<_>>()
+                    .join(",")
+            })
+            .collect();
+        assert_eq!(rows, vec!["H1,8.5", "L1,9"]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() -> Result<(), serde_json::Error> {
+        let info = VectorInfo {
+            name: "H1:TEST".to_string(),
+            num_samples: 2,
+            num_dimensions: 1,
+            dimension_lengths: vec![2],
+            sample_spacing: vec![0.5],
+            x_origins: vec![0.0],
+            unit_x_scale_factors: vec!["s".to_string()],
+            unit_y: "counts".to_string(),
+            compression: 0,
+        };
+        let v = Vector::I16(info, vec![1, -1]);
+        let json = serde_json::to_value(&v)?;
+        assert_eq!(json["type"], "i16");
+        assert_eq!(json["data"], serde_json::json!([1, -1]));
+        assert_eq!(serde_json::from_value::<Vector>(json)?, v);
+
+        let header = Header {
+            version: Version {
+                major: Major::Release8,
+                minor: Minor::Beta,
+            },
+            library: Library::LibFrameCPP,
+            endian: Endian::Little,
+            machine_data_model: data_models::DataModel::LP64,
+            checksum: Checksum::SumCRC,
+        };
+        let json = serde_json::to_value(&header)?;
+        assert_eq!(
+            json["version"],
+            serde_json::json!({"major": 8, "minor": 255})
+        );
+        assert_eq!(json["library"], 2);
+        assert_eq!(json["machine_data_model"], "LP64");
+        assert_eq!(serde_json::from_value::<Header>(json)?, header);
+        Ok(())
+    }
+
+    #[test]
+    fn test_calibrate() {
+        let adc = ADC {
+            name: "H1:TEST".to_string(),
+            comment: String::new(),
+            channel_group: 0,
+            channel_number: 0,
+            num_bits: 16,
+            bias: 1.0,
+            slope: 0.5,
+            units: Some("m".to_string()),
+            sample_rate: 16.0,
+            time_offset_s: 0.0,
+            f_shift: 0.0,
+            phase: 0.0,
+            data_valid: DataValid(0),
+        };
+        let info = VectorInfo {
+            name: "H1:TEST".to_string(),
+            num_samples: 3,
+            num_dimensions: 1,
+            dimension_lengths: vec![3],
+            sample_spacing: vec![0.0625],
+            x_origins: vec![0.0],
+            unit_x_scale_factors: vec!["s".to_string()],
+            unit_y: "counts".to_string(),
+            compression: 0,
+        };
+
+        let calibrated = adc
+            .calibrate(Vector::I16(info.clone(), vec![-1, 0, 3]))
+            .unwrap();
+        assert_eq!(
+            calibrated,
+            Calibrated {
+                units: Some("m".to_string()),
+                samples: vec![0.0, 0.5, 2.0],
+            }
+        );
+
+        assert!(adc
+            .calibrate(Vector::Strings(info, vec!["a".to_string()]))
+            .is_err());
+    }
 }
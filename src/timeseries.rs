@@ -0,0 +1,480 @@
+use crate::handler::Handler;
+use crate::structures::*;
+use std::collections::BTreeMap;
+use std::io::{Error, ErrorKind, Result};
+
+/// Samples are the values of a time series.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Samples {
+    Real(Vec<f64>),
+    Complex(Vec<Complex>),
+}
+
+impl Samples {
+    pub fn len(&self) -> usize {
+        match self {
+            Samples::Real(v) => v.len(),
+            Samples::Complex(v) => v.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// TimeSeries is a contiguous, regularly sampled channel.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TimeSeries {
+    pub name: String,
+    /// gps_start_s is the GPS time of the first sample.
+    pub gps_start_s: f64,
+    pub sample_rate: f64,
+    pub unit: String,
+    pub samples: Samples,
+}
+
+/// Discontinuity describes why two consecutive pieces of a channel were not joined.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Discontinuity {
+    /// Gap is missing data starting at gps_s.
+    Gap { gps_s: f64, duration_s: f64 },
+    /// Overlap is data starting at gps_s that was already covered.
+    Overlap { gps_s: f64, duration_s: f64 },
+    /// Mismatch is a change in sample rate, unit or sample type at gps_s.
+    Mismatch { gps_s: f64, reason: String },
+}
+
+impl TimeSeries {
+    /// new creates a time series from one frame's data vector.
+    /// The start time is the frame start plus the channel's time offset and the vector's origin.
+    pub fn new(frame: &FrameHeader, channel: &Channel, data: Vector) -> Result<TimeSeries> {
+        let info = data.info();
+        if info.num_dimensions != 1 || info.sample_spacing.is_empty() {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "{}: expected one dimension, got {}",
+                    channel.name(),
+                    info.num_dimensions
+                ),
+            ));
+        }
+
+        let rate = match channel {
+            Channel::ADC(c) => c.sample_rate,
+            Channel::PostProcessed(c) => {
+                if c.data_type != DataType::TimeSeries && c.data_type != DataType::Unknown {
+                    return Err(Error::new(
+                        ErrorKind::InvalidData,
+                        format!("{}: not a time series", c.name),
+                    ));
+                }
+                0.0
+            }
+            Channel::Simulation(c) => c.sample_rate,
+            Channel::Serial(c) => c.sample_rate,
+        };
+
+        let dx = info.sample_spacing[0];
+        let sample_rate = if dx > 0.0 { 1.0 / dx } else { rate };
+        if !(sample_rate.is_finite() && sample_rate > 0.0) {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("{}: invalid sample rate {}", channel.name(), sample_rate),
+            ));
+        }
+        let gps_start_s =
+            channel.start_time(frame).as_seconds() + info.x_origins.first().copied().unwrap_or(0.0);
+        let unit = info.unit_y.clone();
+
+        let samples = match data {
+            Vector::Complexes(_, v) => Samples::Complex(v),
+            Vector::Strings(_, _) => {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("{}: not numeric", channel.name()),
+                ))
+            }
+            // all other vectors are real numbers.
+            v => Samples::Real(v.into_f64().unwrap_or_default()),
+        };
+
+        Ok(TimeSeries {
+            name: channel.name().to_string(),
+            gps_start_s,
+            sample_rate,
+            unit,
+            samples,
+        })
+    }
+
+    /// calibrated creates a time series from one frame of an ADC's data in physical units.
+    pub fn calibrated(frame: &FrameHeader, adc: &ADC, data: Vector) -> Result<TimeSeries> {
+        let channel = Channel::ADC(adc.clone());
+        let info = data.info().clone();
+        let calibrated = adc.calibrate(data)?;
+        let mut ts = TimeSeries::new(frame, &channel, Vector::F64(info, calibrated.samples))?;
+        ts.unit = calibrated.units.unwrap_or_default();
+        Ok(ts)
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    pub fn duration_s(&self) -> f64 {
+        self.len() as f64 / self.sample_rate
+    }
+
+    /// gps_end_s is the GPS time just after the last sample.
+    pub fn gps_end_s(&self) -> f64 {
+        self.gps_start_s + self.duration_s()
+    }
+
+    /// window returns the samples in `[gps_start_s, gps_end_s)`.
+    pub fn window(&self, gps_start_s: f64, gps_end_s: f64) -> TimeSeries {
+        let index = |t: f64| {
+            let i = ((t - self.gps_start_s) * self.sample_rate - 1e-6).ceil();
+            i.max(0.0).min(self.len() as f64) as usize
+        };
+        let (i, j) = (index(gps_start_s), index(gps_end_s).max(index(gps_start_s)));
+        let samples = match &self.samples {
+            Samples::Real(v) => Samples::Real(v[i..j].to_vec()),
+            Samples::Complex(v) => Samples::Complex(v[i..j].to_vec()),
+        };
+        TimeSeries {
+            name: self.name.clone(),
+            gps_start_s: self.gps_start_s + i as f64 / self.sample_rate,
+            sample_rate: self.sample_rate,
+            unit: self.unit.clone(),
+            samples,
+        }
+    }
+
+    /// discontinuity checks whether next directly follows this series.
+    /// Start times within half a sample of the end are contiguous.
+    pub fn discontinuity(&self, next: &TimeSeries) -> Option<Discontinuity> {
+        let end = self.gps_end_s();
+        if (self.sample_rate - next.sample_rate).abs() > f64::EPSILON * self.sample_rate {
+            return Some(Discontinuity::Mismatch {
+                gps_s: next.gps_start_s,
+                reason: format!(
+                    "sample rate changed from {} to {}",
+                    self.sample_rate, next.sample_rate
+                ),
+            });
+        }
+        if self.unit != next.unit {
+            return Some(Discontinuity::Mismatch {
+                gps_s: next.gps_start_s,
+                reason: format!("unit changed from {:?} to {:?}", self.unit, next.unit),
+            });
+        }
+        match (&self.samples, &next.samples) {
+            (Samples::Real(_), Samples::Real(_)) | (Samples::Complex(_), Samples::Complex(_)) => {}
+            _ => {
+                return Some(Discontinuity::Mismatch {
+                    gps_s: next.gps_start_s,
+                    reason: "sample type changed".to_string(),
+                })
+            }
+        }
+
+        let delta = next.gps_start_s - end;
+        let tolerance = 0.5 / self.sample_rate;
+        if delta > tolerance {
+            Some(Discontinuity::Gap {
+                gps_s: end,
+                duration_s: delta,
+            })
+        } else if delta < -tolerance {
+            Some(Discontinuity::Overlap {
+                gps_s: next.gps_start_s,
+                duration_s: -delta,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// append joins next onto the end of this series.  If next does not
+    /// directly follow, nothing is joined and the discontinuity is returned.
+    pub fn append(&mut self, next: TimeSeries) -> std::result::Result<(), Discontinuity> {
+        if let Some(d) = self.discontinuity(&next) {
+            return Err(d);
+        }
+        match (&mut self.samples, next.samples) {
+            (Samples::Real(a), Samples::Real(mut b)) => a.append(&mut b),
+            (Samples::Complex(a), Samples::Complex(mut b)) => a.append(&mut b),
+            _ => unreachable!(),
+        }
+        Ok(())
+    }
+}
+
+/// Segments are the contiguous pieces of one channel and the discontinuities between them.
+#[derive(Debug, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Segments {
+    pub series: Vec<TimeSeries>,
+    pub discontinuities: Vec<Discontinuity>,
+}
+
+impl Segments {
+    fn push(&mut self, next: TimeSeries) {
+        if let Some(last) = self.series.last_mut() {
+            match last.discontinuity(&next) {
+                None => {
+                    // cannot fail as there is no discontinuity.
+                    let _ = last.append(next);
+                    return;
+                }
+                Some(d) => self.discontinuities.push(d),
+            }
+        }
+        self.series.push(next);
+    }
+}
+
+/// Collector is a handler assembling time series for channels across frames and files.
+/// Parse files in GPS order with the same collector to join them.
+pub struct Collector {
+    names: Vec<String>,
+    calibrate: bool,
+    frame: Option<FrameHeader>,
+    channels: BTreeMap<String, Segments>,
+    errors: Vec<Error>,
+}
+
+impl Collector {
+    /// new collects the named channels.  With no names every channel is collected.
+    pub fn new(names: &[&str]) -> Self {
+        Collector {
+            names: names.iter().map(|n| n.to_string()).collect(),
+            calibrate: false,
+            frame: None,
+            channels: BTreeMap::new(),
+            errors: Vec::new(),
+        }
+    }
+
+    /// calibrate sets whether ADC channels are converted into physical units.
+    pub fn calibrate(&mut self, calibrate: bool) {
+        self.calibrate = calibrate;
+    }
+
+    /// segments returns the pieces collected for name.
+    pub fn segments(&self, name: &str) -> Option<&Segments> {
+        self.channels.get(name)
+    }
+
+    /// errors are channels that could not be turned into time series.
+    pub fn errors(&self) -> &[Error] {
+        &self.errors
+    }
+
+    pub fn into_segments(self) -> BTreeMap<String, Segments> {
+        self.channels
+    }
+
+    fn wants(&self, name: &str) -> bool {
+        self.names.is_empty() || self.names.iter().any(|n| n == name)
+    }
+}
+
+impl Handler for Collector {
+    fn begin_frame(&mut self) -> Option<fn(&mut Self, header: FrameHeader)> {
+        Some(|c: &mut Collector, header: FrameHeader| {
+            c.frame = Some(header);
+        })
+    }
+
+    fn channel(&mut self) -> Option<fn(&mut Self, channel: Channel, data: Vector)> {
+        Some(|c: &mut Collector, channel: Channel, data: Vector| {
+            if !c.wants(channel.name()) {
+                return;
+            }
+            let frame = match &c.frame {
+                Some(f) => f,
+                None => {
+                    c.errors.push(Error::new(
+                        ErrorKind::InvalidData,
+                        format!("{}: data before the first frame", channel.name()),
+                    ));
+                    return;
+                }
+            };
+            let ts = match &channel {
+                Channel::ADC(adc) if c.calibrate => TimeSeries::calibrated(frame, adc, data),
+                _ => TimeSeries::new(frame, &channel, data),
+            };
+            match ts {
+                Ok(ts) => c.channels.entry(ts.name.clone()).or_default().push(ts),
+                Err(e) => c.errors.push(e),
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod timeseries_tests {
+    use super::*;
+
+    fn frame(gps_start_time_s: u32) -> FrameHeader {
+        FrameHeader {
+            name: "H".to_string(),
+            run: 0,
+            frame: 0,
+            data_quality: DataQuality(0),
+            gps_start_time_s,
+            gps_residual_time_ns: 0,
+            gps_leap_s: 18,
+            frame_length_s: 1.0,
+        }
+    }
+
+    fn adc(time_offset_s: f64) -> Channel {
+        Channel::ADC(ADC {
+            name: "H1:TEST".to_string(),
+            comment: String::new(),
+            channel_group: 0,
+            channel_number: 0,
+            num_bits: 16,
+            bias: 0.0,
+            slope: 1.0,
+            units: None,
+            sample_rate: 4.0,
+            time_offset_s,
+            f_shift: 0.0,
+            phase: 0.0,
+            data_valid: DataValid(0),
+        })
+    }
+
+    fn data(values: Vec<i16>) -> Vector {
+        Vector::I16(
+            VectorInfo {
+                name: "H1:TEST".to_string(),
+                num_samples: values.len() as u64,
+                num_dimensions: 1,
+                dimension_lengths: vec![values.len() as u64],
+                sample_spacing: vec![0.25],
+                x_origins: vec![0.0],
+                unit_x_scale_factors: vec!["s".to_string()],
+                unit_y: "counts".to_string(),
+                compression: 0,
+            },
+            values,
+        )
+    }
+
+    #[test]
+    fn test_new() -> Result<()> {
+        let ts = TimeSeries::new(&frame(1000), &adc(0.5), data(vec![1, 2, 3, 4]))?;
+        assert_eq!(ts.gps_start_s, 1000.5);
+        assert_eq!(ts.sample_rate, 4.0);
+        assert_eq!(ts.gps_end_s(), 1001.5);
+        assert_eq!(ts.samples, Samples::Real(vec![1.0, 2.0, 3.0, 4.0]));
+
+        // without a spacing the channel's rate is used, and it must be positive.
+        let mut unspaced = data(vec![1, 2]);
+        unspaced.info_mut().sample_spacing[0] = 0.0;
+        let ts = TimeSeries::new(&frame(1000), &adc(0.0), unspaced.clone())?;
+        assert_eq!(ts.sample_rate, 4.0);
+        let mut channel = adc(0.0);
+        if let Channel::ADC(c) = &mut channel {
+            c.sample_rate = 0.0;
+        }
+        let err = TimeSeries::new(&frame(1000), &channel, unspaced).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        Ok(())
+    }
+
+    #[test]
+    fn test_append() -> Result<()> {
+        let mut ts = TimeSeries::new(&frame(1000), &adc(0.0), data(vec![1, 2, 3, 4]))?;
+        let next = TimeSeries::new(&frame(1001), &adc(0.0), data(vec![5, 6, 7, 8]))?;
+        assert_eq!(ts.append(next), Ok(()));
+        assert_eq!(ts.len(), 8);
+        assert_eq!(ts.gps_end_s(), 1002.0);
+
+        let gap = TimeSeries::new(&frame(1003), &adc(0.0), data(vec![1, 2, 3, 4]))?;
+        assert_eq!(
+            ts.append(gap),
+            Err(Discontinuity::Gap {
+                gps_s: 1002.0,
+                duration_s: 1.0
+            })
+        );
+
+        let overlap = TimeSeries::new(&frame(1001), &adc(0.5), data(vec![1, 2, 3, 4]))?;
+        assert_eq!(
+            ts.append(overlap),
+            Err(Discontinuity::Overlap {
+                gps_s: 1001.5,
+                duration_s: 0.5
+            })
+        );
+        assert_eq!(ts.len(), 8);
+        Ok(())
+    }
+
+    #[test]
+    fn test_window() -> Result<()> {
+        let ts = TimeSeries::new(&frame(1000), &adc(0.0), data(vec![1, 2, 3, 4]))?;
+        let w = ts.window(1000.25, 1000.75);
+        assert_eq!(w.gps_start_s, 1000.25);
+        assert_eq!(w.samples, Samples::Real(vec![2.0, 3.0]));
+        assert_eq!(ts.window(999.0, 1000.1).samples, Samples::Real(vec![1.0]));
+        assert!(ts.window(1001.0, 1002.0).is_empty());
+        assert!(ts.window(1000.5, 1000.0).is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_calibrated() -> Result<()> {
+        let adc = match adc(0.0) {
+            Channel::ADC(mut a) => {
+                a.slope = 2.0;
+                a.units = Some("m".to_string());
+                a
+            }
+            _ => unreachable!(),
+        };
+        let ts = TimeSeries::calibrated(&frame(1000), &adc, data(vec![1, 2, 3, 4]))?;
+        assert_eq!(ts.unit, "m");
+        assert_eq!(ts.samples, Samples::Real(vec![2.0, 4.0, 6.0, 8.0]));
+        Ok(())
+    }
+
+    #[test]
+    fn test_collector() {
+        let mut c = Collector::new(&["H1:TEST"]);
+        let begin_frame = c.begin_frame().unwrap();
+        let channel = c.channel().unwrap();
+        for start in &[1000, 1001, 1003] {
+            begin_frame(&mut c, frame(*start));
+            channel(&mut c, adc(0.0), data(vec![0; 4]));
+        }
+
+        let segments = c.segments("H1:TEST").unwrap();
+        assert_eq!(segments.series.len(), 2);
+        assert_eq!(segments.series[0].len(), 8);
+        assert_eq!(
+            segments.discontinuities,
+            vec![Discontinuity::Gap {
+                gps_s: 1002.0,
+                duration_s: 1.0
+            }]
+        );
+        assert!(c.errors().is_empty());
+    }
+}
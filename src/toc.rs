@@ -0,0 +1,171 @@
+use crate::endian::EndianRead;
+use crate::structures::*;
+use crate::v8;
+use std::io::{Error, ErrorKind, Read, Result, Seek, SeekFrom};
+
+/// Toc is the table of contents a version 8 file stores before its end.
+/// It locates every frame and channel without reading the whole file.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Toc {
+    pub leap_seconds: i16,
+    pub frames: Vec<TocFrame>,
+    /// dictionary is the class id and name of each structure type in the file.
+    pub dictionary: Vec<(u16, String)>,
+    pub channels: Vec<TocChannel>,
+}
+
+/// TocFrame is the table of contents entry of one frame.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TocFrame {
+    pub run: i32,
+    pub frame: u32,
+    pub data_quality: DataQuality,
+    pub gps_start_time_s: u32,
+    pub gps_residual_time_ns: u32,
+    pub frame_length_s: f64,
+    /// position is the byte offset of the frame header.
+    pub position: u64,
+}
+
+/// TocChannel is the table of contents entry of one channel.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TocChannel {
+    pub name: String,
+    pub kind: ChannelKind,
+    /// positions is the byte offset of the channel's structure in each
+    /// frame; 0 when the frame does not have the channel.
+    pub positions: Vec<u64>,
+}
+
+impl TocChannel {
+    /// frames is the number of frames that have the channel.
+    pub fn frames(&self) -> usize {
+        self.positions.iter().filter(|p| **p != 0).count()
+    }
+}
+
+/// EOF_SIZE is the length of a version 8 FrEndOfFile.
+const EOF_SIZE: u64 = 46;
+
+/// read returns the table of contents of reader. Files without one,
+/// including every version 6 file, return None.
+pub fn read<R: Read + Seek>(reader: &mut R) -> Result<Option<Toc>> {
+    let header = crate::parse_header(reader)?;
+    if header.version.major != Major::Release8 {
+        return Ok(None);
+    }
+    let file_len = reader.seek(SeekFrom::End(0))?;
+    if file_len < Header::size_of() as u64 + EOF_SIZE {
+        return Ok(None);
+    }
+
+    let mut eof = [0; EOF_SIZE as usize];
+    reader.seek(SeekFrom::Start(file_len - EOF_SIZE))?;
+    reader.read_exact(&mut eof)?;
+    if u64::from_bytes(&eof[0..8], header.endian) != EOF_SIZE {
+        return Ok(None);
+    }
+    let seek_toc = u64::from_bytes(&eof[26..34], header.endian);
+    if seek_toc == 0 {
+        return Ok(None);
+    }
+    if seek_toc > file_len - Header::size_of() as u64 {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!(
+                "table of contents is {} bytes before the end of a {} byte file",
+                seek_toc, file_len
+            ),
+        ));
+    }
+
+    reader.seek(SeekFrom::Start(file_len - seek_toc))?;
+    let mut common_buf = [0; Common::size_of()];
+    let common = v8::common(&header, reader, &mut common_buf)?;
+    let mut toc_reader = reader.take(common.struct_length());
+    v8::toc(&header, &mut toc_reader).map(Some)
+}
+
+#[cfg(test)]
+mod toc_tests {
+    use super::*;
+
+    fn header() -> Header {
+        Header {
+            version: Version {
+                major: Major::Release8,
+                minor: Minor::Minor(0),
+            },
+            library: Library::LibUnknown(0),
+            endian: Endian::Little,
+            machine_data_model: data_models::DataModel::LP64,
+            checksum: Checksum::SumNone,
+        }
+    }
+
+    fn string(buf: &mut Vec<u8>, s: &str) {
+        buf.extend_from_slice(&(s.len() as u16 + 1).to_le_bytes());
+        buf.extend_from_slice(s.as_bytes());
+        buf.push(0);
+    }
+
+    #[test]
+    fn test_toc() {
+        let mut buf = Vec::new();
+        buf.extend_from_slice(&18i16.to_le_bytes());
+        buf.extend_from_slice(&2u32.to_le_bytes());
+        for dq in [0u32, 1].iter() {
+            buf.extend_from_slice(&dq.to_le_bytes());
+        }
+        for s in [1_000_000_000u32, 1_000_000_001].iter() {
+            buf.extend_from_slice(&s.to_le_bytes());
+        }
+        buf.extend_from_slice(&[0; 8]); // GTimeN
+        buf.extend_from_slice(&1.0f64.to_le_bytes());
+        buf.extend_from_slice(&1.0f64.to_le_bytes());
+        buf.extend_from_slice(&[0; 8]); // runs
+        buf.extend_from_slice(&7u32.to_le_bytes());
+        buf.extend_from_slice(&8u32.to_le_bytes());
+        buf.extend_from_slice(&100u64.to_le_bytes());
+        buf.extend_from_slice(&200u64.to_le_bytes());
+        buf.extend_from_slice(&[0; 4 * 2 * 8]); // nFirst*
+
+        buf.extend_from_slice(&1u32.to_le_bytes());
+        buf.extend_from_slice(&3u16.to_le_bytes());
+        string(&mut buf, "FrameH");
+        buf.extend_from_slice(&[0; 4]); // detectors
+        buf.extend_from_slice(&[0; 8]); // static data
+
+        buf.extend_from_slice(&1u32.to_le_bytes());
+        string(&mut buf, "H1:ADC");
+        buf.extend_from_slice(&[0; 8]); // channelID and groupID
+        buf.extend_from_slice(&110u64.to_le_bytes());
+        buf.extend_from_slice(&0u64.to_le_bytes());
+
+        buf.extend_from_slice(&1u32.to_le_bytes());
+        string(&mut buf, "H1:PROC");
+        buf.extend_from_slice(&120u64.to_le_bytes());
+        buf.extend_from_slice(&220u64.to_le_bytes());
+        buf.extend_from_slice(&[0; 8]); // simulated and serial
+
+        let toc = v8::toc(&header(), &mut buf.as_slice()).unwrap();
+        assert_eq!(toc.leap_seconds, 18);
+        assert_eq!(toc.frames.len(), 2);
+        assert_eq!(toc.frames[1].gps_start_time_s, 1_000_000_001);
+        assert_eq!(toc.frames[1].data_quality, DataQuality(1));
+        assert_eq!(toc.frames[1].frame, 8);
+        assert_eq!(toc.frames[1].position, 200);
+        assert_eq!(toc.dictionary, vec![(3, "FrameH".to_string())]);
+
+        assert_eq!(toc.channels.len(), 2);
+        assert_eq!(toc.channels[0].name, "H1:ADC");
+        assert_eq!(toc.channels[0].kind, ChannelKind::ADC);
+        assert_eq!(toc.channels[0].positions, vec![110, 0]);
+        assert_eq!(toc.channels[0].frames(), 1);
+        assert_eq!(toc.channels[1].kind, ChannelKind::PostProcessed);
+        assert_eq!(toc.channels[1].positions, vec![120, 220]);
+    }
+}
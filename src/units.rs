@@ -0,0 +1,495 @@
+use crate::structures::{VectorInfo, ADC};
+use std::fmt;
+use std::io::{Error, ErrorKind, Result};
+use std::ops::{Div, Mul};
+use std::str::FromStr;
+
+/// Base is one of the SI base dimensions.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Base {
+    Meter,
+    Kilogram,
+    Second,
+    Ampere,
+    Kelvin,
+    Mole,
+    Candela,
+}
+
+const BASES: [(Base, &str); 7] = [
+    (Base::Kilogram, "kg"),
+    (Base::Meter, "m"),
+    (Base::Second, "s"),
+    (Base::Ampere, "A"),
+    (Base::Kelvin, "K"),
+    (Base::Mole, "mol"),
+    (Base::Candela, "cd"),
+];
+
+/// Unit is a scale factor times a product of SI base dimensions, for example
+/// `mHz` is 0.001 s^-1.  Strain, counts and radians are dimensionless.
+#[derive(Debug, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Unit {
+    pub scale: f64,
+    // twice the exponent of each Base so that amplitude spectral densities
+    // such as strain/sqrt(Hz) can be represented.
+    halves: [i8; 7],
+}
+
+/// dims doubles the exponents of m, kg, s and A.
+const fn dims(m: i8, kg: i8, s: i8, a: i8) -> [i8; 7] {
+    [2 * m, 2 * kg, 2 * s, 2 * a, 0, 0, 0]
+}
+
+const NONE: [i8; 7] = [0; 7];
+
+/// UNITS are the symbols and aliases understood by the parser, their scale and
+/// dimensions, and whether an SI prefix may be attached.
+const UNITS: &[(&str, f64, [i8; 7], bool)] = &[
+    ("none", 1.0, NONE, false),
+    ("dimensionless", 1.0, NONE, false),
+    ("unitless", 1.0, NONE, false),
+    ("strain", 1.0, NONE, false),
+    ("counts", 1.0, NONE, false),
+    ("count", 1.0, NONE, false),
+    ("cts", 1.0, NONE, false),
+    ("ct", 1.0, NONE, false),
+    ("rad", 1.0, NONE, true),
+    ("radian", 1.0, NONE, false),
+    ("radians", 1.0, NONE, false),
+    ("deg", std::f64::consts::PI / 180.0, NONE, false),
+    ("degree", std::f64::consts::PI / 180.0, NONE, false),
+    ("degrees", std::f64::consts::PI / 180.0, NONE, false),
+    ("%", 0.01, NONE, false),
+    ("m", 1.0, dims(1, 0, 0, 0), true),
+    ("meter", 1.0, dims(1, 0, 0, 0), false),
+    ("meters", 1.0, dims(1, 0, 0, 0), false),
+    ("metre", 1.0, dims(1, 0, 0, 0), false),
+    ("metres", 1.0, dims(1, 0, 0, 0), false),
+    ("g", 1e-3, dims(0, 1, 0, 0), true),
+    ("gram", 1e-3, dims(0, 1, 0, 0), false),
+    ("grams", 1e-3, dims(0, 1, 0, 0), false),
+    ("s", 1.0, dims(0, 0, 1, 0), true),
+    ("sec", 1.0, dims(0, 0, 1, 0), false),
+    ("second", 1.0, dims(0, 0, 1, 0), false),
+    ("seconds", 1.0, dims(0, 0, 1, 0), false),
+    ("min", 60.0, dims(0, 0, 1, 0), false),
+    ("minute", 60.0, dims(0, 0, 1, 0), false),
+    ("minutes", 60.0, dims(0, 0, 1, 0), false),
+    ("h", 3600.0, dims(0, 0, 1, 0), false),
+    ("hr", 3600.0, dims(0, 0, 1, 0), false),
+    ("hour", 3600.0, dims(0, 0, 1, 0), false),
+    ("hours", 3600.0, dims(0, 0, 1, 0), false),
+    ("A", 1.0, dims(0, 0, 0, 1), true),
+    ("amp", 1.0, dims(0, 0, 0, 1), false),
+    ("amps", 1.0, dims(0, 0, 0, 1), false),
+    ("ampere", 1.0, dims(0, 0, 0, 1), false),
+    ("K", 1.0, [0, 0, 0, 0, 2, 0, 0], true),
+    ("kelvin", 1.0, [0, 0, 0, 0, 2, 0, 0], false),
+    ("mol", 1.0, [0, 0, 0, 0, 0, 2, 0], true),
+    ("cd", 1.0, [0, 0, 0, 0, 0, 0, 2], true),
+    ("Hz", 1.0, dims(0, 0, -1, 0), true),
+    ("hz", 1.0, dims(0, 0, -1, 0), false),
+    ("hertz", 1.0, dims(0, 0, -1, 0), false),
+    ("rtHz", 1.0, [0, 0, -1, 0, 0, 0, 0], false),
+    ("rthz", 1.0, [0, 0, -1, 0, 0, 0, 0], false),
+    ("N", 1.0, dims(1, 1, -2, 0), true),
+    ("newton", 1.0, dims(1, 1, -2, 0), false),
+    ("Pa", 1.0, dims(-1, 1, -2, 0), true),
+    ("bar", 1e5, dims(-1, 1, -2, 0), true),
+    ("torr", 133.322_368_421, dims(-1, 1, -2, 0), false),
+    ("Torr", 133.322_368_421, dims(-1, 1, -2, 0), false),
+    ("J", 1.0, dims(2, 1, -2, 0), true),
+    ("W", 1.0, dims(2, 1, -3, 0), true),
+    ("watt", 1.0, dims(2, 1, -3, 0), false),
+    ("watts", 1.0, dims(2, 1, -3, 0), false),
+    ("C", 1.0, dims(0, 0, 1, 1), true),
+    ("V", 1.0, dims(2, 1, -3, -1), true),
+    ("volt", 1.0, dims(2, 1, -3, -1), false),
+    ("volts", 1.0, dims(2, 1, -3, -1), false),
+    ("Ohm", 1.0, dims(2, 1, -3, -2), true),
+    ("ohm", 1.0, dims(2, 1, -3, -2), true),
+    ("ohms", 1.0, dims(2, 1, -3, -2), false),
+    ("Ω", 1.0, dims(2, 1, -3, -2), true),
+    ("T", 1.0, dims(0, 1, -2, -1), true),
+    ("tesla", 1.0, dims(0, 1, -2, -1), false),
+    ("gauss", 1e-4, dims(0, 1, -2, -1), false),
+];
+
+const PREFIXES: [(&str, f64); 10] = [
+    ("p", 1e-12),
+    ("n", 1e-9),
+    ("u", 1e-6),
+    ("µ", 1e-6),
+    ("μ", 1e-6),
+    ("m", 1e-3),
+    ("c", 1e-2),
+    ("k", 1e3),
+    ("M", 1e6),
+    ("G", 1e9),
+];
+
+impl Unit {
+    pub fn dimensionless() -> Unit {
+        Unit {
+            scale: 1.0,
+            halves: NONE,
+        }
+    }
+
+    /// base returns the unit of one SI base dimension.
+    pub fn base(base: Base) -> Unit {
+        let mut u = Unit::dimensionless();
+        u.halves[base as usize] = 2;
+        u
+    }
+
+    /// exponent returns the power of base, e.g. -0.5 for seconds in strain/sqrt(Hz).
+    pub fn exponent(&self, base: Base) -> f64 {
+        self.halves[base as usize] as f64 / 2.0
+    }
+
+    pub fn is_dimensionless(&self) -> bool {
+        self.halves == NONE
+    }
+
+    /// same_dimensions is true if the units only differ by their scale.
+    pub fn same_dimensions(&self, other: &Unit) -> bool {
+        self.halves == other.halves
+    }
+
+    /// factor_to returns the number to multiply values in this unit by to express them in other.
+    pub fn factor_to(&self, other: &Unit) -> Option<f64> {
+        if self.same_dimensions(other) {
+            Some(self.scale / other.scale)
+        } else {
+            None
+        }
+    }
+
+    pub fn powi(&self, n: i32) -> Unit {
+        let mut halves = self.halves;
+        for h in halves.iter_mut() {
+            *h = (*h as i32 * n) as i8;
+        }
+        Unit {
+            scale: self.scale.powi(n),
+            halves,
+        }
+    }
+
+    /// sqrt is None if a dimension would need a quarter power.
+    pub fn sqrt(&self) -> Option<Unit> {
+        if self.halves.iter().any(|h| h % 2 != 0) {
+            return None;
+        }
+        let mut halves = self.halves;
+        for h in halves.iter_mut() {
+            *h /= 2;
+        }
+        Some(Unit {
+            scale: self.scale.sqrt(),
+            halves,
+        })
+    }
+
+    fn pow_halves(&self, halves: i32) -> Option<Unit> {
+        if halves % 2 == 0 {
+            return Some(self.powi(halves / 2));
+        }
+        self.powi(halves).sqrt()
+    }
+
+    fn symbol(word: &str) -> Option<Unit> {
+        let find = |w: &str| UNITS.iter().find(|(name, _, _, _)| *name == w);
+        let unit = |(_, scale, halves, _): &(&str, f64, [i8; 7], bool)| Unit {
+            scale: *scale,
+            halves: *halves,
+        };
+
+        if let Some(u) = find(word) {
+            return Some(unit(u));
+        }
+        let lower = word.to_lowercase();
+        if word.chars().count() > 2 {
+            if let Some(u) = find(&lower) {
+                return Some(unit(u));
+            }
+        }
+        for (prefix, factor) in PREFIXES.iter() {
+            if let Some(rest) = word.strip_prefix(prefix) {
+                if let Some(u) = find(rest).filter(|(_, _, _, prefixable)| *prefixable) {
+                    let mut u = unit(u);
+                    u.scale *= factor;
+                    return Some(u);
+                }
+            }
+        }
+        None
+    }
+}
+
+impl ADC {
+    /// unit parses the units of the calibrated data; no units are dimensionless.
+    pub fn unit(&self) -> Result<Unit> {
+        self.units.as_deref().unwrap_or_default().parse()
+    }
+}
+
+impl VectorInfo {
+    /// y_unit parses unit_y.
+    pub fn y_unit(&self) -> Result<Unit> {
+        self.unit_y.parse()
+    }
+}
+
+impl Default for Unit {
+    fn default() -> Self {
+        Unit::dimensionless()
+    }
+}
+
+impl Mul for Unit {
+    type Output = Unit;
+    fn mul(self, rhs: Unit) -> Unit {
+        let mut halves = self.halves;
+        for (h, r) in halves.iter_mut().zip(rhs.halves.iter()) {
+            *h += r;
+        }
+        Unit {
+            scale: self.scale * rhs.scale,
+            halves,
+        }
+    }
+}
+
+impl Div for Unit {
+    type Output = Unit;
+    fn div(self, rhs: Unit) -> Unit {
+        self * rhs.powi(-1)
+    }
+}
+
+/// Display writes the unit in SI base units, e.g. `0.001 kg m^2 s^-2`.
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut parts: Vec<String> = Vec::new();
+        if self.scale != 1.0 || self.is_dimensionless() {
+            parts.push(format!("{}", self.scale));
+        }
+        for (base, symbol) in BASES.iter() {
+            let h = self.halves[*base as usize];
+            match h {
+                0 => {}
+                2 => parts.push(symbol.to_string()),
+                h if h % 2 == 0 => parts.push(format!("{}^{}", symbol, h / 2)),
+                h => parts.push(format!("{}^({}/2)", symbol, h)),
+            }
+        }
+        write!(f, "{}", parts.join(" "))
+    }
+}
+
+impl FromStr for Unit {
+    type Err = Error;
+
+    /// from_str parses strings such as `m/s^2`, `strain/sqrt(Hz)`, `1/Hz` or
+    /// `counts`.  An empty string is dimensionless.
+    fn from_str(s: &str) -> Result<Unit> {
+        let mut p = UnitParser {
+            chars: s.trim().chars().collect(),
+            pos: 0,
+        };
+        if p.chars.is_empty() {
+            return Ok(Unit::dimensionless());
+        }
+        let invalid = || Error::new(ErrorKind::InvalidInput, format!("invalid unit: {}", s));
+        let u = p.expr().ok_or_else(invalid)?;
+        p.skip_spaces();
+        if p.pos != p.chars.len() {
+            return Err(invalid());
+        }
+        Ok(u)
+    }
+}
+
+struct UnitParser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl UnitParser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn skip_spaces(&mut self) {
+        while self.peek() == Some(' ') {
+            self.pos += 1;
+        }
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expr(&mut self) -> Option<Unit> {
+        let mut u = self.term()?;
+        loop {
+            self.skip_spaces();
+            if self.eat('/') {
+                self.skip_spaces();
+                u = u / self.term()?;
+            } else if self.eat('*') || self.eat('.') || self.eat('·') {
+                self.skip_spaces();
+                u = u * self.term()?;
+            } else if self.peek().is_some_and(|c| c == '(' || is_word(c)) {
+                // whitespace between two units is a product.
+                u = u * self.term()?;
+            } else {
+                return Some(u);
+            }
+        }
+    }
+
+    fn term(&mut self) -> Option<Unit> {
+        let u = self.atom()?;
+        let power = if self.eat('^') || (self.peek() == Some('*') && self.peek_at(1) == Some('*')) {
+            if self.peek() == Some('*') {
+                self.pos += 2;
+            }
+            self.exponent()?
+        } else if self.peek().is_some_and(|c| c.is_ascii_digit() || c == '-') {
+            // exponents may follow the symbol directly, as in m2 or s-1.
+            self.exponent()?
+        } else {
+            2
+        };
+        u.pow_halves(power)
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.pos + offset).copied()
+    }
+
+    fn atom(&mut self) -> Option<Unit> {
+        if self.eat('(') {
+            let u = self.expr()?;
+            self.skip_spaces();
+            return if self.eat(')') { Some(u) } else { None };
+        }
+        if self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            let n = self.number()?;
+            return Some(Unit {
+                scale: n,
+                halves: NONE,
+            });
+        }
+
+        let start = self.pos;
+        while self.peek().is_some_and(is_word) {
+            self.pos += 1;
+        }
+        let word: String = self.chars[start..self.pos].iter().collect();
+        if word == "sqrt" {
+            return self.atom()?.sqrt();
+        }
+        Unit::symbol(&word)
+    }
+
+    fn number(&mut self) -> Option<f64> {
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|c| c.is_ascii_digit() || c == '.' || c == 'e')
+        {
+            self.pos += 1;
+        }
+        let s: String = self.chars[start..self.pos].iter().collect();
+        s.parse().ok()
+    }
+
+    /// exponent returns twice the exponent: 2, -1/2, (1/2) and 0.5 are accepted.
+    fn exponent(&mut self) -> Option<i32> {
+        let paren = self.eat('(');
+        let negative = self.eat('-');
+        let mut value = self.number()?;
+        if paren && self.eat('/') {
+            value /= self.number()?;
+        }
+        if paren && !self.eat(')') {
+            return None;
+        }
+        if negative {
+            value = -value;
+        }
+        let halves = value * 2.0;
+        if halves.fract() != 0.0 {
+            return None;
+        }
+        Some(halves as i32)
+    }
+}
+
+fn is_word(c: char) -> bool {
+    c.is_alphabetic() || c == '%' || c == '_'
+}
+
+#[cfg(test)]
+mod units_tests {
+    use super::*;
+
+    fn unit(s: &str) -> Unit {
+        s.parse().unwrap_or_else(|e| panic!("{}: {}", s, e))
+    }
+
+    #[test]
+    fn test_parse() {
+        assert!(unit("").is_dimensionless());
+        assert!(unit("none").is_dimensionless());
+        assert!(unit("strain").is_dimensionless());
+        assert!(unit("counts").is_dimensionless());
+
+        let accel = unit("m/s^2");
+        assert_eq!(accel.exponent(Base::Meter), 1.0);
+        assert_eq!(accel.exponent(Base::Second), -2.0);
+        assert_eq!(accel, unit("m s^-2"));
+        assert_eq!(accel, unit("m*s**-2"));
+
+        assert_eq!(unit("Hz"), unit("1/s"));
+        assert_eq!(unit("Hz"), unit("s-1"));
+        assert_eq!(unit("mHz").scale, 1e-3);
+        assert_eq!(unit("um").scale, 1e-6);
+        assert_eq!(unit("kg"), Unit::base(Base::Kilogram));
+        assert_eq!(unit("Meters"), unit("m"));
+
+        let asd = unit("strain/sqrt(Hz)");
+        assert_eq!(asd.exponent(Base::Second), 0.5);
+        assert_eq!(asd, unit("Hz^(-1/2)"));
+        assert_eq!(asd, unit("1/rtHz"));
+
+        assert!("furlong".parse::<Unit>().is_err());
+        assert!("m/".parse::<Unit>().is_err());
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let v = unit("V");
+        let a = unit("A");
+        assert_eq!(v / a, unit("Ohm"));
+        assert_eq!(unit("N") * unit("m"), unit("J"));
+        assert_eq!(unit("W") / unit("J"), unit("Hz"));
+        assert_eq!(unit("nm").factor_to(&unit("m")), Some(1e-9));
+        assert_eq!(unit("m").factor_to(&unit("s")), None);
+        assert_eq!(unit("m^2").sqrt(), Some(unit("m")));
+        assert_eq!(unit("mJ").to_string(), "0.001 kg m^2 s^-2");
+        assert_eq!(unit("strain/rtHz").to_string(), "s^(1/2)");
+    }
+}
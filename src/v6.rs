@@ -1,7 +1,9 @@
 use crate::endian::*;
 use crate::handler::*;
+use crate::links::Links;
 use crate::strings::*;
 use crate::structures::*;
+use crate::v8::reference;
 use std::io::Read;
 
 pub(crate) fn common<R: Read>(header: &Header, reader: &mut R) -> std::io::Result<Common> {
@@ -10,7 +12,8 @@ pub(crate) fn common<R: Read>(header: &Header, reader: &mut R) -> std::io::Resul
 
     let length = u64::from_bytes(&common[0..8], header.endian);
     let class = u16::from_bytes(&common[8..10], header.endian) as u8;
-    Ok(Common::new(length, class))
+    let instance = u32::from_bytes(&common[10..14], header.endian);
+    Ok(Common::new(length, class, instance))
 }
 
 pub(crate) fn simulated_event<R: Read, T: Handler>(
@@ -108,7 +111,9 @@ pub(crate) fn serial<R: Read, T: Handler>(
     header: &Header,
     reader: &mut R,
     handler: &mut T,
+    links: &mut Links,
 ) -> std::io::Result<()> {
+    let mut buf2 = [0; core::mem::size_of::<u16>()];
     let mut buf4 = [0; core::mem::size_of::<u32>()];
     let s = Serial {
         name: string(header, reader)?,
@@ -117,6 +122,10 @@ pub(crate) fn serial<R: Read, T: Handler>(
         sample_rate: f32::read_into(&mut buf4, reader, header.endian)? as f64,
         data: string(header, reader)?,
     };
+    let data = reference(header, reader, &mut buf2, &mut buf4)?;
+    if handler.channel().is_some() {
+        links.expect(data, Channel::Serial(s.clone()));
+    }
     if let Some(handle_serial) = handler.serial() {
         handle_serial(handler, s);
     }
@@ -127,7 +136,9 @@ pub(crate) fn simulated<R: Read, T: Handler>(
     header: &Header,
     reader: &mut R,
     handler: &mut T,
+    links: &mut Links,
 ) -> std::io::Result<()> {
+    let mut buf2 = [0; core::mem::size_of::<u16>()];
     let mut buf4 = [0; core::mem::size_of::<f32>()];
     let mut buf8 = [0; core::mem::size_of::<f64>()];
     let s = Simulation {
@@ -138,6 +149,10 @@ pub(crate) fn simulated<R: Read, T: Handler>(
         f_shift: f64::read_into(&mut buf8, reader, header.endian)?,
         phase: f32::read_into(&mut buf4, reader, header.endian)?,
     };
+    let data = reference(header, reader, &mut buf2, &mut buf4)?;
+    if handler.channel().is_some() {
+        links.expect(data, Channel::Simulation(s.clone()));
+    }
     if let Some(handle_simulated) = handler.simulated() {
         handle_simulated(handler, s);
     }
@@ -1,6 +1,8 @@
 use crate::handler::*;
+use crate::links::{Links, PendingStatic};
 use crate::strings::*;
 use crate::structures::*;
+use crate::toc::{Toc, TocChannel, TocFrame};
 use crate::{decompress::Decompress, endian::*};
 use core::ptr::copy_nonoverlapping;
 use std::io::Read;
@@ -13,8 +15,22 @@ pub(crate) fn common<R: Read>(
     reader.read_exact(common)?;
     let length = u64::from_bytes(&common[0..8], header.endian);
     let class = common[9];
+    let instance = u32::from_bytes(&common[10..14], header.endian);
 
-    Ok(Common::new(length, class))
+    Ok(Common::new(length, class, instance))
+}
+
+/// reference reads a PTR_STRUCT: the class and instance of the structure pointed to.
+pub(crate) fn reference<R: Read>(
+    header: &Header,
+    reader: &mut R,
+    buf16: &mut [u8; core::mem::size_of::<u16>()],
+    buf32: &mut [u8; core::mem::size_of::<u32>()],
+) -> std::io::Result<Reference> {
+    Ok(Reference {
+        class: u16::read_into(buf16, reader, header.endian)?,
+        instance: u32::read_into(buf32, reader, header.endian)?,
+    })
 }
 
 pub(crate) fn frameheader<R: Read, T: Handler>(
@@ -29,7 +45,7 @@ pub(crate) fn frameheader<R: Read, T: Handler>(
         name: string(header, reader)?,
         run: i32::read_into(buf32, reader, header.endian)?,
         frame: u32::read_into(buf32, reader, header.endian)?,
-        data_quality: u32::read_into(buf32, reader, header.endian)?,
+        data_quality: DataQuality(u32::read_into(buf32, reader, header.endian)?),
         gps_start_time_s: u32::read_into(buf32, reader, header.endian)?,
         gps_residual_time_ns: u32::read_into(buf32, reader, header.endian)?,
         gps_leap_s: { u16::read_into(buf16, reader, header.endian)? },
@@ -62,8 +78,10 @@ pub(crate) fn structure_header<R: Read>(
 
 pub(crate) fn detector<R: Read, T: Handler>(
     header: &Header,
+    common: &Common,
     reader: &mut R,
     handler: &mut T,
+    links: &mut Links,
     buf32: &mut [u8; core::mem::size_of::<f32>()],
     buf64: &mut [u8; core::mem::size_of::<f64>()],
 ) -> std::io::Result<()> {
@@ -85,6 +103,11 @@ pub(crate) fn detector<R: Read, T: Handler>(
         arm_y_midpoint_meters: f32::read_into(buf32, reader, header.endian)?,
         local_time_utc_offset_s: i32::read_into(buf32, reader, header.endian)?,
     };
+    if let Some(handle_static) = handler.static_data() {
+        for s in links.detector(common, d.clone()) {
+            handle_static(handler, s);
+        }
+    }
     if let Some(handle_detector) = handler.detector() {
         handle_detector(handler, d);
     }
@@ -95,6 +118,7 @@ pub(crate) fn adc<R: Read, T: Handler>(
     header: &Header,
     reader: &mut R,
     handler: &mut T,
+    links: &mut Links,
     buf16: &mut [u8; core::mem::size_of::<u16>()],
     buf32: &mut [u8; core::mem::size_of::<u32>()],
     buf64: &mut [u8; core::mem::size_of::<u64>()],
@@ -118,11 +142,12 @@ pub(crate) fn adc<R: Read, T: Handler>(
         time_offset_s: f64::read_into(buf64, reader, header.endian)?,
         f_shift: f64::read_into(buf64, reader, header.endian)?,
         phase: f32::read_into(buf32, reader, header.endian)?,
-        data_valid: {
-            let valid = u16::read_into(buf16, reader, header.endian)?;
-            valid == 0
-        },
+        data_valid: DataValid(u16::read_into(buf16, reader, header.endian)?),
     };
+    let data = reference(header, reader, buf16, buf32)?;
+    if handler.channel().is_some() {
+        links.expect(data, Channel::ADC(a.clone()));
+    }
     if let Some(handle_adc) = handler.adc() {
         handle_adc(handler, a);
     }
@@ -183,6 +208,7 @@ pub(crate) fn post_processed<R: Read, T: Handler>(
     header: &Header,
     reader: &mut R,
     handler: &mut T,
+    links: &mut Links,
     buf16: &mut [u8; core::mem::size_of::<u16>()],
     buf32: &mut [u8; core::mem::size_of::<u32>()],
     buf64: &mut [u8; core::mem::size_of::<u64>()],
@@ -190,9 +216,8 @@ pub(crate) fn post_processed<R: Read, T: Handler>(
     let mut ppd = PostProcessed {
         name: string(header, reader)?,
         comment: string(header, reader)?,
-        // TODO(goller): convert to enum.
-        data_type: u16::read_into(buf16, reader, header.endian)?,
-        sub_type: u16::read_into(buf16, reader, header.endian)?,
+        data_type: DataType::from(u16::read_into(buf16, reader, header.endian)?),
+        sub_type: SubType::from(u16::read_into(buf16, reader, header.endian)?),
         time_offset_s: f64::read_into(buf64, reader, header.endian)?,
         time_range_s: f64::read_into(buf64, reader, header.endian)?,
         f_shift: f64::read_into(buf64, reader, header.endian)?,
@@ -219,6 +244,10 @@ pub(crate) fn post_processed<R: Read, T: Handler>(
         ppd.auxiliary_parameters.push(param);
     }
 
+    let data = reference(header, reader, buf16, buf32)?;
+    if handler.channel().is_some() {
+        links.expect(data, Channel::PostProcessed(ppd.clone()));
+    }
     if let Some(post_process) = handler.post_processed() {
         post_process(handler, ppd);
     }
@@ -229,6 +258,8 @@ pub(crate) fn simulated<R: Read, T: Handler>(
     header: &Header,
     reader: &mut R,
     handler: &mut T,
+    links: &mut Links,
+    buf16: &mut [u8; core::mem::size_of::<u16>()],
     buf32: &mut [u8; core::mem::size_of::<u32>()],
     buf64: &mut [u8; core::mem::size_of::<u64>()],
 ) -> std::io::Result<()> {
@@ -240,6 +271,10 @@ pub(crate) fn simulated<R: Read, T: Handler>(
         f_shift: f64::read_into(buf64, reader, header.endian)?,
         phase: f32::read_into(buf32, reader, header.endian)?,
     };
+    let data = reference(header, reader, buf16, buf32)?;
+    if handler.channel().is_some() {
+        links.expect(data, Channel::Simulation(s.clone()));
+    }
     if let Some(handle_simulated) = handler.simulated() {
         handle_simulated(handler, s);
     }
@@ -290,6 +325,8 @@ pub(crate) fn serial<R: Read, T: Handler>(
     header: &Header,
     reader: &mut R,
     handler: &mut T,
+    links: &mut Links,
+    buf16: &mut [u8; core::mem::size_of::<u16>()],
     buf32: &mut [u8; core::mem::size_of::<u32>()],
     buf64: &mut [u8; core::mem::size_of::<u64>()],
 ) -> std::io::Result<()> {
@@ -300,6 +337,10 @@ pub(crate) fn serial<R: Read, T: Handler>(
         sample_rate: f64::read_into(buf64, reader, header.endian)?,
         data: string(header, reader)?,
     };
+    let data = reference(header, reader, buf16, buf32)?;
+    if handler.channel().is_some() {
+        links.expect(data, Channel::Serial(s.clone()));
+    }
     if let Some(handle_serial) = handler.serial() {
         handle_serial(handler, s);
     }
@@ -310,6 +351,8 @@ pub(crate) fn static_data<R: Read, T: Handler>(
     header: &Header,
     reader: &mut R,
     handler: &mut T,
+    links: &mut Links,
+    buf16: &mut [u8; core::mem::size_of::<u16>()],
     buf32: &mut [u8; core::mem::size_of::<u32>()],
 ) -> std::io::Result<()> {
     let s = StaticData {
@@ -319,9 +362,18 @@ pub(crate) fn static_data<R: Read, T: Handler>(
         gps_time_start_s: u32::read_into(buf32, reader, header.endian)?,
         gps_time_end_s: u32::read_into(buf32, reader, header.endian)?,
         version: u32::read_into(buf32, reader, header.endian)?,
+        detector: None,
+        data: None,
     };
-    if let Some(handle_static) = handler.static_data() {
-        handle_static(handler, s);
+    let pending = PendingStatic {
+        data: s,
+        detector_ref: reference(header, reader, buf16, buf32)?,
+        data_ref: reference(header, reader, buf16, buf32)?,
+    };
+    if let Some(s) = links.expect_static(pending) {
+        if let Some(handle_static) = handler.static_data() {
+            handle_static(handler, s);
+        }
     }
     Ok(())
 }
@@ -345,10 +397,50 @@ pub(crate) fn summary<R: Read, T: Handler>(
     Ok(())
 }
 
+pub(crate) fn end_of_frame<R: Read, T: Handler>(
+    header: &Header,
+    reader: &mut R,
+    handler: &mut T,
+    buf32: &mut [u8; core::mem::size_of::<u32>()],
+) -> std::io::Result<()> {
+    let e = EndOfFrame {
+        run: i32::read_into(buf32, reader, header.endian)?,
+        frame: u32::read_into(buf32, reader, header.endian)?,
+        gps_time_s: u32::read_into(buf32, reader, header.endian)?,
+        gps_residual_time_ns: u32::read_into(buf32, reader, header.endian)?,
+    };
+    if let Some(handle_end_frame) = handler.end_frame() {
+        handle_end_frame(handler, e);
+    }
+    Ok(())
+}
+
+pub(crate) fn end_of_file<R: Read, T: Handler>(
+    header: &Header,
+    reader: &mut R,
+    handler: &mut T,
+    buf32: &mut [u8; core::mem::size_of::<u32>()],
+    buf64: &mut [u8; core::mem::size_of::<u64>()],
+) -> std::io::Result<()> {
+    let e = EndOfFile {
+        num_frames: u32::read_into(buf32, reader, header.endian)?,
+        num_bytes: u64::read_into(buf64, reader, header.endian)?,
+        seek_toc: u64::read_into(buf64, reader, header.endian)?,
+        header_checksum: u32::read_into(buf32, reader, header.endian)?,
+        checksum: u32::read_into(buf32, reader, header.endian)?,
+        file_checksum: u32::read_into(buf32, reader, header.endian)?,
+    };
+    if let Some(handle_end_of_file) = handler.end_of_file() {
+        handle_end_of_file(handler, e);
+    }
+    Ok(())
+}
+
 pub(crate) fn table<R: Read, T: Handler>(
     header: &Header,
     reader: &mut R,
     handler: &mut T,
+    links: &mut Links,
     buf16: &mut [u8; core::mem::size_of::<u16>()],
     buf32: &mut [u8; core::mem::size_of::<u32>()],
 ) -> std::io::Result<()> {
@@ -357,6 +449,7 @@ pub(crate) fn table<R: Read, T: Handler>(
         comment: string(header, reader)?,
         num_rows: 0,
         column_names: Vec::with_capacity(0),
+        columns: Vec::new(),
     };
 
     let num_columns = u16::read_into(buf16, reader, header.endian)?;
@@ -367,7 +460,35 @@ pub(crate) fn table<R: Read, T: Handler>(
         tbl.column_names.push(string(header, reader)?);
     }
 
-    if let Some(handle_table) = handler.table() {
+    // The columns are a list of vectors starting at column and chained by FrVect.next.
+    let column = reference(header, reader, buf16, buf32)?;
+    if num_columns > 0 && !column.is_null() {
+        links.expect_column(column, tbl);
+    } else if let Some(handle_table) = handler.table() {
+        handle_table(handler, tbl);
+    }
+    Ok(())
+}
+
+/// column adds the vector to its table and either waits for the next
+/// column or hands the finished table to the handler.  A vector without
+/// num_rows elements ends the table early, leaving it and the columns after
+/// it missing.
+fn column<T: Handler>(
+    handler: &mut T,
+    links: &mut Links,
+    mut tbl: Table,
+    v: Vector,
+    next: Reference,
+) -> std::io::Result<()> {
+    let fits = v.len() == tbl.num_rows as usize;
+    if fits {
+        tbl.columns.push(v);
+    }
+
+    if fits && tbl.columns.len() < tbl.column_names.len() && !next.is_null() {
+        links.expect_column(next, tbl);
+    } else if let Some(handle_table) = handler.table() {
         handle_table(handler, tbl);
     }
     Ok(())
@@ -460,7 +581,7 @@ fn new_vector(data_buf: Vec<u8>, class: u16, endian: Endian, info: VectorInfo) -
             let mut v = vec![0.0; data_buf.len()/core::mem::size_of::<f32>()];
             read_into_slice_f32(data_buf.as_slice(), v.as_mut_slice(), endian);
             let mut c: Vec<Complex> = Vec::with_capacity(v.len()/2);
-            for i in 0..c.len() {
+            for i in 0..v.len()/2 {
                 c.push(Complex{
                     real: v[i*2] as f64,
                     imaginary: v[i*2+1] as f64,
@@ -472,7 +593,7 @@ fn new_vector(data_buf: Vec<u8>, class: u16, endian: Endian, info: VectorInfo) -
            let mut v = vec![0.0; data_buf.len()/core::mem::size_of::<f64>()];
             read_into_slice_f64(data_buf.as_slice(), v.as_mut_slice(), endian);
             let mut c: Vec<Complex> = Vec::with_capacity(v.len()/2);
-            for i in 0..c.len() {
+            for i in 0..v.len()/2 {
                 c.push(Complex{
                     real: v[i*2],
                     imaginary: v[i*2+1],
@@ -480,9 +601,9 @@ fn new_vector(data_buf: Vec<u8>, class: u16, endian: Endian, info: VectorInfo) -
             }
             Vector::Complexes(info, c)
         }
-        // TODO(goller): create string vector
-        // 8 NOTE: strings are not compressed
-        //
+        8 /* strings; these are never compressed */ => {
+            Vector::Strings(info, strings(data_buf.as_slice(), endian))
+        },
         9 /* u16 */ =>{
             let v = Vec::<u16>::transmute(data_buf, endian);
             Vector::U16(info, v)
@@ -502,10 +623,13 @@ fn new_vector(data_buf: Vec<u8>, class: u16, endian: Endian, info: VectorInfo) -
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn vector<R: Read, T: Handler>(
     header: &Header,
+    common: &Common,
     reader: &mut R,
     handler: &mut T,
+    links: &mut Links,
     buf16: &mut [u8; core::mem::size_of::<u16>()],
     buf32: &mut [u8; core::mem::size_of::<u32>()],
     buf64: &mut [u8; core::mem::size_of::<u64>()],
@@ -520,11 +644,6 @@ pub(crate) fn vector<R: Read, T: Handler>(
     let mut raw_buf = vec![0; len as usize];
     reader.read_exact(&mut raw_buf.as_mut_slice())?;
 
-    let data_buf = match compression {
-        0 | 256 => raw_buf,
-        _ => dec.decompress(&raw_buf, compression, vector_class, num_samples)?,
-    };
-
     let num_dimensions = u32::read_into(buf32, reader, header.endian)?;
     let mut raw_nx = vec![0; num_dimensions as usize * core::mem::size_of::<u64>()];
     reader.read_exact(&mut raw_nx.as_mut_slice())?;
@@ -554,6 +673,7 @@ pub(crate) fn vector<R: Read, T: Handler>(
     }
 
     let unit_y = string(header, reader)?;
+    let next = reference(header, reader, buf16, buf32)?;
 
     let info = VectorInfo {
         name,
@@ -564,11 +684,163 @@ pub(crate) fn vector<R: Read, T: Handler>(
         x_origins,
         unit_x_scale_factors,
         unit_y,
+        compression,
     };
 
-    if let Some(vector) = handler.vector() {
-        let v = new_vector(data_buf, vector_class, header.endian, info);
-        vector(handler, v);
+    let owner = links.channel(common);
+    let tbl = links.table(common);
+    let stat = links.static_data(common);
+    if owner.is_none() && tbl.is_none() && stat.is_none() && handler.vector().is_none() {
+        return Ok(());
+    }
+
+    let data_buf = match compression {
+        0 | 256 => raw_buf,
+        _ => dec.decompress(
+            &raw_buf,
+            compression,
+            vector_class,
+            num_samples,
+            header.endian,
+        )?,
+    };
+    let v = new_vector(data_buf, vector_class, header.endian, info);
+
+    if let Some(mut stat) = stat {
+        if let Some(vector) = handler.vector() {
+            vector(handler, v.clone());
+        }
+        stat.data.data = Some(v);
+        stat.data_ref = Reference::default();
+        if let Some(s) = links.expect_static(stat) {
+            if let Some(handle_static) = handler.static_data() {
+                handle_static(handler, s);
+            }
+        }
+        return Ok(());
+    }
+
+    if let Some(tbl) = tbl {
+        if let Some(vector) = handler.vector() {
+            vector(handler, v.clone());
+        }
+        return column(handler, links, tbl, v, next);
+    }
+
+    match (owner, handler.channel()) {
+        (Some(owner), Some(channel)) => {
+            if let Some(vector) = handler.vector() {
+                vector(handler, v.clone());
+            }
+            channel(handler, owner, v);
+        }
+        _ => {
+            if let Some(vector) = handler.vector() {
+                vector(handler, v);
+            }
+        }
     }
     Ok(())
 }
+
+/// array reads n consecutive numbers.
+fn array<R: Read, T: EndianRead>(
+    header: &Header,
+    reader: &mut R,
+    n: usize,
+) -> std::io::Result<Vec<T>>
+where
+    T::Array: Default,
+{
+    let mut buf = T::Array::default();
+    (0..n)
+        .map(|_| T::read_into(&mut buf, reader, header.endian))
+        .collect()
+}
+
+fn strings_array<R: Read>(
+    header: &Header,
+    reader: &mut R,
+    n: usize,
+) -> std::io::Result<Vec<String>> {
+    (0..n).map(|_| string(header, reader)).collect()
+}
+
+/// toc reads an FrTOC through the serial data section; summaries and
+/// events that follow are not read.
+pub(crate) fn toc<R: Read>(header: &Header, reader: &mut R) -> std::io::Result<Toc> {
+    let mut buf32 = [0; core::mem::size_of::<u32>()];
+    let leap_seconds = array::<_, i16>(header, reader, 1)?[0];
+    let num_frames = u32::read_into(&mut buf32, reader, header.endian)? as usize;
+
+    let data_quality: Vec<u32> = array(header, reader, num_frames)?;
+    let gps_start_time_s: Vec<u32> = array(header, reader, num_frames)?;
+    let gps_residual_time_ns: Vec<u32> = array(header, reader, num_frames)?;
+    let frame_length_s: Vec<f64> = array(header, reader, num_frames)?;
+    let run: Vec<i32> = array(header, reader, num_frames)?;
+    let frame: Vec<u32> = array(header, reader, num_frames)?;
+    let position: Vec<u64> = array(header, reader, num_frames)?;
+    // nFirstADC, nFirstSer, nFirstTable and nFirstMsg
+    array::<_, u64>(header, reader, 4 * num_frames)?;
+
+    let frames = (0..num_frames)
+        .map(|i| TocFrame {
+            run: run[i],
+            frame: frame[i],
+            data_quality: DataQuality(data_quality[i]),
+            gps_start_time_s: gps_start_time_s[i],
+            gps_residual_time_ns: gps_residual_time_ns[i],
+            frame_length_s: frame_length_s[i],
+            position: position[i],
+        })
+        .collect();
+
+    let num_sh = u32::read_into(&mut buf32, reader, header.endian)? as usize;
+    let ids: Vec<u16> = array(header, reader, num_sh)?;
+    let names = strings_array(header, reader, num_sh)?;
+    let dictionary = ids.into_iter().zip(names).collect();
+
+    let num_detectors = u32::read_into(&mut buf32, reader, header.endian)? as usize;
+    strings_array(header, reader, num_detectors)?;
+    array::<_, u64>(header, reader, num_detectors)?;
+
+    let num_stat_types = u32::read_into(&mut buf32, reader, header.endian)? as usize;
+    strings_array(header, reader, 2 * num_stat_types)?;
+    array::<_, u32>(header, reader, num_stat_types)?;
+    let num_stats = u32::read_into(&mut buf32, reader, header.endian)? as usize;
+    // tStart, tEnd and version are INT_4U; positionStat is INT_8U.
+    array::<_, u32>(header, reader, 3 * num_stats)?;
+    array::<_, u64>(header, reader, num_stats)?;
+
+    let mut channels = Vec::new();
+    for kind in [
+        ChannelKind::ADC,
+        ChannelKind::PostProcessed,
+        ChannelKind::Simulation,
+        ChannelKind::Serial,
+    ]
+    .iter()
+    {
+        let n = u32::read_into(&mut buf32, reader, header.endian)? as usize;
+        let names = strings_array(header, reader, n)?;
+        if *kind == ChannelKind::ADC {
+            // channelID and groupID
+            array::<_, u32>(header, reader, 2 * n)?;
+        }
+        let positions: Vec<u64> = array(header, reader, n * num_frames)?;
+        for (i, name) in names.into_iter().enumerate() {
+            channels.push(TocChannel {
+                name,
+                kind: *kind,
+                positions: positions[i * num_frames..(i + 1) * num_frames].to_vec(),
+            });
+        }
+    }
+
+    Ok(Toc {
+        leap_seconds,
+        frames,
+        dictionary,
+        channels,
+    })
+}
@@ -0,0 +1,802 @@
+use crate::cksum::{cksum, Cksum};
+use crate::endian::EndianRead;
+use crate::structures::*;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::io::{Read, Result, Seek, SeekFrom};
+
+/// Problem is something wrong with a file at offset bytes from its start.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Problem {
+    pub offset: u64,
+    pub message: String,
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "byte {}: {}", self.offset, self.message)
+    }
+}
+
+/// verify reads all of reader and returns every problem found: a bad file
+/// header, structures whose length does not match the elements the file's
+/// dictionary describes, classes missing from the dictionary, pointers to
+/// structures that are not in the file, a table of contents that does not
+/// match the structures at its positions, and wrong checksums.  Errors are
+/// only returned when reading fails.
+pub fn verify<R: Read + Seek>(reader: &mut R) -> Result<Vec<Problem>> {
+    let file_len = reader.seek(SeekFrom::End(0))?;
+    reader.seek(SeekFrom::Start(0))?;
+    let mut v = Verifier {
+        problems: Vec::new(),
+        endian: Endian::Little,
+        v8: false,
+        crc: false,
+        dictionary: HashMap::new(),
+        names: HashMap::new(),
+        described: None,
+        structures: HashMap::new(),
+        pointers: Vec::new(),
+        frames: Vec::new(),
+    };
+
+    let mut header = [0; Header::size_of()];
+    if file_len < header.len() as u64 {
+        v.problem(
+            0,
+            format!("{} bytes is too short for a file header", file_len),
+        );
+        return Ok(v.problems);
+    }
+    reader.read_exact(&mut header)?;
+    if !v.header(&header) {
+        return Ok(v.problems);
+    }
+
+    let mut file_sum = Cksum::new();
+    file_sum.update(&header);
+    let mut position = header.len() as u64;
+    let mut end_of_file = None;
+    let common_len = Common::size_of() as u64;
+    while position < file_len {
+        if file_len - position < common_len {
+            v.problem(position, "structure header runs past the end of the file");
+            break;
+        }
+        let mut common = [0; Common::size_of()];
+        reader.read_exact(&mut common)?;
+        let length = u64::from_bytes(&common[0..8], v.endian);
+        let class = match v.v8 {
+            true => common[9] as u16,
+            false => u16::from_bytes(&common[8..10], v.endian),
+        };
+        let instance = u32::from_bytes(&common[10..14], v.endian);
+        let minimum = common_len + if v.v8 { 4 } else { 0 };
+        if length < minimum {
+            v.problem(
+                position,
+                format!("length {} is shorter than a structure", length),
+            );
+            break;
+        }
+        if length > file_len - position {
+            v.problem(
+                position,
+                format!(
+                    "length {} runs past the end of the file at byte {}",
+                    length, file_len
+                ),
+            );
+            break;
+        }
+
+        let mut buf = vec![0; length as usize];
+        buf[..common.len()].copy_from_slice(&common);
+        reader.read_exact(&mut buf[common.len()..])?;
+        let structure = v.structure(position, class, instance, &buf);
+        if structure == Some(Structures::FrEndOfFile) {
+            end_of_file = Some((position, buf));
+            position += length;
+            break;
+        }
+        file_sum.update(&buf);
+        position += length;
+    }
+
+    match end_of_file {
+        Some((offset, buf)) => {
+            if position != file_len {
+                v.problem(
+                    position,
+                    format!("{} bytes after the end of file", file_len - position),
+                );
+            }
+            if v.v8 {
+                v.end_of_file(offset, &buf, &header, file_sum, file_len);
+                v.toc(reader, offset, &buf, file_len)?;
+            }
+        }
+        None => v.problem(position, "no end of file structure"),
+    }
+    v.dangling();
+    Ok(v.problems)
+}
+
+/// Element is one element of a structure as described by an FrSE.
+struct Element {
+    name: String,
+    kind: Kind,
+    /// dimensions multiply into the number of values: numbers or the names
+    /// of earlier elements.
+    dimensions: Vec<String>,
+}
+
+#[derive(Copy, Clone, PartialEq)]
+enum Kind {
+    Integer(usize),
+    Real(usize),
+    String,
+    Pointer,
+}
+
+impl Element {
+    /// parse reads element types such as `INT_8U[nADC][nFrame]`; types it
+    /// does not know return None.
+    fn parse(name: &str, kind: &str) -> Option<Element> {
+        let kind = kind.trim();
+        let (base, rest) = match kind.find('[') {
+            Some(i) => (&kind[..i], &kind[i..]),
+            None => (kind, ""),
+        };
+        let kind = match base.trim() {
+            "CHAR" | "CHAR_U" => Kind::Integer(1),
+            "INT_2S" | "INT_2U" => Kind::Integer(2),
+            "INT_4S" | "INT_4U" => Kind::Integer(4),
+            "INT_8S" | "INT_8U" => Kind::Integer(8),
+            "REAL_4" => Kind::Real(4),
+            "REAL_8" | "COMPLEX_8" => Kind::Real(8),
+            "COMPLEX_16" => Kind::Real(16),
+            "STRING" => Kind::String,
+            b if b.starts_with("PTR_STRUCT") => Kind::Pointer,
+            _ => return None,
+        };
+        let mut dimensions = Vec::new();
+        for d in rest.split(']') {
+            let d = d.trim();
+            if d.is_empty() {
+                continue;
+            }
+            let d = d.strip_prefix('[')?;
+            dimensions.extend(d.split('*').map(|d| d.trim().to_string()));
+        }
+        Some(Element {
+            name: name.to_string(),
+            kind,
+            dimensions,
+        })
+    }
+}
+
+/// builtin are the elements of FrSH and FrSE, which describe the others.
+fn builtin(class: u16, v8: bool) -> Vec<Element> {
+    let mut elements = vec![
+        ("name", "STRING"),
+        ("class", "INT_2U"),
+        ("comment", "STRING"),
+    ];
+    if class == 2 {
+        elements[1].1 = "STRING";
+    }
+    if v8 {
+        elements.push(("chkSum", "INT_4U"));
+    }
+    elements
+        .iter()
+        .filter_map(|(name, kind)| Element::parse(name, kind))
+        .collect()
+}
+
+/// Decoded is what verify keeps of a structure's elements.
+#[derive(Default)]
+struct Decoded {
+    /// integers and strings are the last value of each element.
+    integers: HashMap<String, u64>,
+    strings: HashMap<String, String>,
+    pointers: Vec<(String, u16, u32)>,
+    /// checksum is the offset in the structure of the chkSum element.
+    checksum: Option<usize>,
+}
+
+struct Verifier {
+    problems: Vec<Problem>,
+    endian: Endian,
+    v8: bool,
+    /// crc is set when the file header says checksums are CRCs.
+    crc: bool,
+    /// dictionary is the elements of each class, None when their types are
+    /// not understood.
+    dictionary: HashMap<u16, Option<Vec<Element>>>,
+    names: HashMap<u16, String>,
+    /// described is the class of the last FrSH, which the FrSEs after it describe.
+    described: Option<u16>,
+    /// structures are the class, instance and name element of the structure
+    /// at each offset.
+    structures: HashMap<u64, (u16, u32, Option<String>)>,
+    /// pointers are the offset of the structure, the element, class and
+    /// instance of every pointer.
+    pointers: Vec<(u64, String, u16, u32)>,
+    /// frames are the offset, GTimeS and GTimeN of every FrameH.
+    frames: Vec<(u64, Option<u64>, Option<u64>)>,
+}
+
+impl Verifier {
+    fn problem<S: Into<String>>(&mut self, offset: u64, message: S) {
+        self.problems.push(Problem {
+            offset,
+            message: message.into(),
+        });
+    }
+
+    /// header checks the file header, returning false when the rest of the
+    /// file cannot be read.
+    fn header(&mut self, h: &[u8; Header::size_of()]) -> bool {
+        if h[..5] != *b"IGWD\0" {
+            self.problem(0, format!("bad magic {:?}", &h[..5]));
+            return false;
+        }
+        match h[5] {
+            8 => self.v8 = true,
+            6 => self.v8 = false,
+            v => {
+                self.problem(5, format!("version {} is not supported", v));
+                return false;
+            }
+        }
+        self.endian = match [h[12], h[13]] {
+            [0x12, 0x34] => Endian::Big,
+            [0x34, 0x12] => Endian::Little,
+            b => {
+                self.problem(12, format!("bad byte order pattern {:02x?}", b));
+                return false;
+            }
+        };
+        let sizes = [2, 4, 8, 4, 8];
+        if h[7..12] != sizes {
+            self.problem(7, format!("sizes {:?} are not {:?}", &h[7..12], sizes));
+        }
+        if u32::from_bytes(&h[14..18], self.endian) != 0x1234_5678 {
+            self.problem(14, "bad 4 byte integer pattern");
+        }
+        if u64::from_bytes(&h[18..26], self.endian) != 0x0123_4567_89ab_cdef {
+            self.problem(18, "bad 8 byte integer pattern");
+        }
+        if f32::from_bytes(&h[26..30], self.endian) != std::f32::consts::PI {
+            self.problem(26, "bad 4 byte float pattern");
+        }
+        if f64::from_bytes(&h[30..38], self.endian) != std::f64::consts::PI {
+            self.problem(30, "bad 8 byte float pattern");
+        }
+        match h[39] {
+            0 => {}
+            1 => self.crc = true,
+            c => self.problem(39, format!("unknown checksum type {}", c)),
+        }
+        true
+    }
+
+    /// structure checks one structure and returns its class.
+    fn structure(
+        &mut self,
+        offset: u64,
+        class: u16,
+        instance: u32,
+        buf: &[u8],
+    ) -> Option<Structures> {
+        let name = self.names.get(&class).cloned();
+        let structure = match class {
+            1 => Some(Structures::FrSH),
+            2 => Some(Structures::FrSE),
+            _ => name.as_deref().and_then(|n| n.parse().ok()),
+        };
+        let elements = match class {
+            1 | 2 => Some(builtin(class, self.v8)),
+            _ => match self.dictionary.remove(&class) {
+                Some(elements) => elements,
+                None => {
+                    self.problem(offset, format!("class {} is not in the dictionary", class));
+                    None
+                }
+            },
+        };
+        let decoded = match &elements {
+            Some(elements) => match self.decode(buf, elements) {
+                Ok(d) => d,
+                Err(e) => {
+                    self.problem(
+                        offset,
+                        format!("{} of length {}: {}", label(&name, class), buf.len(), e),
+                    );
+                    None
+                }
+            },
+            None => None,
+        };
+        if class > 2 {
+            if let Some(elements) = elements {
+                self.dictionary.insert(class, Some(elements));
+            } else if name.is_some() {
+                self.dictionary.insert(class, None);
+            }
+        }
+
+        if self.v8 {
+            self.checksum(offset, buf, structure, &decoded, &name, class);
+        }
+        let d = decoded.unwrap_or_default();
+        match class {
+            1 => {
+                if let (Some(n), Some(id)) = (d.strings.get("name"), d.integers.get("class")) {
+                    let id = *id as u16;
+                    self.names.insert(id, n.clone());
+                    self.dictionary.insert(id, Some(Vec::new()));
+                    self.described = Some(id);
+                }
+            }
+            2 => {
+                if let Some(id) = self.described {
+                    let element = match (d.strings.get("name"), d.strings.get("class")) {
+                        (Some(n), Some(k)) => Element::parse(n, k),
+                        _ => None,
+                    };
+                    let elements = self.dictionary.get_mut(&id).and_then(|e| e.as_mut());
+                    match (elements, element) {
+                        (Some(elements), Some(element)) => elements.push(element),
+                        // elements of types verify does not know are not checked.
+                        _ => {
+                            self.dictionary.insert(id, None);
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+        if structure == Some(Structures::FrameH) {
+            self.frames.push((
+                offset,
+                d.integers.get("GTimeS").copied(),
+                d.integers.get("GTimeN").copied(),
+            ));
+        }
+        for (element, class, instance) in d.pointers {
+            self.pointers.push((offset, element, class, instance));
+        }
+        self.structures
+            .insert(offset, (class, instance, d.strings.get("name").cloned()));
+        structure
+    }
+
+    /// decode reads the elements of a structure, returning None when they
+    /// cannot all be sized, e.g. for arrays whose length is not an element.
+    fn decode(
+        &self,
+        buf: &[u8],
+        elements: &[Element],
+    ) -> std::result::Result<Option<Decoded>, String> {
+        let mut d = Decoded::default();
+        let mut at = Common::size_of();
+        let overrun = |e: &Element| format!("element {} runs past the end", e.name);
+        for e in elements {
+            let mut count: u64 = 1;
+            for dim in e.dimensions.iter() {
+                let n = match dim.parse::<u64>() {
+                    Ok(n) => n,
+                    Err(_) => match d.integers.get(dim) {
+                        Some(n) => *n,
+                        None => return Ok(None),
+                    },
+                };
+                count = count.saturating_mul(n);
+            }
+            let fixed = match e.kind {
+                Kind::Integer(n) | Kind::Real(n) => Some(n as u64),
+                Kind::Pointer => Some(6),
+                Kind::String => None,
+            };
+            if let Some(size) = fixed {
+                let len = count.saturating_mul(size);
+                if len > (buf.len() - at) as u64 {
+                    return Err(overrun(e));
+                }
+                let len = len as usize;
+                if e.name == "chkSum" {
+                    d.checksum = Some(at);
+                }
+                if count == 1 {
+                    let b = &buf[at..at + len];
+                    match e.kind {
+                        Kind::Integer(1) => {
+                            d.integers.insert(e.name.clone(), b[0] as u64);
+                        }
+                        Kind::Integer(2) => {
+                            d.integers
+                                .insert(e.name.clone(), u16::from_bytes(b, self.endian) as u64);
+                        }
+                        Kind::Integer(4) => {
+                            d.integers
+                                .insert(e.name.clone(), u32::from_bytes(b, self.endian) as u64);
+                        }
+                        Kind::Integer(_) => {
+                            d.integers
+                                .insert(e.name.clone(), u64::from_bytes(b, self.endian));
+                        }
+                        Kind::Pointer => d.pointers.push((
+                            e.name.clone(),
+                            u16::from_bytes(&b[..2], self.endian),
+                            u32::from_bytes(&b[2..], self.endian),
+                        )),
+                        _ => {}
+                    }
+                }
+                at += len;
+                continue;
+            }
+            for _ in 0..count {
+                if buf.len() - at < 2 {
+                    return Err(overrun(e));
+                }
+                let len = u16::from_bytes(&buf[at..at + 2], self.endian) as usize;
+                at += 2;
+                if buf.len() - at < len {
+                    return Err(overrun(e));
+                }
+                let s = &buf[at..at + len];
+                let s = s.strip_suffix(&[0]).unwrap_or(s);
+                d.strings
+                    .insert(e.name.clone(), String::from_utf8_lossy(s).into_owned());
+                at += len;
+            }
+        }
+
+        // a structure checksum that the dictionary leaves out ends the structure.
+        let left = buf.len() - at;
+        if self.v8 && d.checksum.is_none() && left == 4 {
+            d.checksum = Some(at);
+        } else if left != 0 {
+            return Err(format!("{} bytes after the last element", left));
+        }
+        Ok(Some(d))
+    }
+
+    /// checksum checks the CRC of a version 8 structure.
+    fn checksum(
+        &mut self,
+        offset: u64,
+        buf: &[u8],
+        structure: Option<Structures>,
+        decoded: &Option<Decoded>,
+        name: &Option<String>,
+        class: u16,
+    ) {
+        match buf[8] {
+            0 => return,
+            1 => {}
+            t => {
+                self.problem(offset + 8, format!("unknown checksum type {}", t));
+                return;
+            }
+        }
+        let at = match (decoded.as_ref().and_then(|d| d.checksum), structure) {
+            (Some(at), _) => at,
+            (None, Some(Structures::FrEndOfFile)) => buf.len() - 8,
+            (None, _) => buf.len() - 4,
+        };
+        let stored = u32::from_bytes(&buf[at..at + 4], self.endian);
+        let sum = cksum(&buf[..at]);
+        if stored != sum {
+            self.problem(
+                offset + at as u64,
+                format!(
+                    "{} checksum is {:#010x}, the contents sum to {:#010x}",
+                    label(name, class),
+                    stored,
+                    sum
+                ),
+            );
+        }
+    }
+
+    /// end_of_file checks the counts and file checksums of a version 8 FrEndOfFile.
+    fn end_of_file(
+        &mut self,
+        offset: u64,
+        buf: &[u8],
+        header: &[u8],
+        mut file_sum: Cksum,
+        file_len: u64,
+    ) {
+        if buf.len() < 46 {
+            return;
+        }
+        let frames = u32::from_bytes(&buf[14..18], self.endian) as usize;
+        if frames != self.frames.len() {
+            self.problem(
+                offset + 14,
+                format!("nFrames is {}, the file has {}", frames, self.frames.len()),
+            );
+        }
+        let bytes = u64::from_bytes(&buf[18..26], self.endian);
+        if bytes != file_len {
+            self.problem(
+                offset + 18,
+                format!("nBytes is {}, the file has {}", bytes, file_len),
+            );
+        }
+        if !self.crc {
+            return;
+        }
+        let stored = u32::from_bytes(&buf[34..38], self.endian);
+        let sum = cksum(header);
+        if stored != 0 && stored != sum {
+            self.problem(
+                offset + 34,
+                format!(
+                    "file header checksum is {:#010x}, the header sums to {:#010x}",
+                    stored, sum
+                ),
+            );
+        }
+        let stored = u32::from_bytes(&buf[42..46], self.endian);
+        file_sum.update(&buf[..42]);
+        let sum = file_sum.sum();
+        if stored != 0 && stored != sum {
+            self.problem(
+                offset + 42,
+                format!(
+                    "file checksum is {:#010x}, the file sums to {:#010x}",
+                    stored, sum
+                ),
+            );
+        }
+    }
+
+    /// toc checks that the table of contents points at the structures it describes.
+    fn toc<R: Read + Seek>(
+        &mut self,
+        reader: &mut R,
+        offset: u64,
+        buf: &[u8],
+        file_len: u64,
+    ) -> Result<()> {
+        if buf.len() < 34 {
+            return Ok(());
+        }
+        let seek = u64::from_bytes(&buf[26..34], self.endian);
+        if seek == 0 {
+            return Ok(());
+        }
+        let position = file_len.wrapping_sub(seek);
+        if self.class_at(position) != Some(Structures::FrTOC) {
+            self.problem(
+                offset + 26,
+                format!("seekTOC points to byte {}, which is not an FrTOC", position),
+            );
+            return Ok(());
+        }
+        let toc = match crate::toc::read(reader) {
+            Ok(Some(toc)) => toc,
+            Ok(None) => return Ok(()),
+            Err(e) => {
+                self.problem(position, format!("FrTOC: {}", e));
+                return Ok(());
+            }
+        };
+
+        if toc.frames.len() != self.frames.len() {
+            self.problem(
+                position,
+                format!(
+                    "FrTOC has {} frames, the file has {}",
+                    toc.frames.len(),
+                    self.frames.len()
+                ),
+            );
+        }
+        for (i, f) in toc.frames.iter().enumerate() {
+            let frame = self.frames.iter().find(|(o, _, _)| *o == f.position);
+            match frame {
+                None => self.problem(
+                    position,
+                    format!(
+                        "FrTOC frame {} is at byte {}, which is not an FrameH",
+                        i, f.position
+                    ),
+                ),
+                Some((_, s, ns)) => {
+                    if *s != Some(f.gps_start_time_s as u64)
+                        || *ns != Some(f.gps_residual_time_ns as u64)
+                    {
+                        self.problem(
+                            position,
+                            format!(
+                                "FrTOC frame {} starts at {}.{:09}, its FrameH does not",
+                                i, f.gps_start_time_s, f.gps_residual_time_ns
+                            ),
+                        );
+                    }
+                }
+            }
+        }
+        for c in toc.channels.iter() {
+            let (want, label) = match c.kind {
+                ChannelKind::ADC => (Structures::FrAdcData, "FrAdcData"),
+                ChannelKind::PostProcessed => (Structures::FrProcData, "FrProcData"),
+                ChannelKind::Simulation => (Structures::FrSimData, "FrSimData"),
+                ChannelKind::Serial => (Structures::FrSerData, "FrSerData"),
+            };
+            for p in c.positions.iter().filter(|p| **p != 0) {
+                let name = self.structures.get(p).and_then(|s| s.2.as_deref());
+                if self.class_at(*p) != Some(want) || name != Some(c.name.as_str()) {
+                    self.problem(
+                        position,
+                        format!(
+                            "FrTOC puts {} {} at byte {}, which is not it",
+                            label, c.name, p
+                        ),
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn class_at(&self, offset: u64) -> Option<Structures> {
+        let (class, _, _) = self.structures.get(&offset)?;
+        match class {
+            1 => Some(Structures::FrSH),
+            2 => Some(Structures::FrSE),
+            c => self.names.get(c)?.parse().ok(),
+        }
+    }
+
+    /// dangling reports pointers to structures that are not in the file.
+    fn dangling(&mut self) {
+        let targets: HashSet<(u16, u32)> = self
+            .structures
+            .values()
+            .map(|(class, instance, _)| (*class, *instance))
+            .collect();
+        let pointers = std::mem::take(&mut self.pointers);
+        for (offset, element, class, instance) in pointers {
+            if class != 0 && !targets.contains(&(class, instance)) {
+                let name = self.names.get(&class).cloned();
+                self.problem(
+                    offset,
+                    format!(
+                        "{} points to {} instance {}, which is not in the file",
+                        element,
+                        label(&name, class),
+                        instance
+                    ),
+                );
+            }
+        }
+    }
+}
+
+/// label names a class for messages.
+fn label(name: &Option<String>, class: u16) -> String {
+    match name {
+        Some(n) => n.clone(),
+        None => format!("class {}", class),
+    }
+}
+
+#[cfg(test)]
+mod verify_tests {
+    use super::*;
+    use crate::fixtures::file;
+    use std::convert::TryInto;
+    use std::io::Cursor;
+
+    fn problems(buf: &[u8]) -> Vec<String> {
+        verify(&mut Cursor::new(buf))
+            .unwrap()
+            .iter()
+            .map(|p| p.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_verify() {
+        let buf = file(2, &["H1:ADC"]);
+        assert_eq!(problems(&buf), Vec::<String>::new());
+
+        // rename the first ADC: its checksum, the file checksum and the
+        // table of contents are all wrong.
+        let mut bad = buf.clone();
+        let at = bad.windows(6).position(|w| w == b"H1:ADC").unwrap();
+        bad[at + 3] = b'X';
+        let p = problems(&bad);
+        assert_eq!(p.len(), 3, "{:?}", p);
+        assert!(p[0].contains("FrAdcData checksum"), "{:?}", p);
+        assert!(p[1].contains("file checksum"), "{:?}", p);
+        assert!(p[2].contains("FrTOC puts FrAdcData H1:ADC"), "{:?}", p);
+
+        let mut bad = buf.clone();
+        bad[14] ^= 1;
+        let p = problems(&bad);
+        assert_eq!(p[0], "byte 14: bad 4 byte integer pattern");
+        assert!(p[1].contains("file header checksum"), "{:?}", p);
+
+        let p = problems(&buf[..buf.len() - 10]);
+        assert!(p[0].contains("runs past the end of the file"), "{:?}", p);
+        assert!(p[1].contains("no end of file structure"), "{:?}", p);
+    }
+
+    #[test]
+    fn test_lengths() {
+        // grow the FrameH by a byte: its elements no longer fill it and the
+        // walk continues one byte late.
+        let mut buf = file(2, &["H1:ADC"]);
+        let toc = crate::toc::read(&mut Cursor::new(&buf)).unwrap().unwrap();
+        let at = toc.frames[0].position as usize;
+        let len = u64::from_le_bytes(buf[at..at + 8].try_into().unwrap());
+        buf[at..at + 8].copy_from_slice(&(len + 1).to_le_bytes());
+        let p = problems(&buf);
+        assert!(
+            p[0].starts_with(&format!("byte {}: FrameH of length {}", at, len + 1)),
+            "{:?}",
+            p
+        );
+    }
+
+    #[test]
+    fn test_corruption() {
+        let buf = file(2, &["H1:ADC"]);
+        let toc = crate::toc::read(&mut Cursor::new(&buf)).unwrap().unwrap();
+        let frame = toc.frames[1].position as usize;
+        let adc = toc.channels[0].positions[0] as usize;
+        let length = |at: usize| u64::from_le_bytes(buf[at..at + 8].try_into().unwrap()) as usize;
+
+        // give the second FrameH a class no FrSH describes.
+        let mut bad = buf.clone();
+        bad[frame + 9] = 99;
+        let p = problems(&bad);
+        let want = format!("byte {}: class 99 is not in the dictionary", frame);
+        assert!(p.contains(&want), "{:?}", p);
+
+        // renumber the FrVect following the first ADC, leaving its data
+        // pointer dangling.
+        let mut bad = buf.clone();
+        let vect = adc + length(adc);
+        bad[vect + 10..vect + 14].copy_from_slice(&99u32.to_le_bytes());
+        let p = problems(&bad);
+        assert!(
+            p.iter().any(
+                |p| p.starts_with(&format!("byte {}: data points to FrVect", adc))
+                    && p.ends_with("which is not in the file")
+            ),
+            "{:?}",
+            p
+        );
+
+        // move the second FrameH's GTimeS, after its name "TEST", run, frame
+        // and dataQuality, a second later than the FrTOC says.
+        let mut bad = buf.clone();
+        let at = frame + Common::size_of() + 2 + 5 + 12;
+        bad[at..at + 4].copy_from_slice(&1_000_000_002u32.to_le_bytes());
+        let p = problems(&bad);
+        let want = "FrTOC frame 1 starts at 1000000001.000000000, its FrameH does not";
+        assert!(p.iter().any(|p| p.ends_with(want)), "{:?}", p);
+    }
+
+    #[test]
+    fn test_element() {
+        let e = Element::parse("positionADC", "INT_8U[nADC][nFrame]").unwrap();
+        assert!(e.kind == Kind::Integer(8));
+        assert_eq!(e.dimensions, vec!["nADC", "nFrame"]);
+        assert!(Element::parse("x", "PTR_STRUCT(FrVect *)").unwrap().kind == Kind::Pointer);
+        assert!(Element::parse("x", "FLOAT").is_none());
+    }
+}
@@ -0,0 +1,350 @@
+use crate::timeseries::{Samples, TimeSeries};
+use std::f64::consts::PI;
+use std::io::{Error, ErrorKind, Result, Write};
+use std::str::FromStr;
+
+/// Encoding is the sample format of a WAV file.
+#[derive(Debug, PartialEq, Copy, Clone, Default)]
+pub enum Encoding {
+    #[default]
+    Pcm16,
+    Pcm24,
+    Pcm32,
+    Float32,
+}
+
+impl Encoding {
+    fn bytes(self) -> u16 {
+        match self {
+            Encoding::Pcm16 => 2,
+            Encoding::Pcm24 => 3,
+            Encoding::Pcm32 | Encoding::Float32 => 4,
+        }
+    }
+}
+
+impl FromStr for Encoding {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Encoding> {
+        match s {
+            "pcm16" => Ok(Encoding::Pcm16),
+            "pcm24" => Ok(Encoding::Pcm24),
+            "pcm32" => Ok(Encoding::Pcm32),
+            "float32" => Ok(Encoding::Float32),
+            _ => Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("unknown WAV encoding: {}", s),
+            )),
+        }
+    }
+}
+
+/// Wav writes real time series as mono WAV files.
+///
+/// PCM samples are clipped to [-1, 1] of full scale, so physical units such
+/// as strain need normalising to be heard; float samples are written as
+/// they are.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Wav {
+    encoding: Encoding,
+    playback_rate: Option<u32>,
+    band: Option<(f64, f64)>,
+    normalize: bool,
+}
+
+impl Wav {
+    pub fn new(encoding: Encoding) -> Self {
+        Wav {
+            encoding,
+            ..Wav::default()
+        }
+    }
+
+    /// playback_rate sets the sample rate the file declares.  Every sample
+    /// is kept, so a rate above the series' plays it faster and higher.
+    /// By default the series' rate is used.
+    pub fn playback_rate(&mut self, hz: u32) {
+        self.playback_rate = Some(hz);
+    }
+
+    /// band keeps frequencies between low_hz and high_hz of the series with
+    /// a zero phase, eighth order Butterworth band pass.
+    pub fn band(&mut self, low_hz: f64, high_hz: f64) {
+        self.band = Some((low_hz, high_hz));
+    }
+
+    /// normalize scales the samples to a peak of full scale.
+    pub fn normalize(&mut self, normalize: bool) {
+        self.normalize = normalize;
+    }
+
+    /// write writes the series as a WAV file.
+    pub fn write<W: Write>(&self, out: &mut W, series: &TimeSeries) -> Result<()> {
+        let mut samples = match &series.samples {
+            Samples::Real(v) => v.clone(),
+            Samples::Complex(_) => {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("{}: complex samples cannot be played", series.name),
+                ))
+            }
+        };
+        let rate = match self.playback_rate {
+            Some(r) => r,
+            None => series.sample_rate.round() as u32,
+        };
+        if rate == 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("invalid playback rate: {} Hz", rate),
+            ));
+        }
+        let bytes = self.encoding.bytes();
+        let byte_rate = rate.checked_mul(bytes as u32).ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                format!("playback rate {} Hz is too high for a WAV file", rate),
+            )
+        })?;
+        if let Some((low, high)) = self.band {
+            let nyquist = series.sample_rate / 2.0;
+            if !(low > 0.0 && low < high && high < nyquist) {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!(
+                        "{}: band {} to {} Hz is not within 0 to {} Hz",
+                        series.name, low, high, nyquist
+                    ),
+                ));
+            }
+            band_pass(&mut samples, series.sample_rate, low, high);
+        }
+        if self.normalize {
+            let peak = samples.iter().fold(0.0f64, |p, x| p.max(x.abs()));
+            if peak > 0.0 {
+                samples.iter_mut().for_each(|x| *x /= peak);
+            }
+        }
+
+        let data_len = samples.len() as u64 * bytes as u64;
+        let float = self.encoding == Encoding::Float32;
+        // float files carry an empty extension and a fact chunk.
+        let fmt_len: u32 = if float { 18 } else { 16 };
+        let fact_len: u32 = if float { 12 } else { 0 };
+        let riff_len = 4 + 8 + fmt_len as u64 + fact_len as u64 + 8 + data_len;
+        if riff_len > u32::MAX as u64 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("{}: too many samples for a WAV file", series.name),
+            ));
+        }
+
+        let mut header = Vec::with_capacity(58);
+        header.extend_from_slice(b"RIFF");
+        header.extend_from_slice(&(riff_len as u32).to_le_bytes());
+        header.extend_from_slice(b"WAVEfmt ");
+        header.extend_from_slice(&fmt_len.to_le_bytes());
+        header.extend_from_slice(&(if float { 3u16 } else { 1u16 }).to_le_bytes());
+        header.extend_from_slice(&1u16.to_le_bytes()); // channels
+        header.extend_from_slice(&rate.to_le_bytes());
+        header.extend_from_slice(&byte_rate.to_le_bytes());
+        header.extend_from_slice(&bytes.to_le_bytes());
+        header.extend_from_slice(&(bytes * 8).to_le_bytes());
+        if float {
+            header.extend_from_slice(&0u16.to_le_bytes());
+            header.extend_from_slice(b"fact");
+            header.extend_from_slice(&4u32.to_le_bytes());
+            header.extend_from_slice(&(samples.len() as u32).to_le_bytes());
+        }
+        header.extend_from_slice(b"data");
+        header.extend_from_slice(&(data_len as u32).to_le_bytes());
+        out.write_all(&header)?;
+
+        let mut data = Vec::with_capacity(data_len as usize);
+        for x in samples {
+            match self.encoding {
+                Encoding::Pcm16 => data.extend_from_slice(&(pcm(x, 15) as i16).to_le_bytes()),
+                Encoding::Pcm24 => data.extend_from_slice(&pcm(x, 23).to_le_bytes()[..3]),
+                Encoding::Pcm32 => data.extend_from_slice(&(pcm(x, 31) as i32).to_le_bytes()),
+                Encoding::Float32 => data.extend_from_slice(&(x as f32).to_le_bytes()),
+            }
+        }
+        out.write_all(&data)
+    }
+}
+
+/// pcm scales x in [-1, 1] to a signed integer of bits plus a sign bit.
+fn pcm(x: f64, bits: u32) -> i64 {
+    let full = ((1i64 << bits) - 1) as f64;
+    (x.clamp(-1.0, 1.0) * full).round() as i64
+}
+
+/// band_pass filters the samples forward and backward through fourth order
+/// Butterworth high and low passes.  The mean is removed and the ends are
+/// tapered first so the filters start quietly.
+fn band_pass(samples: &mut [f64], sample_rate: f64, low_hz: f64, high_hz: f64) {
+    let n = samples.len();
+    if n == 0 {
+        return;
+    }
+    let mean = samples.iter().sum::<f64>() / n as f64;
+    let taper = ((0.1 * sample_rate) as usize).min(n / 10);
+    for (i, x) in samples.iter_mut().enumerate() {
+        *x -= mean;
+        let edge = i.min(n - 1 - i);
+        if edge < taper {
+            *x *= 0.5 - 0.5 * (PI * edge as f64 / taper as f64).cos();
+        }
+    }
+
+    // the quality factors of the two sections of a fourth order Butterworth filter.
+    const Q: [f64; 2] = [0.541_196_100_146_197, 1.306_562_964_876_376_5];
+    let mut filters = Vec::with_capacity(4);
+    for q in Q.iter() {
+        filters.push(Biquad::high_pass(low_hz / sample_rate, *q));
+        filters.push(Biquad::low_pass(high_hz / sample_rate, *q));
+    }
+    for f in filters.iter() {
+        f.run(samples.iter_mut());
+        f.run(samples.iter_mut().rev());
+    }
+}
+
+/// Biquad is a second order section with coefficients normalised by a0.
+struct Biquad {
+    b: [f64; 3],
+    a: [f64; 2],
+}
+
+impl Biquad {
+    /// high_pass and low_pass take the corner frequency as a fraction of the sample rate.
+    fn high_pass(f: f64, q: f64) -> Biquad {
+        let (cos, alpha) = Biquad::angle(f, q);
+        Biquad::new(
+            [(1.0 + cos) / 2.0, -(1.0 + cos), (1.0 + cos) / 2.0],
+            [1.0 + alpha, -2.0 * cos, 1.0 - alpha],
+        )
+    }
+
+    fn low_pass(f: f64, q: f64) -> Biquad {
+        let (cos, alpha) = Biquad::angle(f, q);
+        Biquad::new(
+            [(1.0 - cos) / 2.0, 1.0 - cos, (1.0 - cos) / 2.0],
+            [1.0 + alpha, -2.0 * cos, 1.0 - alpha],
+        )
+    }
+
+    fn angle(f: f64, q: f64) -> (f64, f64) {
+        let w = 2.0 * PI * f;
+        (w.cos(), w.sin() / (2.0 * q))
+    }
+
+    fn new(b: [f64; 3], a: [f64; 3]) -> Biquad {
+        Biquad {
+            b: [b[0] / a[0], b[1] / a[0], b[2] / a[0]],
+            a: [a[1] / a[0], a[2] / a[0]],
+        }
+    }
+
+    /// run filters the samples in place in the order given.
+    fn run<'a>(&self, samples: impl Iterator<Item = &'a mut f64>) {
+        let (mut s1, mut s2) = (0.0, 0.0);
+        for x in samples {
+            let y = self.b[0] * *x + s1;
+            s1 = self.b[1] * *x - self.a[0] * y + s2;
+            s2 = self.b[2] * *x - self.a[1] * y;
+            *x = y;
+        }
+    }
+}
+
+#[cfg(test)]
+mod wav_tests {
+    use super::*;
+
+    fn series(samples: Vec<f64>, sample_rate: f64) -> TimeSeries {
+        TimeSeries {
+            name: "H1:STRAIN".to_string(),
+            gps_start_s: 1e9,
+            sample_rate,
+            unit: "strain".to_string(),
+            samples: Samples::Real(samples),
+        }
+    }
+
+    #[test]
+    fn test_write_pcm() -> Result<()> {
+        let mut wav = Wav::new(Encoding::Pcm16);
+        wav.normalize(true);
+        wav.playback_rate(8000);
+        let mut out = Vec::new();
+        wav.write(&mut out, &series(vec![0.0, 1e-21, -2e-21], 4096.0))?;
+        assert_eq!(&out[..4], b"RIFF");
+        assert_eq!(u32::from_le_bytes([out[4], out[5], out[6], out[7]]), 36 + 6);
+        assert_eq!(&out[8..16], b"WAVEfmt ");
+        assert_eq!(u16::from_le_bytes([out[20], out[21]]), 1);
+        assert_eq!(
+            u32::from_le_bytes([out[24], out[25], out[26], out[27]]),
+            8000
+        );
+        assert_eq!(&out[36..40], b"data");
+        let samples: Vec<i16> = out[44..]
+            .chunks(2)
+            .map(|c| i16::from_le_bytes([c[0], c[1]]))
+            .collect();
+        assert_eq!(samples, vec![0, 16384, -32767]);
+
+        let mut out = Vec::new();
+        Wav::new(Encoding::Pcm24).write(&mut out, &series(vec![2.0, -1.0], 16.0))?;
+        assert_eq!(u32::from_le_bytes([out[24], out[25], out[26], out[27]]), 16);
+        assert_eq!(&out[44..], &[0xff, 0xff, 0x7f, 0x01, 0x00, 0x80]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_playback_rate() {
+        let mut wav = Wav::new(Encoding::Float32);
+        wav.playback_rate(u32::MAX / 2);
+        let err = wav
+            .write(&mut Vec::new(), &series(vec![0.0], 16.0))
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn test_write_float() -> Result<()> {
+        let mut out = Vec::new();
+        Wav::new(Encoding::Float32).write(&mut out, &series(vec![1e-21], 16384.0))?;
+        assert_eq!(u16::from_le_bytes([out[20], out[21]]), 3);
+        assert_eq!(&out[38..42], b"fact");
+        assert_eq!(&out[50..54], b"data");
+        assert_eq!(&out[58..], &1e-21f32.to_le_bytes());
+        Ok(())
+    }
+
+    #[test]
+    fn test_band() -> Result<()> {
+        let rate = 1024.0;
+        let tone = |f: f64| -> Vec<f64> {
+            (0..4096)
+                .map(|i| (2.0 * PI * f * i as f64 / rate).sin())
+                .collect()
+        };
+        let power = |v: &[f64]| v[1024..3072].iter().map(|x| x * x).sum::<f64>();
+        for (f, kept) in [(5.0, false), (100.0, true), (400.0, false)].iter() {
+            let mut v = tone(*f);
+            let before = power(&v);
+            band_pass(&mut v, rate, 50.0, 200.0);
+            let ratio = power(&v) / before;
+            assert_eq!(ratio > 0.9, *kept, "{} Hz kept {}", f, ratio);
+            assert!(!(0.01..=0.9).contains(&ratio), "{} Hz kept {}", f, ratio);
+        }
+
+        let mut wav = Wav::new(Encoding::Float32);
+        wav.band(50.0, 600.0);
+        assert!(wav
+            .write(&mut Vec::new(), &series(tone(10.0), rate))
+            .is_err());
+        Ok(())
+    }
+}
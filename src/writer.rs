@@ -0,0 +1,1309 @@
+use crate::cksum::{cksum, Cksum};
+use crate::compress::compress;
+use crate::structures::*;
+use crate::toc::TocFrame;
+use std::collections::HashMap;
+use std::io::{Error, ErrorKind, Result, Write};
+use std::str::FromStr;
+
+/// Frame is everything the writer stores for one frame.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Frame {
+    pub header: FrameHeader,
+    pub detectors: Vec<Detector>,
+    pub history: Vec<History>,
+    /// raw names the FrRawData holding the ADC and serial channels.
+    pub raw: Option<RawData>,
+    /// channels are written grouped by kind, each group in order.
+    pub channels: Vec<(Channel, Vector)>,
+}
+
+impl Frame {
+    pub fn new(header: FrameHeader) -> Frame {
+        Frame {
+            header,
+            detectors: Vec::new(),
+            history: Vec::new(),
+            raw: None,
+            channels: Vec::new(),
+        }
+    }
+}
+
+/// Compression selects how the writer stores FrVect data.  Levels are gzip
+/// levels from 0 to 9.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Default)]
+pub enum Compression {
+    #[default]
+    Raw,
+    Gzip(u32),
+    /// DiffGzip gzips the differences between integers; other data is gzipped.
+    DiffGzip(u32),
+    /// ZeroSuppress zero suppresses 2, 4 and 8 byte integers; other data is raw.
+    ZeroSuppress,
+    /// Auto uses the smallest of the schemes that apply to the data, or raw
+    /// when none of them is smaller.
+    Auto(u32),
+}
+
+/// Compression parses from `raw`, `zero_suppress`, or `gzip`, `diff_gzip`
+/// and `auto` with an optional level such as `gzip:9`.  The level is 6 by
+/// default.
+impl FromStr for Compression {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Compression> {
+        let invalid = || {
+            Error::new(
+                ErrorKind::InvalidInput,
+                format!("unknown compression: {}", s),
+            )
+        };
+        let (scheme, level) = match s.split_once(':') {
+            Some((scheme, level)) => (scheme, Some(level.parse().map_err(|_| invalid())?)),
+            None => (s, None),
+        };
+        if level.is_some_and(|l: u32| l > 9) {
+            return Err(invalid());
+        }
+        match (scheme, level) {
+            ("raw", None) => Ok(Compression::Raw),
+            ("zero_suppress", None) => Ok(Compression::ZeroSuppress),
+            ("gzip", l) => Ok(Compression::Gzip(l.unwrap_or(6))),
+            ("diff_gzip", l) => Ok(Compression::DiffGzip(l.unwrap_or(6))),
+            ("auto", l) => Ok(Compression::Auto(l.unwrap_or(6))),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+/// Writer writes little endian version 8 frame files with CRC checksums.
+/// Every frame is written as it is given; finish writes the table of
+/// contents and the end of file.
+///
+/// ```no_run
+/// # use gwf::writer::{Compression, Frame, Writer};
+/// # fn frame() -> Frame { unimplemented!() }
+/// let file = std::fs::File::create("H-TEST-1000000000-1.gwf")?;
+/// let mut writer = Writer::new(std::io::BufWriter::new(file))?;
+/// writer.compress(Compression::Auto(6));
+/// writer.write_frame(&frame())?;
+/// writer.finish()?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub struct Writer<W: Write> {
+    writer: W,
+    /// position is the number of bytes written; positions in the table of
+    /// contents are from the start of the file.
+    position: u64,
+    file_sum: Cksum,
+    header_sum: u32,
+    /// leap_seconds is the GPS-UTC offset of the first frame.
+    leap_seconds: i16,
+    /// instances is the next instance of each structure class.
+    instances: [u32; CLASSES],
+    frames: Vec<TocFrame>,
+    /// first is the first FrAdcData and FrSerData instance of each frame.
+    first: Vec<(u32, u32)>,
+    detectors: Vec<(String, u64)>,
+    channels: Vec<TocEntry>,
+    index: HashMap<(String, ChannelKind), usize>,
+    compression: Compression,
+    /// channel_compression overrides compression for the named channels.
+    channel_compression: HashMap<String, Compression>,
+}
+
+/// TocEntry is a channel in the table of contents.
+struct TocEntry {
+    name: String,
+    kind: ChannelKind,
+    /// ids are the channel number and group of an ADC.
+    ids: (u32, u32),
+    positions: Vec<u64>,
+}
+
+const CLASSES: usize = Structures::FrVect as usize + 1;
+
+/// STRUCT_SUM_SIZE is the length of the checksum that ends every structure.
+const STRUCT_SUM_SIZE: usize = core::mem::size_of::<u32>();
+
+impl<W: Write> Writer<W> {
+    /// new writes the file header and the dictionary.
+    pub fn new(writer: W) -> Result<Writer<W>> {
+        let mut w = Writer {
+            writer,
+            position: 0,
+            file_sum: Cksum::new(),
+            header_sum: 0,
+            leap_seconds: 0,
+            instances: [0; CLASSES],
+            frames: Vec::new(),
+            first: Vec::new(),
+            detectors: Vec::new(),
+            channels: Vec::new(),
+            index: HashMap::new(),
+            compression: Compression::default(),
+            channel_compression: HashMap::new(),
+        };
+
+        let mut header = Vec::with_capacity(Header::size_of());
+        header.extend_from_slice(b"IGWD\0");
+        header.push(u8::from(Major::Release8));
+        header.push(0); // minor version
+        header.extend_from_slice(&[2, 4, 8, 4, 8]);
+        header.extend_from_slice(&0x1234u16.to_le_bytes());
+        header.extend_from_slice(&0x1234_5678u32.to_le_bytes());
+        header.extend_from_slice(&0x0123_4567_89ab_cdefu64.to_le_bytes());
+        header.extend_from_slice(&std::f32::consts::PI.to_le_bytes());
+        header.extend_from_slice(&std::f64::consts::PI.to_le_bytes());
+        header.push(u8::from(Library::LibUnknown(0)));
+        header.push(1); // Checksum::SumCRC
+        w.header_sum = cksum(&header);
+        w.emit(&header)?;
+
+        for (structure, name, elements) in DICTIONARY.iter() {
+            let mut e = Encoder::default();
+            e.string(name)?;
+            e.u16(*structure as u16);
+            e.string("")?;
+            w.structure(Structures::FrSH, e)?;
+            for (name, class) in elements.iter() {
+                let mut e = Encoder::default();
+                e.string(name)?;
+                e.string(class)?;
+                e.string("")?;
+                w.structure(Structures::FrSE, e)?;
+            }
+        }
+        Ok(w)
+    }
+
+    /// compress sets the compression of every channel without its own.
+    pub fn compress(&mut self, compression: Compression) {
+        self.compression = compression;
+    }
+
+    /// compress_channel sets the compression of the named channel.
+    pub fn compress_channel(&mut self, name: &str, compression: Compression) {
+        self.channel_compression
+            .insert(name.to_string(), compression);
+    }
+
+    pub fn write_frame(&mut self, frame: &Frame) -> Result<()> {
+        let kind = |k: ChannelKind| -> Vec<&(Channel, Vector)> {
+            frame
+                .channels
+                .iter()
+                .filter(|(c, _)| c.kind() == k)
+                .collect()
+        };
+        let adcs = kind(ChannelKind::ADC);
+        let serials = kind(ChannelKind::Serial);
+        let procs = kind(ChannelKind::PostProcessed);
+        let sims = kind(ChannelKind::Simulation);
+        let raw = match &frame.raw {
+            Some(raw) => Some(raw.clone()),
+            None if !adcs.is_empty() || !serials.is_empty() => Some(RawData {
+                name: "rawData".to_string(),
+            }),
+            None => None,
+        };
+
+        if self.frames.is_empty() {
+            self.leap_seconds = frame.header.gps_leap_s as i16;
+        }
+        for entry in self.channels.iter_mut() {
+            entry.positions.push(0);
+        }
+
+        let h = &frame.header;
+        let mut e = Encoder::default();
+        e.string(&h.name)?;
+        e.i32(h.run);
+        e.u32(h.frame);
+        e.u32(h.data_quality.0);
+        e.u32(h.gps_start_time_s);
+        e.u32(h.gps_residual_time_ns);
+        e.u16(h.gps_leap_s);
+        e.f64(h.frame_length_s);
+        e.reference(Reference::default()); // type
+        e.reference(Reference::default()); // user
+        e.reference(Reference::default()); // detectSim
+        e.reference(self.first(Structures::FrDetector, frame.detectors.len()));
+        e.reference(self.first(Structures::FrHistory, frame.history.len()));
+        e.reference(self.first(Structures::FrRawData, raw.is_some() as usize));
+        e.reference(self.first(Structures::FrProcData, procs.len()));
+        e.reference(self.first(Structures::FrSimData, sims.len()));
+        for _ in 0..5 {
+            // event, simEvent, summaryData, auxData and auxTable
+            e.reference(Reference::default());
+        }
+        let position = self.structure(Structures::FrameH, e)?;
+        self.frames.push(TocFrame {
+            run: h.run,
+            frame: h.frame,
+            data_quality: h.data_quality,
+            gps_start_time_s: h.gps_start_time_s,
+            gps_residual_time_ns: h.gps_residual_time_ns,
+            frame_length_s: h.frame_length_s,
+            position,
+        });
+        self.first.push((
+            self.instances[Structures::FrAdcData as usize],
+            self.instances[Structures::FrSerData as usize],
+        ));
+
+        for (i, d) in frame.detectors.iter().enumerate() {
+            let mut e = Encoder::default();
+            e.string(&d.name)?;
+            e.buf.extend(d.prefix.iter().map(|c| *c as u8));
+            e.f64(d.longitude_radians);
+            e.f64(d.latitude_radians);
+            e.f32(d.elevation_meters);
+            e.f32(d.arm_x_azimuth_radians);
+            e.f32(d.arm_y_azimuth_radians);
+            e.f32(d.arm_x_altitude_radians);
+            e.f32(d.arm_y_altitude_radians);
+            e.f32(d.arm_x_midpoint_meters);
+            e.f32(d.arm_y_midpoint_meters);
+            e.i32(d.local_time_utc_offset_s);
+            e.reference(Reference::default()); // aux
+            e.reference(Reference::default()); // table
+            e.reference(self.next(Structures::FrDetector, i + 1 < frame.detectors.len()));
+            let position = self.structure(Structures::FrDetector, e)?;
+            if !self.detectors.iter().any(|(name, _)| *name == d.name) {
+                self.detectors.push((d.name.clone(), position));
+            }
+        }
+
+        for (i, h) in frame.history.iter().enumerate() {
+            let mut e = Encoder::default();
+            e.string(&h.name)?;
+            e.u32(h.gps_time_s);
+            e.string(&h.comment)?;
+            e.reference(self.next(Structures::FrHistory, i + 1 < frame.history.len()));
+            self.structure(Structures::FrHistory, e)?;
+        }
+
+        if let Some(raw) = raw {
+            let mut e = Encoder::default();
+            e.string(&raw.name)?;
+            e.reference(self.first(Structures::FrSerData, serials.len()));
+            e.reference(self.first(Structures::FrAdcData, adcs.len()));
+            e.reference(Reference::default()); // firstTable
+            e.reference(Reference::default()); // logMsg
+            e.reference(Reference::default()); // more
+            self.structure(Structures::FrRawData, e)?;
+        }
+
+        for group in [adcs, serials, procs, sims].iter() {
+            for (i, (channel, data)) in group.iter().enumerate() {
+                self.channel(channel, data, i + 1 < group.len())?;
+            }
+        }
+
+        let mut e = Encoder::default();
+        e.i32(h.run);
+        e.u32(h.frame);
+        e.u32(h.gps_start_time_s);
+        e.u32(h.gps_residual_time_ns);
+        self.structure(Structures::FrEndOfFrame, e)?;
+        Ok(())
+    }
+
+    /// channel writes the channel's structure followed by its data vector.
+    fn channel(&mut self, channel: &Channel, data: &Vector, more: bool) -> Result<()> {
+        let vector = self.first(Structures::FrVect, 1);
+        let mut e = Encoder::default();
+        let (structure, ids) = match channel {
+            Channel::ADC(a) => {
+                e.string(&a.name)?;
+                e.string(&a.comment)?;
+                e.u32(a.channel_group);
+                e.u32(a.channel_number);
+                e.u32(a.num_bits);
+                e.f32(a.bias);
+                e.f32(a.slope);
+                e.string(a.units.as_deref().unwrap_or("NONE"))?;
+                e.f64(a.sample_rate);
+                e.f64(a.time_offset_s);
+                e.f64(a.f_shift);
+                e.f32(a.phase);
+                e.u16(a.data_valid.0);
+                e.reference(vector);
+                e.reference(Reference::default()); // aux
+                e.reference(self.next(Structures::FrAdcData, more));
+                (Structures::FrAdcData, (a.channel_number, a.channel_group))
+            }
+            Channel::Serial(s) => {
+                e.string(&s.name)?;
+                e.u32(s.gps_time_s);
+                e.u32(s.gps_residual_time_ns);
+                e.f64(s.sample_rate);
+                e.string(&s.data)?;
+                e.reference(vector);
+                e.reference(Reference::default()); // table
+                e.reference(self.next(Structures::FrSerData, more));
+                (Structures::FrSerData, (0, 0))
+            }
+            Channel::PostProcessed(p) => {
+                e.string(&p.name)?;
+                e.string(&p.comment)?;
+                e.u16(u16::from(p.data_type));
+                e.u16(u16::from(p.sub_type));
+                e.f64(p.time_offset_s);
+                e.f64(p.time_range_s);
+                e.f64(p.f_shift);
+                e.f32(p.phase);
+                e.f64(p.frequency_range);
+                e.f64(p.bandwidth);
+                e.u16(count(p.auxiliary_parameters.len(), "auxiliary parameters")?);
+                for a in p.auxiliary_parameters.iter() {
+                    e.f64(a.value);
+                }
+                for a in p.auxiliary_parameters.iter() {
+                    e.string(&a.name)?;
+                }
+                e.reference(vector);
+                e.reference(Reference::default()); // aux
+                e.reference(Reference::default()); // table
+                e.reference(Reference::default()); // history
+                e.reference(self.next(Structures::FrProcData, more));
+                (Structures::FrProcData, (0, 0))
+            }
+            Channel::Simulation(s) => {
+                e.string(&s.name)?;
+                e.string(&s.comment)?;
+                e.f64(s.sample_rate);
+                e.f64(s.time_offset_s);
+                e.f64(s.f_shift);
+                e.f32(s.phase);
+                e.reference(vector);
+                e.reference(Reference::default()); // input
+                e.reference(Reference::default()); // table
+                e.reference(self.next(Structures::FrSimData, more));
+                (Structures::FrSimData, (0, 0))
+            }
+        };
+        let position = self.structure(structure, e)?;
+
+        let key = (channel.name().to_string(), channel.kind());
+        let frames = self.frames.len();
+        let i = match self.index.get(&key) {
+            Some(i) => *i,
+            None => {
+                self.index.insert(key, self.channels.len());
+                self.channels.push(TocEntry {
+                    name: channel.name().to_string(),
+                    kind: channel.kind(),
+                    ids,
+                    positions: vec![0; frames],
+                });
+                self.channels.len() - 1
+            }
+        };
+        self.channels[i].positions[frames - 1] = position;
+
+        let compression = match self.channel_compression.get(channel.name()) {
+            Some(c) => *c,
+            None => self.compression,
+        };
+        self.vector(data, compression)
+    }
+
+    fn vector(&mut self, v: &Vector, compression: Compression) -> Result<()> {
+        let info = v.info();
+        let axes = info.axes();
+        let samples: usize = axes.iter().map(|a| a.length).product();
+        if samples != v.len() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "{}: dimensions of {} samples for a vector of {}",
+                    info.name,
+                    samples,
+                    v.len()
+                ),
+            ));
+        }
+        let (class, data) = encode(v)?;
+        let (scheme, data) = compress(data, class, compression, Endian::Little)?;
+
+        let mut e = Encoder::default();
+        e.string(&info.name)?;
+        e.u16(scheme.compression(Endian::Little));
+        e.u16(class);
+        e.u64(v.len() as u64);
+        e.u64(data.len() as u64);
+        e.buf.extend_from_slice(&data);
+        e.u32(axes.len() as u32);
+        for a in axes.iter() {
+            e.u64(a.length as u64);
+        }
+        for a in axes.iter() {
+            e.f64(a.spacing);
+        }
+        for a in axes.iter() {
+            e.f64(a.origin);
+        }
+        for a in axes.iter() {
+            e.string(&a.unit)?;
+        }
+        e.string(&info.unit_y)?;
+        e.reference(Reference::default()); // next
+        self.structure(Structures::FrVect, e)?;
+        Ok(())
+    }
+
+    /// finish writes the table of contents and the end of file and returns
+    /// the underlying writer.
+    pub fn finish(mut self) -> Result<W> {
+        let mut e = Encoder::default();
+        e.i16(self.leap_seconds);
+        e.u32(self.frames.len() as u32);
+        for f in self.frames.iter() {
+            e.u32(f.data_quality.0);
+        }
+        for f in self.frames.iter() {
+            e.u32(f.gps_start_time_s);
+        }
+        for f in self.frames.iter() {
+            e.u32(f.gps_residual_time_ns);
+        }
+        for f in self.frames.iter() {
+            e.f64(f.frame_length_s);
+        }
+        for f in self.frames.iter() {
+            e.i32(f.run);
+        }
+        for f in self.frames.iter() {
+            e.u32(f.frame);
+        }
+        for f in self.frames.iter() {
+            e.u64(f.position);
+        }
+        for (adc, _) in self.first.iter() {
+            e.u64(*adc as u64);
+        }
+        for (_, ser) in self.first.iter() {
+            e.u64(*ser as u64);
+        }
+        for _ in 0..2 * self.frames.len() {
+            // nFirstTable and nFirstMsg
+            e.u64(0);
+        }
+
+        e.u32(DICTIONARY.len() as u32);
+        for (structure, _, _) in DICTIONARY.iter() {
+            e.u16(*structure as u16);
+        }
+        for (_, name, _) in DICTIONARY.iter() {
+            e.string(name)?;
+        }
+
+        e.u32(self.detectors.len() as u32);
+        for (name, _) in self.detectors.iter() {
+            e.string(name)?;
+        }
+        for (_, position) in self.detectors.iter() {
+            e.u64(*position);
+        }
+
+        e.u32(0); // nStatType
+        e.u32(0); // nTotalStat
+
+        for kind in [
+            ChannelKind::ADC,
+            ChannelKind::PostProcessed,
+            ChannelKind::Simulation,
+            ChannelKind::Serial,
+        ]
+        .iter()
+        {
+            let channels: Vec<&TocEntry> =
+                self.channels.iter().filter(|c| c.kind == *kind).collect();
+            e.u32(channels.len() as u32);
+            for c in channels.iter() {
+                e.string(&c.name)?;
+            }
+            if *kind == ChannelKind::ADC {
+                for c in channels.iter() {
+                    e.u32(c.ids.0);
+                }
+                for c in channels.iter() {
+                    e.u32(c.ids.1);
+                }
+            }
+            for c in channels.iter() {
+                for p in c.positions.iter() {
+                    e.u64(*p);
+                }
+            }
+        }
+
+        e.u32(0); // nSummary
+        e.u32(0); // nEventType
+        e.u32(0); // nTotalEvent
+        e.u32(0); // nSimEventType
+        e.u32(0); // nTotalSEvent
+        let toc = self.structure(Structures::FrTOC, e)?;
+
+        let length = Common::size_of() as u64 + 32;
+        let file_len = self.position + length;
+        let mut eof = Vec::with_capacity(length as usize);
+        eof.extend_from_slice(&length.to_le_bytes());
+        eof.push(1);
+        eof.push(Structures::FrEndOfFile as u8);
+        eof.extend_from_slice(&self.instances[Structures::FrEndOfFile as usize].to_le_bytes());
+        eof.extend_from_slice(&(self.frames.len() as u32).to_le_bytes());
+        eof.extend_from_slice(&file_len.to_le_bytes());
+        eof.extend_from_slice(&(file_len - toc).to_le_bytes());
+        eof.extend_from_slice(&self.header_sum.to_le_bytes());
+        let sum = cksum(&eof);
+        eof.extend_from_slice(&sum.to_le_bytes());
+        let mut file_sum = self.file_sum;
+        file_sum.update(&eof);
+        eof.extend_from_slice(&file_sum.sum().to_le_bytes());
+        self.emit(&eof)?;
+
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+
+    /// first refers to the next structure of class when n are about to be written.
+    fn first(&self, class: Structures, n: usize) -> Reference {
+        if n == 0 {
+            return Reference::default();
+        }
+        Reference {
+            class: class as u16,
+            instance: self.instances[class as usize],
+        }
+    }
+
+    /// next refers to the structure of class after the one about to be written.
+    fn next(&self, class: Structures, more: bool) -> Reference {
+        if !more {
+            return Reference::default();
+        }
+        Reference {
+            class: class as u16,
+            instance: self.instances[class as usize] + 1,
+        }
+    }
+
+    /// structure writes the common header, body and checksum of a structure
+    /// and returns its position.
+    fn structure(&mut self, class: Structures, body: Encoder) -> Result<u64> {
+        let length = Common::size_of() + body.buf.len() + STRUCT_SUM_SIZE;
+        let instance = self.instances[class as usize];
+        self.instances[class as usize] += 1;
+
+        let mut buf = Vec::with_capacity(length);
+        buf.extend_from_slice(&(length as u64).to_le_bytes());
+        buf.push(1); // chkType: CRC
+        buf.push(class as u8);
+        buf.extend_from_slice(&instance.to_le_bytes());
+        buf.extend_from_slice(&body.buf);
+        let sum = cksum(&buf);
+        buf.extend_from_slice(&sum.to_le_bytes());
+
+        let position = self.position;
+        self.emit(&buf)?;
+        Ok(position)
+    }
+
+    fn emit(&mut self, buf: &[u8]) -> Result<()> {
+        self.writer.write_all(buf)?;
+        self.file_sum.update(buf);
+        self.position += buf.len() as u64;
+        Ok(())
+    }
+}
+
+/// Encoder builds the body of a structure in little endian order.
+#[derive(Default)]
+struct Encoder {
+    buf: Vec<u8>,
+}
+
+impl Encoder {
+    fn u16(&mut self, v: u16) {
+        self.buf.extend_from_slice(&v.to_le_bytes());
+    }
+
+    fn i16(&mut self, v: i16) {
+        self.buf.extend_from_slice(&v.to_le_bytes());
+    }
+
+    fn u32(&mut self, v: u32) {
+        self.buf.extend_from_slice(&v.to_le_bytes());
+    }
+
+    fn i32(&mut self, v: i32) {
+        self.buf.extend_from_slice(&v.to_le_bytes());
+    }
+
+    fn u64(&mut self, v: u64) {
+        self.buf.extend_from_slice(&v.to_le_bytes());
+    }
+
+    fn f32(&mut self, v: f32) {
+        self.buf.extend_from_slice(&v.to_le_bytes());
+    }
+
+    fn f64(&mut self, v: f64) {
+        self.buf.extend_from_slice(&v.to_le_bytes());
+    }
+
+    /// string writes the length including the null terminator, the bytes and the terminator.
+    fn string(&mut self, s: &str) -> Result<()> {
+        if s.contains('\0') {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("{:?}: strings cannot contain null characters", s),
+            ));
+        }
+        self.u16(count(s.len() + 1, "string bytes")?);
+        self.buf.extend_from_slice(s.as_bytes());
+        self.buf.push(0);
+        Ok(())
+    }
+
+    fn reference(&mut self, r: Reference) {
+        self.u16(r.class);
+        self.u32(r.instance);
+    }
+}
+
+/// count checks that n fits in the 16 bit length of an array.
+fn count(n: usize, what: &str) -> Result<u16> {
+    if n > u16::MAX as usize {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("{} {} exceeds {}", n, what, u16::MAX),
+        ));
+    }
+    Ok(n as u16)
+}
+
+/// encode returns the FrVect type and the little endian bytes of the data.
+fn encode(v: &Vector) -> Result<(u16, Vec<u8>)> {
+    macro_rules! le_bytes {
+        ($data:expr) => {
+            $data.iter().flat_map(|x| x.to_le_bytes()).collect()
+        };
+    }
+    Ok(match v {
+        Vector::I8(_, d) => (0, d.iter().map(|x| *x as u8).collect()),
+        Vector::I16(_, d) => (1, le_bytes!(d)),
+        Vector::F64(_, d) => (2, le_bytes!(d)),
+        Vector::F32(_, d) => (3, le_bytes!(d)),
+        Vector::I32(_, d) => (4, le_bytes!(d)),
+        Vector::I64(_, d) => (5, le_bytes!(d)),
+        Vector::Complexes(_, d) => (
+            7,
+            d.iter()
+                .flat_map(|c| {
+                    let mut b = [0; 16];
+                    b[..8].copy_from_slice(&c.real.to_le_bytes());
+                    b[8..].copy_from_slice(&c.imaginary.to_le_bytes());
+                    b
+                })
+                .collect(),
+        ),
+        Vector::Strings(_, d) => {
+            let mut e = Encoder::default();
+            for s in d.iter() {
+                e.string(s)?;
+            }
+            (8, e.buf)
+        }
+        Vector::U16(_, d) => (9, le_bytes!(d)),
+        Vector::U32(_, d) => (10, le_bytes!(d)),
+        Vector::U64(_, d) => (11, le_bytes!(d)),
+        Vector::U8(_, d) => (12, d.clone()),
+    })
+}
+
+/// Elements are the name and type of each element of a structure.
+type Elements = &'static [(&'static str, &'static str)];
+
+/// DICTIONARY describes every structure the writer emits: its class, name
+/// and the name and type of each element.
+const DICTIONARY: [(Structures, &str, Elements); 14] = [
+    (
+        Structures::FrSH,
+        "FrSH",
+        &[
+            ("name", "STRING"),
+            ("class", "INT_2U"),
+            ("comment", "STRING"),
+            ("chkSum", "INT_4U"),
+        ],
+    ),
+    (
+        Structures::FrSE,
+        "FrSE",
+        &[
+            ("name", "STRING"),
+            ("class", "STRING"),
+            ("comment", "STRING"),
+            ("chkSum", "INT_4U"),
+        ],
+    ),
+    (
+        Structures::FrameH,
+        "FrameH",
+        &[
+            ("name", "STRING"),
+            ("run", "INT_4S"),
+            ("frame", "INT_4U"),
+            ("dataQuality", "INT_4U"),
+            ("GTimeS", "INT_4U"),
+            ("GTimeN", "INT_4U"),
+            ("ULeapS", "INT_2U"),
+            ("dt", "REAL_8"),
+            ("type", "PTR_STRUCT(FrVect *)"),
+            ("user", "PTR_STRUCT(FrVect *)"),
+            ("detectSim", "PTR_STRUCT(FrDetector *)"),
+            ("detectProc", "PTR_STRUCT(FrDetector *)"),
+            ("history", "PTR_STRUCT(FrHistory *)"),
+            ("rawData", "PTR_STRUCT(FrRawData *)"),
+            ("procData", "PTR_STRUCT(FrProcData *)"),
+            ("simData", "PTR_STRUCT(FrSimData *)"),
+            ("event", "PTR_STRUCT(FrEvent *)"),
+            ("simEvent", "PTR_STRUCT(FrSimEvent *)"),
+            ("summaryData", "PTR_STRUCT(FrSummary *)"),
+            ("auxData", "PTR_STRUCT(FrVect *)"),
+            ("auxTable", "PTR_STRUCT(FrTable *)"),
+            ("chkSum", "INT_4U"),
+        ],
+    ),
+    (
+        Structures::FrAdcData,
+        "FrAdcData",
+        &[
+            ("name", "STRING"),
+            ("comment", "STRING"),
+            ("channelGroup", "INT_4U"),
+            ("channelNumber", "INT_4U"),
+            ("nBits", "INT_4U"),
+            ("bias", "REAL_4"),
+            ("slope", "REAL_4"),
+            ("units", "STRING"),
+            ("sampleRate", "REAL_8"),
+            ("timeOffset", "REAL_8"),
+            ("fShift", "REAL_8"),
+            ("phase", "REAL_4"),
+            ("dataValid", "INT_2U"),
+            ("data", "PTR_STRUCT(FrVect *)"),
+            ("aux", "PTR_STRUCT(FrVect *)"),
+            ("next", "PTR_STRUCT(FrAdcData *)"),
+            ("chkSum", "INT_4U"),
+        ],
+    ),
+    (
+        Structures::FrDetector,
+        "FrDetector",
+        &[
+            ("name", "STRING"),
+            ("prefix", "CHAR[2]"),
+            ("longitude", "REAL_8"),
+            ("latitude", "REAL_8"),
+            ("elevation", "REAL_4"),
+            ("armXazimuth", "REAL_4"),
+            ("armYazimuth", "REAL_4"),
+            ("armXaltitude", "REAL_4"),
+            ("armYaltitude", "REAL_4"),
+            ("armXmidpoint", "REAL_4"),
+            ("armYmidpoint", "REAL_4"),
+            ("localTime", "INT_4S"),
+            ("aux", "PTR_STRUCT(FrVect *)"),
+            ("table", "PTR_STRUCT(FrTable *)"),
+            ("next", "PTR_STRUCT(FrDetector *)"),
+            ("chkSum", "INT_4U"),
+        ],
+    ),
+    (
+        Structures::FrEndOfFile,
+        "FrEndOfFile",
+        &[
+            ("nFrames", "INT_4U"),
+            ("nBytes", "INT_8U"),
+            ("seekTOC", "INT_8U"),
+            ("chkSumFrHeader", "INT_4U"),
+            ("chkSum", "INT_4U"),
+            ("chkSumFile", "INT_4U"),
+        ],
+    ),
+    (
+        Structures::FrEndOfFrame,
+        "FrEndOfFrame",
+        &[
+            ("run", "INT_4S"),
+            ("frame", "INT_4U"),
+            ("GTimeS", "INT_4U"),
+            ("GTimeN", "INT_4U"),
+            ("chkSum", "INT_4U"),
+        ],
+    ),
+    (
+        Structures::FrHistory,
+        "FrHistory",
+        &[
+            ("name", "STRING"),
+            ("time", "INT_4U"),
+            ("comment", "STRING"),
+            ("next", "PTR_STRUCT(FrHistory *)"),
+            ("chkSum", "INT_4U"),
+        ],
+    ),
+    (
+        Structures::FrProcData,
+        "FrProcData",
+        &[
+            ("name", "STRING"),
+            ("comment", "STRING"),
+            ("type", "INT_2U"),
+            ("subType", "INT_2U"),
+            ("timeOffset", "REAL_8"),
+            ("tRange", "REAL_8"),
+            ("fShift", "REAL_8"),
+            ("phase", "REAL_4"),
+            ("fRange", "REAL_8"),
+            ("BW", "REAL_8"),
+            ("nAuxParam", "INT_2U"),
+            ("auxParam", "REAL_8[nAuxParam]"),
+            ("auxParamNames", "STRING[nAuxParam]"),
+            ("data", "PTR_STRUCT(FrVect *)"),
+            ("aux", "PTR_STRUCT(FrVect *)"),
+            ("table", "PTR_STRUCT(FrTable *)"),
+            ("history", "PTR_STRUCT(FrHistory *)"),
+            ("next", "PTR_STRUCT(FrProcData *)"),
+            ("chkSum", "INT_4U"),
+        ],
+    ),
+    (
+        Structures::FrRawData,
+        "FrRawData",
+        &[
+            ("name", "STRING"),
+            ("firstSer", "PTR_STRUCT(FrSerData *)"),
+            ("firstAdc", "PTR_STRUCT(FrAdcData *)"),
+            ("firstTable", "PTR_STRUCT(FrTable *)"),
+            ("logMsg", "PTR_STRUCT(FrMsg *)"),
+            ("more", "PTR_STRUCT(FrVect *)"),
+            ("chkSum", "INT_4U"),
+        ],
+    ),
+    (
+        Structures::FrSerData,
+        "FrSerData",
+        &[
+            ("name", "STRING"),
+            ("timeSec", "INT_4U"),
+            ("timeNsec", "INT_4U"),
+            ("sampleRate", "REAL_8"),
+            ("data", "STRING"),
+            ("serial", "PTR_STRUCT(FrVect *)"),
+            ("table", "PTR_STRUCT(FrTable *)"),
+            ("next", "PTR_STRUCT(FrSerData *)"),
+            ("chkSum", "INT_4U"),
+        ],
+    ),
+    (
+        Structures::FrSimData,
+        "FrSimData",
+        &[
+            ("name", "STRING"),
+            ("comment", "STRING"),
+            ("sampleRate", "REAL_8"),
+            ("timeOffset", "REAL_8"),
+            ("fShift", "REAL_8"),
+            ("phase", "REAL_4"),
+            ("data", "PTR_STRUCT(FrVect *)"),
+            ("input", "PTR_STRUCT(FrVect *)"),
+            ("table", "PTR_STRUCT(FrTable *)"),
+            ("next", "PTR_STRUCT(FrSimData *)"),
+            ("chkSum", "INT_4U"),
+        ],
+    ),
+    (
+        Structures::FrTOC,
+        "FrTOC",
+        &[
+            ("ULeapS", "INT_2S"),
+            ("nFrame", "INT_4U"),
+            ("dataQuality", "INT_4U[nFrame]"),
+            ("GTimeS", "INT_4U[nFrame]"),
+            ("GTimeN", "INT_4U[nFrame]"),
+            ("dt", "REAL_8[nFrame]"),
+            ("runs", "INT_4S[nFrame]"),
+            ("frame", "INT_4U[nFrame]"),
+            ("positionH", "INT_8U[nFrame]"),
+            ("nFirstADC", "INT_8U[nFrame]"),
+            ("nFirstSer", "INT_8U[nFrame]"),
+            ("nFirstTable", "INT_8U[nFrame]"),
+            ("nFirstMsg", "INT_8U[nFrame]"),
+            ("nSH", "INT_4U"),
+            ("SHid", "INT_2U[nSH]"),
+            ("SHname", "STRING[nSH]"),
+            ("nDetector", "INT_4U"),
+            ("nameDetector", "STRING[nDetector]"),
+            ("positionDetector", "INT_8U[nDetector]"),
+            ("nStatType", "INT_4U"),
+            ("nameStat", "STRING[nStatType]"),
+            ("detector", "STRING[nStatType]"),
+            ("nStatInstance", "INT_4U[nStatType]"),
+            ("nTotalStat", "INT_4U"),
+            ("tStart", "INT_4U[nTotalStat]"),
+            ("tEnd", "INT_4U[nTotalStat]"),
+            ("version", "INT_4U[nTotalStat]"),
+            ("positionStat", "INT_8U[nTotalStat]"),
+            ("nADC", "INT_4U"),
+            ("name", "STRING[nADC]"),
+            ("channelID", "INT_4U[nADC]"),
+            ("groupID", "INT_4U[nADC]"),
+            ("positionADC", "INT_8U[nADC][nFrame]"),
+            ("nProc", "INT_4U"),
+            ("nameProc", "STRING[nProc]"),
+            ("positionProc", "INT_8U[nProc][nFrame]"),
+            ("nSim", "INT_4U"),
+            ("nameSim", "STRING[nSim]"),
+            ("positionSim", "INT_8U[nSim][nFrame]"),
+            ("nSer", "INT_4U"),
+            ("nameSer", "STRING[nSer]"),
+            ("positionSer", "INT_8U[nSer][nFrame]"),
+            ("nSummary", "INT_4U"),
+            ("nameSum", "STRING[nSummary]"),
+            ("positionSum", "INT_8U[nSummary][nFrame]"),
+            ("nEventType", "INT_4U"),
+            ("nameEvent", "STRING[nEventType]"),
+            ("nEvent", "INT_4U[nEventType]"),
+            ("nTotalEvent", "INT_4U"),
+            ("GTimeSEvent", "INT_4U[nTotalEvent]"),
+            ("GTimeNEvent", "INT_4U[nTotalEvent]"),
+            ("amplitudeEvent", "REAL_4[nTotalEvent]"),
+            ("positionEvent", "INT_8U[nTotalEvent]"),
+            ("nSimEventType", "INT_4U"),
+            ("nameSimEvent", "STRING[nSimEventType]"),
+            ("nSimEvent", "INT_4U[nSimEventType]"),
+            ("nTotalSEvent", "INT_4U"),
+            ("GTimeSSim", "INT_4U[nTotalSEvent]"),
+            ("GTimeNSim", "INT_4U[nTotalSEvent]"),
+            ("amplitudeSimEvent", "REAL_4[nTotalSEvent]"),
+            ("positionSimEvent", "INT_8U[nTotalSEvent]"),
+            ("chkSum", "INT_4U"),
+        ],
+    ),
+    (
+        Structures::FrVect,
+        "FrVect",
+        &[
+            ("name", "STRING"),
+            ("compress", "INT_2U"),
+            ("type", "INT_2U"),
+            ("nData", "INT_8U"),
+            ("nBytes", "INT_8U"),
+            ("data", "CHAR[nBytes]"),
+            ("nDim", "INT_4U"),
+            ("nx", "INT_8U[nDim]"),
+            ("dx", "REAL_8[nDim]"),
+            ("startX", "REAL_8[nDim]"),
+            ("unitX", "STRING[nDim]"),
+            ("unitY", "STRING"),
+            ("next", "PTR_STRUCT(FrVect *)"),
+            ("chkSum", "INT_4U"),
+        ],
+    ),
+];
+
+#[cfg(test)]
+mod writer_tests {
+    use super::*;
+    use crate::fixtures::{self, write};
+    use crate::handler::Handler;
+    use std::convert::TryInto;
+    use std::io::Cursor;
+
+    fn frame(frame: u32, with_sim: bool) -> Frame {
+        let mut f = Frame::new(FrameHeader {
+            run: -1,
+            data_quality: DataQuality(frame),
+            ..fixtures::header(frame)
+        });
+        f.detectors.push(Detector {
+            name: "LHO_4k".to_string(),
+            prefix: [b'H' as i8, b'1' as i8],
+            longitude_radians: -2.08,
+            latitude_radians: 0.81,
+            elevation_meters: 142.5,
+            arm_x_azimuth_radians: 5.65,
+            arm_y_azimuth_radians: 4.08,
+            arm_x_altitude_radians: -0.0006,
+            arm_y_altitude_radians: 0.00001,
+            arm_x_midpoint_meters: 1997.5,
+            arm_y_midpoint_meters: 1997.5,
+            local_time_utc_offset_s: -25200,
+        });
+        f.history.push(History {
+            name: "gwf".to_string(),
+            gps_time_s: 1_000_000_000,
+            comment: "written by the tests".to_string(),
+        });
+        f.channels.push((
+            Channel::ADC(ADC {
+                channel_group: 1,
+                channel_number: 2,
+                ..fixtures::adc("H1:ADC")
+            }),
+            Vector::I16(fixtures::info("H1:ADC", 4), vec![1, -2, 3, frame as i16]),
+        ));
+        f.channels.push((
+            Channel::PostProcessed(PostProcessed {
+                name: "H1:PROC".to_string(),
+                comment: "strain".to_string(),
+                data_type: DataType::TimeSeries,
+                sub_type: SubType::Unknown,
+                time_offset_s: 0.0,
+                time_range_s: 1.0,
+                f_shift: 0.0,
+                phase: 0.0,
+                frequency_range: 2.0,
+                bandwidth: 0.0,
+                auxiliary_parameters: vec![AuxiliaryParameter {
+                    value: 1.5,
+                    name: "gain".to_string(),
+                }],
+            }),
+            Vector::F64(fixtures::info("H1:PROC", 4), vec![0.5, 1.5, -2.5, 1e-21]),
+        ));
+        f.channels.push((
+            Channel::Serial(Serial {
+                name: "H1:SER".to_string(),
+                gps_time_s: 1_000_000_000 + frame,
+                gps_residual_time_ns: 5,
+                sample_rate: 1.0,
+                data: "on".to_string(),
+            }),
+            Vector::Strings(
+                fixtures::info("H1:SER", 2),
+                vec!["a".to_string(), "bc".to_string()],
+            ),
+        ));
+        if with_sim {
+            f.channels.push((
+                Channel::Simulation(Simulation {
+                    name: "H1:SIM".to_string(),
+                    comment: String::new(),
+                    sample_rate: 4.0,
+                    time_offset_s: 0.0,
+                    f_shift: 0.0,
+                    phase: 0.0,
+                }),
+                Vector::Complexes(
+                    fixtures::info("H1:SIM", 1),
+                    vec![Complex {
+                        real: 1.0,
+                        imaginary: -1.0,
+                    }],
+                ),
+            ));
+        }
+        f
+    }
+
+    #[derive(Default)]
+    struct Reader {
+        frames: Vec<Frame>,
+    }
+
+    impl Handler for Reader {
+        fn begin_frame(&mut self) -> Option<fn(&mut Self, header: FrameHeader)> {
+            Some(|r, h| r.frames.push(Frame::new(h)))
+        }
+
+        fn detector(&mut self) -> Option<fn(&mut Self, data: Detector)> {
+            Some(|r, d| r.frames.last_mut().unwrap().detectors.push(d))
+        }
+
+        fn history(&mut self) -> Option<fn(&mut Self, data: History)> {
+            Some(|r, h| r.frames.last_mut().unwrap().history.push(h))
+        }
+
+        fn raw(&mut self) -> Option<fn(&mut Self, data: RawData)> {
+            Some(|r, raw| r.frames.last_mut().unwrap().raw = Some(raw))
+        }
+
+        fn channel(&mut self) -> Option<fn(&mut Self, channel: Channel, data: Vector)> {
+            Some(|r, c, v| r.frames.last_mut().unwrap().channels.push((c, v)))
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut frames = vec![frame(0, true), frame(1, false)];
+        let buf = write(&frames);
+
+        let mut reader = Reader::default();
+        crate::parse(&mut Cursor::new(&buf), &mut reader).unwrap();
+        for f in frames.iter_mut() {
+            f.raw = Some(RawData {
+                name: "rawData".to_string(),
+            });
+            // channels are grouped by kind: ADC, serial, processed then simulated.
+            f.channels.swap(1, 2);
+        }
+        assert_eq!(reader.frames, frames);
+    }
+
+    #[test]
+    fn test_toc() {
+        let buf = write(&[frame(0, true), frame(1, false)]);
+        let toc = crate::toc::read(&mut Cursor::new(&buf)).unwrap().unwrap();
+        assert_eq!(toc.leap_seconds, 18);
+        assert_eq!(toc.frames.len(), 2);
+        assert_eq!(toc.frames[1].gps_start_time_s, 1_000_000_001);
+        assert_eq!(
+            &buf[toc.frames[1].position as usize + 9],
+            &(Structures::FrameH as u8)
+        );
+
+        let names: Vec<(&str, ChannelKind)> = toc
+            .channels
+            .iter()
+            .map(|c| (c.name.as_str(), c.kind))
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                ("H1:ADC", ChannelKind::ADC),
+                ("H1:PROC", ChannelKind::PostProcessed),
+                ("H1:SIM", ChannelKind::Simulation),
+                ("H1:SER", ChannelKind::Serial),
+            ]
+        );
+        assert_eq!(toc.channels[2].frames(), 1);
+        for c in toc.channels.iter() {
+            let class = match c.kind {
+                ChannelKind::ADC => Structures::FrAdcData,
+                ChannelKind::PostProcessed => Structures::FrProcData,
+                ChannelKind::Simulation => Structures::FrSimData,
+                ChannelKind::Serial => Structures::FrSerData,
+            };
+            assert_eq!(buf[c.positions[0] as usize + 9], class as u8);
+        }
+
+        let inventory = crate::inventory::read(&mut Cursor::new(&buf)).unwrap();
+        assert_eq!(inventory.frames, 2);
+        assert_eq!(inventory.channels.len(), 4);
+        assert_eq!(inventory.channels[0].data_type, "i16");
+        assert_eq!(inventory.channels[0].frames, 2);
+        assert_eq!(inventory.channels[1].sample_rate, 4.0);
+        assert_eq!(inventory.channels[2].data_type, "complex");
+        assert_eq!(inventory.channels[2].frames, 1);
+    }
+
+    #[derive(Default)]
+    struct Ends {
+        frames: Vec<EndOfFrame>,
+        toc: Option<crate::toc::Toc>,
+        file: Option<EndOfFile>,
+    }
+
+    impl Handler for Ends {
+        fn end_frame(&mut self) -> Option<fn(&mut Self, data: EndOfFrame)> {
+            Some(|e, f| e.frames.push(f))
+        }
+
+        fn toc(&mut self) -> Option<fn(&mut Self, data: crate::toc::Toc)> {
+            Some(|e, t| e.toc = Some(t))
+        }
+
+        fn end_of_file(&mut self) -> Option<fn(&mut Self, data: EndOfFile)> {
+            Some(|e, f| e.file = Some(f))
+        }
+    }
+
+    #[test]
+    fn test_end_structures() {
+        let buf = write(&[frame(0, true), frame(1, false)]);
+        let mut ends = Ends::default();
+        crate::parse(&mut Cursor::new(&buf), &mut ends).unwrap();
+
+        let frames: Vec<(u32, u32)> = ends
+            .frames
+            .iter()
+            .map(|f| (f.frame, f.gps_time_s))
+            .collect();
+        assert_eq!(frames, vec![(0, 1_000_000_000), (1, 1_000_000_001)]);
+        assert_eq!(ends.toc, crate::toc::read(&mut Cursor::new(&buf)).unwrap());
+        let file = ends.file.unwrap();
+        assert_eq!(file.num_frames, 2);
+        assert_eq!(file.num_bytes, buf.len() as u64);
+        assert_ne!(file.seek_toc, 0);
+    }
+
+    #[test]
+    fn test_dimensions() {
+        let mut f = frame(0, false);
+        f.channels[0].1 = Vector::I16(fixtures::info("H1:ADC", 3), vec![1, 2, 3, 4]);
+        let mut w = Writer::new(Vec::new()).unwrap();
+        let err = w.write_frame(&f).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+
+        let mut w = Writer::new(Vec::new()).unwrap();
+        w.write_frame(&frame(0, false)).unwrap();
+        assert!(!w.finish().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_compression() {
+        let frame = frame(0, true);
+        let mut w = Writer::new(Cursor::new(Vec::new())).unwrap();
+        w.compress(Compression::Gzip(9));
+        w.compress_channel("H1:ADC", Compression::ZeroSuppress);
+        w.write_frame(&frame).unwrap();
+        let buf = w.finish().unwrap().into_inner();
+
+        let mut reader = Reader::default();
+        crate::parse(&mut Cursor::new(&buf), &mut reader).unwrap();
+        let mut schemes = Vec::new();
+        for (channel, data) in reader.frames[0].channels.iter() {
+            let (_, want) = frame
+                .channels
+                .iter()
+                .find(|(c, _)| c.name() == channel.name())
+                .unwrap();
+            assert_eq!(data.len(), want.len());
+            assert_eq!(encode(data).unwrap(), encode(want).unwrap());
+            schemes.push(Scheme::from_compression(data.info().compression).unwrap());
+        }
+        assert_eq!(
+            schemes,
+            vec![
+                Scheme::ZeroSuppress2,
+                Scheme::Raw,
+                Scheme::Gzip,
+                Scheme::Gzip
+            ]
+        );
+    }
+
+    #[test]
+    fn test_checksums() {
+        let buf = write(&[frame(0, true)]);
+        let n = buf.len();
+        let sum = |b: &[u8]| u32::from_le_bytes([b[0], b[1], b[2], b[3]]);
+        assert_eq!(sum(&buf[n - 4..]), cksum(&buf[..n - 4]));
+        assert_eq!(sum(&buf[n - 8..]), cksum(&buf[n - 46..n - 8]));
+        assert_eq!(sum(&buf[n - 12..]), cksum(&buf[..Header::size_of()]));
+
+        // the first structure after the file header is the FrSH of FrSH.
+        let start = Header::size_of();
+        let len = u64::from_le_bytes(buf[start..start + 8].try_into().unwrap()) as usize;
+        let end = start + len;
+        assert_eq!(sum(&buf[end - 4..]), cksum(&buf[start..end - 4]));
+    }
+}
@@ -76,8 +76,8 @@ mod tests {
                         gwf::structures::PostProcessed {
                             name: "H1:TEST-STRAIN".to_string(),
                             comment: "None".to_string(),
-                            data_type: 1,
-                            sub_type: 0,
+                            data_type: gwf::structures::DataType::TimeSeries,
+                            sub_type: gwf::structures::SubType::Unknown,
                             time_offset_s: 0.0,
                             time_range_s: 128.0,
                             f_shift: 0.0,
@@ -101,7 +101,7 @@ mod tests {
                                 name: "gwpy".to_string(),
                                 run: 0,
                                 frame: 0,
-                                data_quality: 4005673240,
+                                data_quality: gwf::structures::DataQuality(4005673240),
                                 gps_start_time_s: 0,
                                 gps_residual_time_ns: 0,
                                 gps_leap_s: 19,
@@ -114,7 +114,7 @@ mod tests {
                                 name: "gwpy".to_string(),
                                 run: 0,
                                 frame: 32650,
-                                data_quality: 4005673240,
+                                data_quality: gwf::structures::DataQuality(4005673240),
                                 gps_start_time_s: 0,
                                 gps_residual_time_ns: 0,
                                 gps_leap_s: 19,
@@ -140,6 +140,7 @@ mod tests {
                                     x_origins: vec![0.0],
                                     unit_x_scale_factors: vec![String::from("s")],
                                     unit_y: String::from(""),
+                                    compression: 257,
                                 }
                             );
                             assert!(
@@ -181,8 +182,8 @@ mod tests {
                         gwf::structures::PostProcessed {
                             name: "H1:TEST-STRAIN".to_string(),
                             comment: "None".to_string(),
-                            data_type: 1,
-                            sub_type: 0,
+                            data_type: gwf::structures::DataType::TimeSeries,
+                            sub_type: gwf::structures::SubType::Unknown,
                             time_offset_s: 0.0,
                             time_range_s: 128.0,
                             f_shift: 0.0,
@@ -206,7 +207,7 @@ mod tests {
                                 name: "gwpy".to_string(),
                                 run: 0,
                                 frame: 0,
-                                data_quality: 11,
+                                data_quality: gwf::structures::DataQuality(11),
                                 gps_start_time_s: 0,
                                 gps_residual_time_ns: 0,
                                 gps_leap_s: 19,
@@ -219,7 +220,7 @@ mod tests {
                                 name: "gwpy".to_string(),
                                 run: 0,
                                 frame: 32650,
-                                data_quality: 4005673240,
+                                data_quality: gwf::structures::DataQuality(4005673240),
                                 gps_start_time_s: 0,
                                 gps_residual_time_ns: 0,
                                 gps_leap_s: 19,
@@ -245,6 +246,7 @@ mod tests {
                                     x_origins: vec![0.0],
                                     unit_x_scale_factors: vec![String::from("s")],
                                     unit_y: String::from(""),
+                                    compression: 257,
                                 }
                             );
                             assert!(
@@ -286,8 +288,8 @@ mod tests {
                         gwf::structures::PostProcessed {
                             name: "H1:TEST-STRAIN".to_string(),
                             comment: "None".to_string(),
-                            data_type: 1,
-                            sub_type: 0,
+                            data_type: gwf::structures::DataType::TimeSeries,
+                            sub_type: gwf::structures::SubType::Unknown,
                             time_offset_s: 0.0,
                             time_range_s: 128.0,
                             f_shift: 0.0,
@@ -311,7 +313,7 @@ mod tests {
                                 name: "gwpy".to_string(),
                                 run: 0,
                                 frame: 0,
-                                data_quality: 11,
+                                data_quality: gwf::structures::DataQuality(11),
                                 gps_start_time_s: 0,
                                 gps_residual_time_ns: 0,
                                 gps_leap_s: 19,
@@ -324,7 +326,7 @@ mod tests {
                                 name: "gwpy".to_string(),
                                 run: 0,
                                 frame: 32650,
-                                data_quality: 4005673240,
+                                data_quality: gwf::structures::DataQuality(4005673240),
                                 gps_start_time_s: 0,
                                 gps_residual_time_ns: 0,
                                 gps_leap_s: 19,
@@ -350,6 +352,7 @@ mod tests {
                                     x_origins: vec![0.0],
                                     unit_x_scale_factors: vec![String::from("s")],
                                     unit_y: String::from(""),
+                                    compression: 257,
                                 }
                             );
                             assert!(
@@ -391,8 +394,8 @@ mod tests {
                         gwf::structures::PostProcessed {
                             name: "H1:TEST-STRAIN".to_string(),
                             comment: "None".to_string(),
-                            data_type: 1,
-                            sub_type: 0,
+                            data_type: gwf::structures::DataType::TimeSeries,
+                            sub_type: gwf::structures::SubType::Unknown,
                             time_offset_s: 0.0,
                             time_range_s: 128.0,
                             f_shift: 0.0,
@@ -416,7 +419,7 @@ mod tests {
                                 name: "gwpy".to_string(),
                                 run: 0,
                                 frame: 0,
-                                data_quality: 11,
+                                data_quality: gwf::structures::DataQuality(11),
                                 gps_start_time_s: 0,
                                 gps_residual_time_ns: 0,
                                 gps_leap_s: 19,
@@ -429,7 +432,7 @@ mod tests {
                                 name: "gwpy".to_string(),
                                 run: 0,
                                 frame: 32650,
-                                data_quality: 4005673240,
+                                data_quality: gwf::structures::DataQuality(4005673240),
                                 gps_start_time_s: 0,
                                 gps_residual_time_ns: 0,
                                 gps_leap_s: 19,
@@ -455,6 +458,7 @@ mod tests {
                                     x_origins: vec![0.0],
                                     unit_x_scale_factors: vec![String::from("s")],
                                     unit_y: String::from(""),
+                                    compression: 257,
                                 }
                             );
                             assert!(
@@ -507,7 +511,7 @@ mod tests {
                                 name: "".to_string(),
                                 run: 1,
                                 frame: 1,
-                                data_quality: 0,
+                                data_quality: gwf::structures::DataQuality(0),
                                 gps_start_time_s: 0,
                                 gps_residual_time_ns: 0,
                                 gps_leap_s: 19,
@@ -545,6 +549,7 @@ mod tests {
                                 x_origins: vec![0.0,],
                                 unit_x_scale_factors: vec!["s".to_string(),],
                                 unit_y: "count".to_string(),
+                                compression: 257,
                             }
                         ),
                         1 => {
@@ -559,6 +564,7 @@ mod tests {
                                     x_origins: vec![0.0,],
                                     unit_x_scale_factors: vec!["s".to_string(),],
                                     unit_y: "count".to_string(),
+                                    compression: 257,
                                 }
                             );
                             assert!(